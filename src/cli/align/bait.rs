@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{AlignSeqInput, InputCli, OutputCli, args::align::AlignBaitArgs, collect_paths},
+    core::align::bait::{AlignmentBait, BaitParameters},
+    helper::{logger::AlignSeqLogger, types::DataType},
+};
+
+impl InputCli for BaitParser<'_> {}
+impl OutputCli for BaitParser<'_> {}
+impl AlignSeqInput for BaitParser<'_> {}
+
+pub(in crate::cli) struct BaitParser<'a> {
+    args: &'a AlignBaitArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> BaitParser<'a> {
+    pub(in crate::cli) fn new(args: &'a AlignBaitArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn design(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        assert!(
+            datatype == DataType::Dna,
+            "Bait design only supports DNA alignments"
+        );
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Bait design";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        AlignmentBait::new(&input_fmt, &self.args.output, &params).design(&files);
+    }
+
+    fn parse_params(&self) -> BaitParameters {
+        assert!(
+            self.args.overlap < self.args.bait_length,
+            "Bait overlap must be shorter than the bait length"
+        );
+        log::info!("{}", "Parameters".yellow());
+        log::info!("{:18}: {}", "Bait length", self.args.bait_length);
+        log::info!("{:18}: {}", "Overlap", self.args.overlap);
+        log::info!("{:18}: {}", "Min conservation", self.args.min_conservation);
+        log::info!("{:18}: {}", "Max missing data", self.args.max_missing);
+        log::info!(
+            "{:18}: {}-{}\n",
+            "GC content",
+            self.args.min_gc,
+            self.args.max_gc
+        );
+        BaitParameters {
+            bait_length: self.args.bait_length,
+            overlap: self.args.overlap,
+            min_conservation: self.args.min_conservation,
+            max_missing: self.args.max_missing,
+            min_gc: self.args.min_gc,
+            max_gc: self.args.max_gc,
+        }
+    }
+}
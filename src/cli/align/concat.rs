@@ -1,9 +1,19 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use indexmap::IndexSet;
+use regex::Regex;
 
 use crate::cli::args::align::AlignConcatArgs;
 use crate::cli::{collect_paths, AlignSeqInput, ConcatCli, InputCli, OutputCli};
 use crate::core::align::concat::AlignmentConcatenation;
+use crate::helper::concat::{
+    NameConflictPolicy, NameNormalization, NameRule, Supermatrix, TaxonList,
+};
 use crate::helper::logger::AlignSeqLogger;
+use crate::helper::types::PartitionFmt;
+use crate::helper::utils;
+use crate::parser::txt;
 
 impl ConcatCli for ConcatParser<'_> {}
 impl OutputCli for ConcatParser<'_> {}
@@ -40,8 +50,19 @@ impl<'a> ConcatParser<'a> {
             files.len(),
         )
         .log(task);
+        let supermatrix = self
+            .args
+            .append_to
+            .as_deref()
+            .map(|path| self.parse_supermatrix(path));
         let is_overwrite = self.args.io.force;
         self.check_output_dir_exist(&self.args.output, is_overwrite);
+        let normalization = self.parse_name_normalization();
+        let taxon_list = self
+            .args
+            .taxon_list
+            .as_deref()
+            .map(|path| self.parse_taxon_list(path));
 
         let mut concat = AlignmentConcatenation::new(
             &input_fmt,
@@ -50,6 +71,142 @@ impl<'a> ConcatParser<'a> {
             &part_fmt,
             &prefix,
         );
+        if let Some(normalization) = &normalization {
+            concat.set_name_normalization(normalization);
+        }
+        if let Some(taxon_list) = &taxon_list {
+            concat.set_taxon_list(taxon_list);
+        }
+        if let Some(supermatrix) = &supermatrix {
+            concat.set_supermatrix(supermatrix);
+        }
         concat.concat(&mut files, &datatype);
     }
+
+    // Rules are applied in order: suffix removal, regex, and case folding.
+    fn parse_name_normalization(&self) -> Option<NameNormalization> {
+        let mut rules = Vec::new();
+        if let Some(suffixes) = &self.args.strip_suffix {
+            suffixes
+                .iter()
+                .for_each(|suffix| rules.push(NameRule::StripSuffix(suffix.to_string())));
+        }
+        if let Some(re) = &self.args.name_regex {
+            let re = Regex::new(re).expect("Failed parsing name regex");
+            rules.push(NameRule::Regex(re));
+        }
+        if self.args.case_fold {
+            rules.push(NameRule::CaseFold);
+        }
+        if rules.is_empty() {
+            return None;
+        }
+        let policy: NameConflictPolicy = self
+            .args
+            .name_conflict
+            .parse()
+            .expect("Failed parsing name conflict policy");
+        log::info!("{}", "Name normalization".yellow());
+        if let Some(suffixes) = &self.args.strip_suffix {
+            log::info!("{:18}: {}", "Strip suffix", suffixes.join(", "));
+        }
+        if let Some(re) = &self.args.name_regex {
+            log::info!("{:18}: {}", "Name regex", re);
+        }
+        if self.args.case_fold {
+            log::info!("{:18}: {}", "Case fold", "lowercase");
+        }
+        log::info!("{:18}: {}\n", "Conflict policy", policy);
+        Some(NameNormalization { rules, policy })
+    }
+
+    fn parse_taxon_list(&self, path: &Path) -> TaxonList {
+        let taxa: IndexSet<String> = txt::parse_text_file(path)
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_string())
+            .collect();
+        assert!(!taxa.is_empty(), "Taxon list {} is empty", path.display());
+        log::info!("{}", "Taxon list".yellow());
+        log::info!("{:18}: {}", "File", path.display());
+        log::info!("{:18}: {}", "Taxon counts", utils::fmt_num(&taxa.len()));
+        log::info!("{:18}: {}\n", "Require all taxa", self.args.require_taxa);
+        TaxonList {
+            taxa: taxa.into_iter().collect(),
+            require_present: self.args.require_taxa,
+        }
+    }
+
+    fn parse_supermatrix(&self, path: &Path) -> Supermatrix {
+        let partition = self.args.append_partition.as_deref().unwrap_or(path);
+        let part_fmt = match &self.args.append_part_fmt {
+            Some(fmt) => self.parse_partition_fmt_std(fmt),
+            None => self.infer_supermatrix_part_fmt(partition),
+        };
+        self.check_supermatrix_paths(&[path, partition]);
+        log::info!("{}", "Append to".yellow());
+        log::info!("{:18}: {}", "Alignment", path.display());
+        log::info!("{:18}: {}", "Partition", partition.display());
+        log::info!("{:18}: {}\n", "Partition format", part_fmt);
+        Supermatrix {
+            alignment: path.to_path_buf(),
+            partition: partition.to_path_buf(),
+            part_fmt,
+        }
+    }
+
+    // Without a partition file, the partition must be
+    // embedded in a nexus alignment.
+    fn infer_supermatrix_part_fmt(&self, partition: &Path) -> PartitionFmt {
+        let ext = partition
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        if self.args.append_partition.is_none() {
+            return match ext.as_str() {
+                "nex" | "nexus" | "nxs" => PartitionFmt::Charset,
+                _ => panic!(
+                    "{} is not a nexus file with charset partitions. \
+                    Use --append-partition to specify its partition file",
+                    partition.display()
+                ),
+            };
+        }
+        match ext.as_str() {
+            "txt" | "raxml" => PartitionFmt::Raxml,
+            "nex" | "nexus" | "nxs" | "charset" => PartitionFmt::Nexus,
+            _ => panic!(
+                "Cannot infer partition format of {} from the file extension. \
+                Use --append-partition-format to specify the format",
+                partition.display()
+            ),
+        }
+    }
+
+    // The output directory is removed before writing,
+    // so the appended files must be outside of it.
+    fn check_supermatrix_paths(&self, paths: &[&Path]) {
+        let paths: Vec<PathBuf> = paths
+            .iter()
+            .map(|path| {
+                path.canonicalize()
+                    .unwrap_or_else(|_| panic!("Failed finding {}", path.display()))
+            })
+            .collect();
+        let output = match self.args.output.canonicalize() {
+            Ok(output) => output,
+            Err(_) => return,
+        };
+        paths.iter().for_each(|path| {
+            assert!(
+                !path.starts_with(&output),
+                "{} is inside the output directory {}. \
+                Move it or use a different output directory",
+                path.display(),
+                self.args.output.display()
+            );
+        });
+    }
 }
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+
+use crate::{
+    cli::{AlignSeqInput, InputCli, OutputCli, args::align::AlignCoordinateArgs, collect_paths},
+    core::align::coordinate::{CoordinateParameters, ReferenceCoordinate},
+    helper::{logger::AlignSeqLogger, types::PartitionFmt, utils},
+    parser::{partition::PartitionParser, txt},
+};
+
+impl InputCli for CoordinateParser<'_> {}
+impl OutputCli for CoordinateParser<'_> {}
+impl AlignSeqInput for CoordinateParser<'_> {}
+
+pub(in crate::cli) struct CoordinateParser<'a> {
+    args: &'a AlignCoordinateArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> CoordinateParser<'a> {
+    pub(in crate::cli) fn new(args: &'a AlignCoordinateArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn convert(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Reference coordinate mapping";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        ReferenceCoordinate::new(
+            &input_fmt,
+            &datatype,
+            &self.args.output,
+            &self.args.reference,
+            &params,
+        )
+        .convert(&files);
+    }
+
+    fn parse_params(&self) -> CoordinateParameters {
+        log::info!("{}", "Parameters".yellow());
+        log::info!("{:18}: {}", "Reference", self.args.reference);
+        if let Some(part_path) = &self.args.partition {
+            let part_fmt = self.parse_part_fmt(part_path);
+            let partitions = PartitionParser::new(part_path, &part_fmt, false).parse();
+            log::info!("{:18}: {}", "Partition", part_path.display());
+            log::info!(
+                "{:18}: {}\n",
+                "Partition counts",
+                utils::fmt_num(&partitions.len())
+            );
+            CoordinateParameters::Partition(partitions)
+        } else if let Some(site_path) = &self.args.sites {
+            let sites = self.parse_sites(site_path);
+            log::info!("{:18}: {}", "Sites", site_path.display());
+            log::info!("{:18}: {}\n", "Site counts", utils::fmt_num(&sites.len()));
+            CoordinateParameters::Sites(sites)
+        } else if self.args.pis {
+            log::info!("{:18}: {}\n", "Output", "Parsimony informative sites");
+            CoordinateParameters::ParsInf
+        } else {
+            log::info!("{:18}: {}\n", "Output", "Column map");
+            CoordinateParameters::Map
+        }
+    }
+
+    fn parse_sites(&self, path: &Path) -> Vec<usize> {
+        txt::parse_text_file(path)
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let site = line
+                    .parse::<usize>()
+                    .unwrap_or_else(|_| panic!("Invalid site {} in {}", line, path.display()));
+                assert!(site > 0, "Sites must be 1-based column numbers");
+                site
+            })
+            .collect()
+    }
+
+    fn parse_part_fmt(&self, part_path: &Path) -> PartitionFmt {
+        let fmt = match &self.args.part_fmt {
+            Some(fmt) => fmt.as_str(),
+            None => part_path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .unwrap_or_default(),
+        };
+        match fmt {
+            "txt" | "raxml" => PartitionFmt::Raxml,
+            "nex" | "nexus" | "charset" => PartitionFmt::Nexus,
+            _ => panic!(
+                "Cannot infer partition format from the file extension. \
+                Please, specify using the --partition-format (or -p in short version) option"
+            ),
+        }
+    }
+}
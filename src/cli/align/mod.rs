@@ -1,7 +1,10 @@
+pub(crate) mod bait;
 pub(crate) mod concat;
 pub(crate) mod convert;
+pub(crate) mod coordinate;
 pub(crate) mod filter;
 pub(crate) mod partition;
+pub(crate) mod slice;
 pub(crate) mod split;
 pub(crate) mod summarize;
 pub(crate) mod trim;
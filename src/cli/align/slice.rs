@@ -0,0 +1,129 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{AlignSeqInput, InputCli, OutputCli, args::align::AlignSliceArgs, collect_paths},
+    core::align::slice::{AlignmentSlicing, SliceParameters},
+    helper::{logger::AlignSeqLogger, utils},
+    parser::bed::BedParser,
+};
+
+impl InputCli for SliceParser<'_> {}
+impl OutputCli for SliceParser<'_> {}
+impl AlignSeqInput for SliceParser<'_> {}
+
+pub(in crate::cli) struct SliceParser<'a> {
+    args: &'a AlignSliceArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> SliceParser<'a> {
+    pub(in crate::cli) fn new(args: &'a AlignSliceArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn slice(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        let output_fmt = self.parse_output_fmt(&self.args.out_fmt.output_fmt);
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Alignment slicing";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        AlignmentSlicing::new(
+            &input_fmt,
+            &datatype,
+            &self.args.output,
+            &output_fmt,
+            &params,
+        )
+        .slice(&files);
+    }
+
+    fn parse_params(&self) -> SliceParameters {
+        log::info!("{}", "Regions".yellow());
+        match &self.args.bed {
+            Some(bed) => {
+                let records = BedParser::new(bed)
+                    .parse()
+                    .expect("Failed parsing BED file");
+                let reference = self
+                    .args
+                    .reference
+                    .as_ref()
+                    .expect("Reference sequence ID is required for BED regions");
+                log::info!("{:18}: {}", "BED file", bed.display());
+                log::info!("{:18}: {}", "Reference", reference);
+                log::info!(
+                    "{:18}: {}\n",
+                    "Region counts",
+                    utils::fmt_num(&records.len())
+                );
+                SliceParameters::Bed {
+                    records,
+                    reference: reference.to_string(),
+                }
+            }
+            None => {
+                let ranges: Vec<(usize, usize)> = self
+                    .args
+                    .range
+                    .as_ref()
+                    .expect("Column ranges are required")
+                    .iter()
+                    .map(|r| parse_range(r))
+                    .collect();
+                log::info!("{:18}: {}\n", "Range counts", utils::fmt_num(&ranges.len()));
+                SliceParameters::Range(ranges)
+            }
+        }
+    }
+}
+
+fn parse_range(range: &str) -> (usize, usize) {
+    let (start, end) = range
+        .split_once('-')
+        .unwrap_or_else(|| panic!("Invalid range {}. Use the format start-end", range));
+    let start = start
+        .trim()
+        .parse::<usize>()
+        .unwrap_or_else(|_| panic!("Invalid range start in {}", range));
+    let end = end
+        .trim()
+        .parse::<usize>()
+        .unwrap_or_else(|_| panic!("Invalid range end in {}", range));
+    assert!(
+        start > 0 && start <= end,
+        "Invalid range {}. The start must be at least 1 and not greater than the end",
+        range
+    );
+    (start, end)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!((100, 550), parse_range("100-550"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_parse_invalid_range() {
+        parse_range("550-100");
+    }
+}
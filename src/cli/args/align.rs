@@ -8,12 +8,27 @@ use super::{CommonConcatArgs, CommonSeqInput, CommonSeqOutput, IOArgs};
 
 #[derive(Subcommand)]
 pub(crate) enum AlignmentSubcommand {
+    #[command(
+        about = "Design capture baits from conserved alignment windows",
+        name = "bait"
+    )]
+    Bait(AlignBaitArgs),
     #[command(about = "Concatenate alignments", name = "concat")]
     Concat(AlignConcatArgs),
+    #[command(
+        about = "Map alignment columns to reference coordinates",
+        name = "coordinate"
+    )]
+    Coordinate(AlignCoordinateArgs),
     #[command(about = "Convert sequence formats", name = "convert")]
     Convert(AlignConvertArgs),
     #[command(about = "Filter alignments", name = "filter")]
     Filter(AlignFilterArgs),
+    #[command(
+        about = "Slice alignments by column ranges or BED regions",
+        name = "slice"
+    )]
+    Slice(AlignSliceArgs),
     #[command(about = "Split alignment by partitions", name = "split")]
     Split(AlignSplitArgs),
     #[command(about = "Compute Alignment Statistics", name = "summary")]
@@ -30,6 +45,52 @@ pub(crate) enum PartitionSubcommand {
     Convert(PartitionArgs),
 }
 
+#[derive(Args)]
+pub(crate) struct AlignBaitArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[arg(short, long, help = "Output path", default_value = "Align-Bait")]
+    pub(crate) output: PathBuf,
+    #[arg(
+        long = "bait-length",
+        help = "Bait length in alignment columns",
+        default_value_t = 120
+    )]
+    pub(crate) bait_length: usize,
+    #[arg(
+        long = "overlap",
+        help = "Number of columns shared by adjacent baits",
+        default_value_t = 60
+    )]
+    pub(crate) overlap: usize,
+    #[arg(
+        long = "min-conservation",
+        help = "Minimum proportion of conserved sites in a bait",
+        default_value_t = 0.8
+    )]
+    pub(crate) min_conservation: f64,
+    #[arg(
+        long = "max-missing",
+        help = "Maximum mean proportion of missing data in a bait",
+        default_value_t = 0.2
+    )]
+    pub(crate) max_missing: f64,
+    #[arg(
+        long = "min-gc",
+        help = "Minimum GC content of a bait",
+        default_value_t = 0.3
+    )]
+    pub(crate) min_gc: f64,
+    #[arg(
+        long = "max-gc",
+        help = "Maximum GC content of a bait",
+        default_value_t = 0.7
+    )]
+    pub(crate) max_gc: f64,
+}
+
 #[derive(Args)]
 pub(crate) struct AlignConcatArgs {
     #[command(flatten)]
@@ -42,8 +103,102 @@ pub(crate) struct AlignConcatArgs {
     pub(crate) concat: CommonConcatArgs,
     #[arg(short, long, help = "Output path", default_value = "Align-Concat")]
     pub(crate) output: PathBuf,
-    #[arg(long = "sort", help = "Sort sequences by IDs alphabetically")]
+    #[arg(
+        long = "sort",
+        help = "Sort sequences by IDs alphabetically",
+        conflicts_with("taxon_list")
+    )]
     pub(crate) sort: bool,
+    #[arg(
+        long = "taxon-list",
+        help = "Input a text file of taxa to include, one per line. \
+            Taxa are written in the list order"
+    )]
+    pub(crate) taxon_list: Option<PathBuf>,
+    #[arg(
+        long = "require-taxa",
+        help = "Stop if a listed taxon is absent from all alignments",
+        requires("taxon_list")
+    )]
+    pub(crate) require_taxa: bool,
+    #[arg(
+        long = "append-to",
+        help = "Append the alignments as new loci to an existing concatenated alignment"
+    )]
+    pub(crate) append_to: Option<PathBuf>,
+    #[arg(
+        long = "append-partition",
+        help = "Partition file of the existing concatenated alignment. \
+            Required unless the alignment is a nexus file with charset partitions",
+        requires("append_to")
+    )]
+    pub(crate) append_partition: Option<PathBuf>,
+    #[arg(
+        long = "append-partition-format",
+        help = "Partition format of the existing concatenated alignment. \
+            Inferred from the file extension if not specified",
+        requires("append_to"),
+        value_parser = builder::PossibleValuesParser::new(["charset", "nexus", "raxml"]),
+    )]
+    pub(crate) append_part_fmt: Option<String>,
+    #[arg(
+        long = "strip-suffix",
+        help = "Remove suffixes from taxon names before concatenation",
+        num_args(1..),
+    )]
+    pub(crate) strip_suffix: Option<Vec<String>>,
+    #[arg(
+        long = "name-regex",
+        help = "Normalize taxon names to the first capture group of a regex"
+    )]
+    pub(crate) name_regex: Option<String>,
+    #[arg(long = "case-fold", help = "Convert taxon names to lowercase")]
+    pub(crate) case_fold: bool,
+    #[arg(
+        long = "name-conflict",
+        help = "Resolve sequences in a locus normalized to the same taxon name",
+        default_value = "error",
+        value_parser = builder::PossibleValuesParser::new(["longest", "least-missing", "error"]),
+    )]
+    pub(crate) name_conflict: String,
+}
+
+#[derive(Args)]
+pub(crate) struct AlignCoordinateArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[arg(short, long, help = "Output path", default_value = "Align-Coordinate")]
+    pub(crate) output: PathBuf,
+    #[arg(long = "reference", help = "Reference sequence ID")]
+    pub(crate) reference: String,
+    #[arg(
+        long = "partition",
+        help = "Convert a partition file to reference coordinates in BED",
+        conflicts_with_all(["sites", "pis"]),
+    )]
+    pub(crate) partition: Option<PathBuf>,
+    #[arg(
+        short = 'p',
+        long = "partition-format",
+        help = "Specify partition format",
+        requires("partition"),
+        value_parser = builder::PossibleValuesParser::new(["nexus", "raxml"]),
+    )]
+    pub(crate) part_fmt: Option<String>,
+    #[arg(
+        long = "sites",
+        help = "Convert a text file of 1-based columns, one per line, to reference coordinates in BED",
+        conflicts_with_all(["partition", "pis"]),
+    )]
+    pub(crate) sites: Option<PathBuf>,
+    #[arg(
+        long = "pis",
+        help = "Convert parsimony informative sites to reference coordinates in BED",
+        conflicts_with_all(["partition", "sites"]),
+    )]
+    pub(crate) pis: bool,
 }
 
 #[derive(Args)]
@@ -105,6 +260,35 @@ pub(crate) struct AlignFilterArgs {
     pub(crate) ids: Option<PathBuf>,
 }
 
+#[derive(Args)]
+pub(crate) struct AlignSliceArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[command(flatten)]
+    pub(crate) out_fmt: CommonSeqOutput,
+    #[arg(short, long, help = "Output path", default_value = "Align-Slice")]
+    pub(crate) output: PathBuf,
+    #[arg(
+        long = "range",
+        help = "Input 1-based inclusive column ranges, e.g. 100-550",
+        num_args(1..),
+        required_unless_present("bed"),
+        conflicts_with("bed"),
+    )]
+    pub(crate) range: Option<Vec<String>>,
+    #[arg(
+        long = "bed",
+        help = "Input a BED file of regions in reference coordinates. \
+            Regions are matched to alignments by file names",
+        requires("reference")
+    )]
+    pub(crate) bed: Option<PathBuf>,
+    #[arg(long = "reference", help = "Reference sequence ID for BED coordinates")]
+    pub(crate) reference: Option<String>,
+}
+
 #[derive(Args)]
 pub(crate) struct AlignSplitArgs {
     #[arg(short = 'i', long = "input", help = "Input partition path")]
@@ -1,82 +1,365 @@
-
-
 use std::path::PathBuf;
 
-use clap::{builder, Args, Subcommand};
 use clap::builder::TypedValueParser as _;
+use clap::{Args, Subcommand, builder};
 
-use crate::helper::types::{ContigFmt, SeqReadFmt, SummaryMode};
-
-use super::IOArgs;
+use crate::helper::types::{ContigFmt, QScoreEncoding, ReadConvertMode, SeqReadFmt, SummaryMode};
+use crate::stats::contigs::DEFAULT_LENGTH_THRESHOLDS;
 
+use super::{CommonSeqInput, IOArgs};
 
 #[derive(Subcommand)]
 pub(crate) enum SeqReadSubcommand {
     #[command(about = "Compute sequence read statistics", name = "summary")]
     RawSummary(SeqReadSummaryArgs),
+    #[command(about = "Filter and trim sequence reads", name = "filter")]
+    Filter(ReadFilterArgs),
+    #[command(about = "Subsample sequence reads", name = "subsample")]
+    Subsample(ReadSubsampleArgs),
+    #[command(about = "Convert and reorganize sequence read files", name = "convert")]
+    Convert(ReadConvertArgs),
 }
 
 #[derive(Subcommand)]
 pub(crate) enum ContigSubcommand {
     #[command(about = "Compute contig statistics", name = "summary")]
-    ContigSummary(ContigSummaryArgs),
+    Summary(ContigSummaryArgs),
+    #[command(about = "Filter contigs by length, N, and GC content", name = "filter")]
+    Filter(ContigFilterArgs),
+    #[command(about = "Rename contigs with sequential IDs", name = "rename")]
+    Rename(ContigRenameArgs),
+    #[command(about = "Index contig files in the FASTA index format", name = "index")]
+    Index(ContigIndexArgs),
+    #[command(about = "Extract regions from contig files", name = "extract")]
+    Extract(ContigExtractArgs),
 }
 
 #[derive(Subcommand)]
 pub(crate) enum MafSubcommand {
     #[command(about = "Convert genomic files to other formats", name = "maf")]
     Maf(MafConvertArgs),
+    #[command(
+        about = "Stitch MAF blocks into reference-ordered alignments",
+        name = "stitch"
+    )]
+    Stitch(MafStitchArgs),
+    #[command(about = "Summarize MAF blocks and species coverage", name = "summary")]
+    Summary(MafSummaryArgs),
+    #[command(
+        about = "Filter MAF blocks and project them onto a subset of species",
+        name = "filter"
+    )]
+    Filter(MafFilterArgs),
+    #[command(about = "Rename species in MAF files", name = "rename")]
+    Rename(MafRenameArgs),
+    #[command(
+        about = "Convert reference-anchored alignments to MAF",
+        name = "from-align"
+    )]
+    FromAlign(MafFromAlignArgs),
+    #[command(
+        about = "Index MAF blocks by reference coordinates for BED extraction",
+        name = "index"
+    )]
+    Index(MafIndexArgs),
 }
 
-
 #[derive(Args)]
 pub(crate) struct SeqReadSummaryArgs {
     #[command(flatten)]
     pub(crate) io: IOArgs,
     #[arg(
-        short = 'f', 
-        long ="input-format", 
-        help = "Specify input format", 
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
         default_value_t = SeqReadFmt::Auto,
-        value_parser = 
+        value_parser =
             builder::PossibleValuesParser::new(["auto","fastq","gzip"])
             .map(|x| x.parse::<SeqReadFmt>().expect("Invalid input format")),
     )]
     pub(crate) input_format: SeqReadFmt,
     #[arg(
-        long = "mode", 
-        help = "Summary mode", 
+        long = "mode",
+        help = "Summary mode",
         default_value_t = SummaryMode::Default,
-        value_parser = 
+        value_parser =
             builder::PossibleValuesParser::new(["minimal", "default", "complete"])
             .map(|x| x.parse::<SummaryMode>().unwrap()))]
     pub(crate) mode: SummaryMode,
-    #[arg(short = 'o', long = "output", help = "Output path", default_value = "Read-Summary")]
+    #[arg(
+        long = "qscore-encoding",
+        help = "Quality score encoding. Auto detects it from the first reads of each file",
+        default_value_t = QScoreEncoding::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto", "phred33", "phred64"])
+            .map(|x| x.parse::<QScoreEncoding>().unwrap()))]
+    pub(crate) qscore_encoding: QScoreEncoding,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Read-Summary"
+    )]
     pub(crate) output: PathBuf,
     #[arg(long = "prefix", help = "Specify prefix for output files")]
     pub(crate) prefix: Option<String>,
 }
 
+#[derive(Args)]
+pub(crate) struct ReadFilterArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
+        default_value_t = SeqReadFmt::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto","fastq","gzip"])
+            .map(|x| x.parse::<SeqReadFmt>().expect("Invalid input format")),
+    )]
+    pub(crate) input_format: SeqReadFmt,
+    #[arg(
+        long = "qscore-encoding",
+        help = "Quality score encoding. Auto detects it from the first reads of each file",
+        default_value_t = QScoreEncoding::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto", "phred33", "phred64"])
+            .map(|x| x.parse::<QScoreEncoding>().unwrap()))]
+    pub(crate) qscore_encoding: QScoreEncoding,
+    #[arg(long = "adapters", help = "Trim adapter sequences in a FASTA file")]
+    pub(crate) adapters: Option<PathBuf>,
+    #[arg(
+        long = "min-adapter-overlap",
+        help = "Minimum overlap to trim partial adapters at the 3' end",
+        default_value_t = 5
+    )]
+    pub(crate) min_adapter_overlap: usize,
+    #[arg(
+        long = "window-quality",
+        help = "Trim reads at the first window with a mean quality score below this value"
+    )]
+    pub(crate) window_quality: Option<f64>,
+    #[arg(
+        long = "window-size",
+        help = "Sliding window size for quality trimming",
+        default_value_t = 4
+    )]
+    pub(crate) window_size: usize,
+    #[arg(long = "min-length", help = "Minimum read length after trimming")]
+    pub(crate) min_length: Option<usize>,
+    #[arg(long = "max-n", help = "Maximum proportion of N bases in a read")]
+    pub(crate) max_n: Option<f64>,
+    #[arg(
+        long = "min-mean-quality",
+        help = "Minimum mean quality score of a read"
+    )]
+    pub(crate) min_mean_quality: Option<f64>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Read-Filter"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct ReadSubsampleArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
+        default_value_t = SeqReadFmt::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto","fastq","gzip"])
+            .map(|x| x.parse::<SeqReadFmt>().expect("Invalid input format")),
+    )]
+    pub(crate) input_format: SeqReadFmt,
+    #[arg(
+        long = "fraction",
+        help = "Proportion of reads to keep",
+        required_unless_present_any = ["count", "normalize"],
+        conflicts_with_all = ["count", "normalize"]
+    )]
+    pub(crate) fraction: Option<f64>,
+    #[arg(
+        long = "count",
+        help = "Number of reads to keep in each sample",
+        conflicts_with = "normalize"
+    )]
+    pub(crate) count: Option<usize>,
+    #[arg(
+        long = "normalize",
+        help = "Subsample all samples to the read count of the smallest sample"
+    )]
+    pub(crate) normalize: bool,
+    #[arg(long = "seed", help = "Seed of the random number generator")]
+    pub(crate) seed: Option<u64>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Read-Subsample"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct ReadConvertArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
+        default_value_t = SeqReadFmt::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto","fastq","gzip"])
+            .map(|x| x.parse::<SeqReadFmt>().expect("Invalid input format")),
+    )]
+    pub(crate) input_format: SeqReadFmt,
+    #[arg(
+        long = "mode",
+        help = "Specify conversion mode",
+        value_parser =
+            builder::PossibleValuesParser::new(["fasta","interleave","deinterleave","merge-lanes"])
+            .map(|x| x.parse::<ReadConvertMode>().expect("Invalid conversion mode")),
+    )]
+    pub(crate) mode: ReadConvertMode,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Read-Convert"
+    )]
+    pub(crate) output: PathBuf,
+}
+
 #[derive(Args)]
 pub(crate) struct ContigSummaryArgs {
     #[command(flatten)]
     pub(crate) io: IOArgs,
     #[arg(
-        short = 'f', 
-        long ="input-format", 
-        help = "Specify input format", 
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
         default_value_t = ContigFmt::Auto,
-        value_parser = 
+        value_parser =
             builder::PossibleValuesParser::new(["auto","fasta","gzip"])
             .map(|x| x.parse::<ContigFmt>().expect("Invalid input format")),
     )]
     pub(crate) input_format: ContigFmt,
-    #[arg(short = 'o', long = "output", help = "Output path", default_value = "Contig-Summary")]
+    #[arg(long = "genome-size", help = "Expected genome size to calculate NG50")]
+    pub(crate) genome_size: Option<usize>,
+    #[arg(
+        long = "length-thresholds",
+        help = "Count contigs longer than each length threshold",
+        num_args(1..),
+        default_values_t = DEFAULT_LENGTH_THRESHOLDS
+    )]
+    pub(crate) length_thresholds: Vec<usize>,
+    #[arg(long = "per-contig", help = "Write statistics of each contig")]
+    pub(crate) per_contig: bool,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Contig-Summary"
+    )]
     pub(crate) output: PathBuf,
     #[arg(long = "prefix", help = "Specify prefix for output files")]
     pub(crate) prefix: Option<String>,
 }
 
+#[derive(Args)]
+pub(crate) struct ContigFilterArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
+        default_value_t = ContigFmt::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto","fasta","gzip"])
+            .map(|x| x.parse::<ContigFmt>().expect("Invalid input format")),
+    )]
+    pub(crate) input_format: ContigFmt,
+    #[arg(long = "min-length", help = "Minimum contig length")]
+    pub(crate) min_length: Option<usize>,
+    #[arg(long = "max-length", help = "Maximum contig length")]
+    pub(crate) max_length: Option<usize>,
+    #[arg(long = "max-n", help = "Maximum proportion of N bases in a contig")]
+    pub(crate) max_n: Option<f64>,
+    #[arg(long = "min-gc", help = "Minimum GC content (0-1)")]
+    pub(crate) min_gc: Option<f64>,
+    #[arg(long = "max-gc", help = "Maximum GC content (0-1)")]
+    pub(crate) max_gc: Option<f64>,
+    #[arg(long = "top-n", help = "Keep only the N longest contigs in each file")]
+    pub(crate) top_n: Option<usize>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Contig-Filter"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct ContigRenameArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        short = 'f',
+        long ="input-format",
+        help = "Specify input format",
+        default_value_t = ContigFmt::Auto,
+        value_parser =
+            builder::PossibleValuesParser::new(["auto","fasta","gzip"])
+            .map(|x| x.parse::<ContigFmt>().expect("Invalid input format")),
+    )]
+    pub(crate) input_format: ContigFmt,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Contig-Rename"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct ContigIndexArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+}
+
+#[derive(Args)]
+pub(crate) struct ContigExtractArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        long = "region",
+        help = "Regions to extract, e.g. chr1:1001-2000 or chr1:1001-2000:-",
+        num_args(1..),
+        required_unless_present("bed")
+    )]
+    pub(crate) region: Option<Vec<String>>,
+    #[arg(long = "bed", help = "Extract regions in a BED file")]
+    pub(crate) bed: Option<PathBuf>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Contig-Extract"
+    )]
+    pub(crate) output: PathBuf,
+}
+
 #[derive(Args)]
 pub(crate) struct MafConvertArgs {
     #[command(flatten)]
@@ -86,16 +369,150 @@ pub(crate) struct MafConvertArgs {
     #[arg(long, help = "Source of names is a bed file")]
     pub(crate) from_bed: bool,
     #[arg(
-        short = 't', 
-        long = "output-format", 
-        help = "Specify output format", 
+        long = "species-names",
+        help = "Name sequences by species instead of the full MAF source name"
+    )]
+    pub(crate) species_names: bool,
+    #[arg(
+        short = 't',
+        long = "output-format",
+        help = "Specify output format",
         default_value = "fasta-int",
-        value_parser = 
+        value_parser =
             builder::PossibleValuesParser::new(["fasta", "phylip","fasta-int", "phylip-int"]),
     )]
     pub(crate) output_fmt: String,
-    #[arg(short = 'o', long = "output", help = "Output path", default_value = "Genomic-Convert")]
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Genomic-Convert"
+    )]
     pub(crate) output: PathBuf,
     #[arg(long = "prefix", help = "Specify prefix for output files")]
     pub(crate) prefix: Option<String>,
 }
+
+#[derive(Args)]
+pub(crate) struct MafStitchArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        long = "ref-species",
+        help = "Reference species name. Use the first sequence in each block if not specified"
+    )]
+    pub(crate) ref_species: Option<String>,
+    #[arg(
+        long = "bed",
+        help = "Write one alignment per BED region instead of per chromosome"
+    )]
+    pub(crate) bed: Option<PathBuf>,
+    #[arg(
+        short = 't',
+        long = "output-format",
+        help = "Specify output format",
+        default_value = "fasta",
+        value_parser =
+            builder::PossibleValuesParser::new(["fasta", "phylip", "nexus", "fasta-int", "phylip-int", "nexus-int"]),
+    )]
+    pub(crate) output_fmt: String,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Maf-Stitch"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct MafSummaryArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        long = "ref-species",
+        help = "Reference species name. Use the first sequence in each block if not specified"
+    )]
+    pub(crate) ref_species: Option<String>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Maf-Summary"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct MafFilterArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        long = "min-score",
+        help = "Keep blocks with a score at least this value",
+        required_unless_present_any(["min_length", "species", "project"])
+    )]
+    pub(crate) min_score: Option<f64>,
+    #[arg(
+        long = "min-length",
+        help = "Keep blocks with at least this number of columns"
+    )]
+    pub(crate) min_length: Option<usize>,
+    #[arg(long = "species", help = "Keep blocks containing all the species", num_args(1..))]
+    pub(crate) species: Option<Vec<String>>,
+    #[arg(long = "project", help = "Keep only sequences of the species in each block", num_args(1..))]
+    pub(crate) project: Option<Vec<String>>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Maf-Filter"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct MafRenameArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[arg(
+        long = "replace-id",
+        help = "Rename species using a csv or tsv file of original and new names"
+    )]
+    pub(crate) replace_id: PathBuf,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Maf-Rename"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct MafFromAlignArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[arg(long = "reference", help = "Reference sequence ID")]
+    pub(crate) reference: String,
+    #[arg(
+        long = "chrom-sizes",
+        help = "Chromosome sizes file (chrom<TAB>size) for the reference source sizes"
+    )]
+    pub(crate) chrom_sizes: Option<PathBuf>,
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "Output path",
+        default_value = "Maf-From-Align"
+    )]
+    pub(crate) output: PathBuf,
+}
+
+#[derive(Args)]
+pub(crate) struct MafIndexArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+}
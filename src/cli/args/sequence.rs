@@ -12,12 +12,19 @@ use super::{CommonSeqInput, CommonSeqOutput, IOArgs};
 pub(crate) enum SequenceSubcommand {
     #[command(about = "Add sequences to alignments", name = "add")]
     Add(SequenceAddArgs),
+    #[command(about = "Generate consensus sequences from alignments", name = "consensus")]
+    Consensus(SequenceConsensusArgs),
     #[command(about = "Extract sequence from alignments", name = "extract")]
     Extract(SequenceExtractArgs),
     #[command(about = "Filter sequence based on selected criteria", name = "filter")]
     Filter(SequenceFilterArgs),
     #[command(about = "Parse sample ID across multiple alignments", name = "id")]
     Id(SequenceIdArgs),
+    #[command(
+        about = "Detect outlier sequences based on pairwise distances",
+        name = "outlier"
+    )]
+    Outlier(SequenceOutlierArgs),
     #[command(about = "Remove sequence based on IDs", name = "remove")]
     Remove(SequenceRemoveArgs),
     #[command(
@@ -25,6 +32,11 @@ pub(crate) enum SequenceSubcommand {
         name = "rename"
     )]
     Rename(SequenceRenameArgs),
+    #[command(
+        about = "Select the most complete sample per species",
+        name = "select"
+    )]
+    Select(SequenceSelectArgs),
     #[command(about = "Translate DNA to amino acid sequences", name = "translate")]
     Translate(SequenceTranslateArgs),
 }
@@ -92,6 +104,44 @@ pub(crate) struct SequenceAddArgs {
     pub(crate) include_filename: bool,
 }
 
+#[derive(Args)]
+pub(crate) struct SequenceConsensusArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[command(flatten)]
+    pub(crate) out_fmt: CommonSeqOutput,
+    #[arg(short, long, help = "Output path", default_value = "Sequence-Consensus")]
+    pub(crate) output: PathBuf,
+    #[arg(
+        long = "threshold",
+        help = "Minimum combined frequency of characters in a site. \
+            Use the majority rule if not specified",
+    )]
+    pub(crate) threshold: Option<f64>,
+    #[arg(
+        long = "gap",
+        help = "Specify how to handle gaps",
+        default_value = "ignore",
+        value_parser = builder::PossibleValuesParser::new(["ignore", "include"]),
+    )]
+    pub(crate) gap: String,
+    #[arg(
+        long = "re",
+        help = "Group sequences using a regular expression. \
+            The group name is the first capture group",
+        require_equals = true,
+        conflicts_with("map"),
+    )]
+    pub(crate) re: Option<String>,
+    #[arg(
+        long = "map",
+        help = "Group sequences using a csv or tsv file of sequence IDs and group names",
+        conflicts_with("re"),
+    )]
+    pub(crate) map: Option<PathBuf>,
+}
 
 #[derive(Args)]
 pub(crate) struct SequenceExtractArgs {
@@ -162,6 +212,34 @@ pub(crate) struct SequenceIdArgs {
     pub(crate) map: bool,
 }
 
+#[derive(Args)]
+pub(crate) struct SequenceOutlierArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[command(flatten)]
+    pub(crate) out_fmt: CommonSeqOutput,
+    #[arg(short, long, help = "Output path", default_value = "Sequence-Outlier")]
+    pub(crate) output: PathBuf,
+    #[arg(
+        long = "method",
+        help = "Specify outlier detection method",
+        default_value = "iqr",
+        value_parser = builder::PossibleValuesParser::new(["iqr", "zscore"]),
+    )]
+    pub(crate) method: String,
+    #[arg(
+        long = "threshold",
+        help = "Specify IQR multiplier or z-score threshold. \
+            Default to 1.5 for iqr and 3.5 for zscore. \
+            The zscore method uses a modified z-score based on the median absolute deviation",
+    )]
+    pub(crate) threshold: Option<f64>,
+    #[arg(long = "remove", help = "Write alignments without the outlier sequences")]
+    pub(crate) remove: bool,
+}
+
 #[derive(Args)]
 pub(crate) struct SequenceRemoveArgs {
     #[command(flatten)]
@@ -244,6 +322,23 @@ pub(crate) struct SequenceRenameArgs {
     pub(crate) replace_to: Option<String>,
 }
 
+#[derive(Args)]
+pub(crate) struct SequenceSelectArgs {
+    #[command(flatten)]
+    pub(crate) io: IOArgs,
+    #[command(flatten)]
+    pub(crate) in_fmt: CommonSeqInput,
+    #[command(flatten)]
+    pub(crate) out_fmt: CommonSeqOutput,
+    #[arg(short, long, help = "Output path", default_value = "Sequence-Select")]
+    pub(crate) output: PathBuf,
+    #[arg(
+        long = "map",
+        help = "Input a csv or tsv file of sample IDs and species names",
+    )]
+    pub(crate) map: PathBuf,
+}
+
 #[derive(Args)]
 pub(crate) struct SequenceTranslateArgs {
     #[command(flatten)]
@@ -1,13 +1,21 @@
+use crate::cli::align::bait::BaitParser;
 use crate::cli::align::convert::ConvertParser;
+use crate::cli::align::coordinate::CoordinateParser;
 use crate::cli::align::filter::FilterParser;
 use crate::cli::align::partition::PartParser;
+use crate::cli::align::slice::SliceParser;
 use crate::cli::align::split::SplitParser;
 use crate::cli::align::summarize::SummaryParser;
+use crate::cli::read::convert::ReadConvertParser;
+use crate::cli::read::filter::ReadFilterParser;
+use crate::cli::read::subsample::ReadSubsampleParser;
 use crate::cli::read::summarize::ReadSummaryCliParser;
 use crate::cli::sequence::extract::ExtractParser;
 use crate::cli::sequence::id::IdParser;
+use crate::cli::sequence::outlier::OutlierParser;
 use crate::cli::sequence::remove::RemoveParser;
 use crate::cli::sequence::rename::RenameParser;
+use crate::cli::sequence::select::SelectParser;
 use crate::cli::sequence::translate::TranslateParser;
 
 use super::align::concat::ConcatParser;
@@ -17,9 +25,20 @@ use super::args::align::{AlignmentSubcommand, PartitionSubcommand};
 use super::args::genomics::{ContigSubcommand, MafSubcommand, SeqReadSubcommand};
 use super::args::sequence::SequenceSubcommand;
 use super::args::MainSubcommand;
+use super::contig::extract::ContigExtractParser;
+use super::contig::filter::ContigFilterParser;
+use super::contig::index::ContigIndexParser;
+use super::contig::rename::ContigRenameParser;
 use super::contig::summarize::ContigCliParser;
 use super::maf::convert::MafConvertParser;
+use super::maf::filter::MafFilterParser;
+use super::maf::from_align::FromAlignParser;
+use super::maf::index::MafIndexParser;
+use super::maf::rename::MafRenameParser;
+use super::maf::stitch::MafStitchParser;
+use super::maf::summary::MafSummaryParser;
 use super::sequence::addition::AdditionParser;
+use super::sequence::consensus::ConsensusParser;
 use super::sequence::filter::SequenceFilterParser;
 
 pub(crate) fn match_cli_subcommand(subcommand: &MainSubcommand) {
@@ -35,13 +54,22 @@ pub(crate) fn match_cli_subcommand(subcommand: &MainSubcommand) {
 
 fn match_contig_subcommand(subcommand: &ContigSubcommand) {
     match subcommand {
-        ContigSubcommand::ContigSummary(args) => ContigCliParser::new(args).summarize(),
+        ContigSubcommand::Summary(args) => ContigCliParser::new(args).summarize(),
+        ContigSubcommand::Filter(args) => ContigFilterParser::new(args).filter(),
+        ContigSubcommand::Rename(args) => ContigRenameParser::new(args).rename(),
+        ContigSubcommand::Index(args) => ContigIndexParser::new(args).index(),
+        ContigSubcommand::Extract(args) => ContigExtractParser::new(args).extract(),
     };
 }
 
 fn match_raw_read_subcommand(subcommand: &SeqReadSubcommand) {
     match subcommand {
         SeqReadSubcommand::RawSummary(raw_args) => ReadSummaryCliParser::new(raw_args).summarize(),
+        SeqReadSubcommand::Filter(filter_args) => ReadFilterParser::new(filter_args).filter(),
+        SeqReadSubcommand::Subsample(subsample_args) => {
+            ReadSubsampleParser::new(subsample_args).subsample()
+        }
+        SeqReadSubcommand::Convert(convert_args) => ReadConvertParser::new(convert_args).convert(),
     };
 }
 
@@ -50,6 +78,12 @@ fn match_genomic_subcommand(subcommand: &MafSubcommand) {
         MafSubcommand::Maf(maf_args) => {
             MafConvertParser::new(maf_args).convert();
         }
+        MafSubcommand::Stitch(stitch_args) => MafStitchParser::new(stitch_args).stitch(),
+        MafSubcommand::Summary(summary_args) => MafSummaryParser::new(summary_args).summarize(),
+        MafSubcommand::Filter(filter_args) => MafFilterParser::new(filter_args).filter(),
+        MafSubcommand::Rename(rename_args) => MafRenameParser::new(rename_args).rename(),
+        MafSubcommand::FromAlign(align_args) => FromAlignParser::new(align_args).convert(),
+        MafSubcommand::Index(index_args) => MafIndexParser::new(index_args).index(),
     };
 }
 
@@ -61,9 +95,12 @@ fn match_partition_subcommand(subcommand: &PartitionSubcommand) {
 
 fn match_alignment_subcommand(subcommand: &AlignmentSubcommand) {
     match subcommand {
+        AlignmentSubcommand::Bait(bait_args) => BaitParser::new(bait_args).design(),
         AlignmentSubcommand::Concat(concat_args) => ConcatParser::new(concat_args).concat(),
+        AlignmentSubcommand::Coordinate(coord_args) => CoordinateParser::new(coord_args).convert(),
         AlignmentSubcommand::Convert(convert_args) => ConvertParser::new(convert_args).convert(),
         AlignmentSubcommand::Filter(filter_args) => FilterParser::new(filter_args).filter(),
+        AlignmentSubcommand::Slice(slice_args) => SliceParser::new(slice_args).slice(),
         AlignmentSubcommand::Split(split_args) => SplitParser::new(split_args).split(),
         AlignmentSubcommand::Summary(summary_args) => SummaryParser::new(summary_args).summarize(),
         AlignmentSubcommand::Trim(trim_args) => AlignTrimParser::new(trim_args).trim(),
@@ -73,11 +110,16 @@ fn match_alignment_subcommand(subcommand: &AlignmentSubcommand) {
 
 fn match_sequence_subcommand(subcommand: &SequenceSubcommand) {
     match subcommand {
+        SequenceSubcommand::Consensus(consensus_args) => {
+            ConsensusParser::new(consensus_args).generate()
+        }
         SequenceSubcommand::Extract(extract_args) => ExtractParser::new(extract_args).extract(),
         SequenceSubcommand::Filter(filter_args) => SequenceFilterParser::new(filter_args).filter(),
         SequenceSubcommand::Id(id_args) => IdParser::new(id_args).extract(),
+        SequenceSubcommand::Outlier(outlier_args) => OutlierParser::new(outlier_args).detect(),
         SequenceSubcommand::Remove(remove_args) => RemoveParser::new(remove_args).remove(),
         SequenceSubcommand::Rename(rename_args) => RenameParser::new(rename_args).rename(),
+        SequenceSubcommand::Select(select_args) => SelectParser::new(select_args).select(),
         SequenceSubcommand::Translate(trans_args) => TranslateParser::new(trans_args).translate(),
         SequenceSubcommand::Add(add_args) => AdditionParser::new(add_args).add(),
     };
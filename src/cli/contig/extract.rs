@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::cli::args::genomics::ContigExtractArgs;
+use crate::cli::{ContigInputCli, InputCli, OutputCli, collect_paths};
+use crate::core::contig::extract::{ContigExtractor, ContigRegion};
+use crate::helper::{logger::ContigLogger, types::ContigFmt, utils};
+use crate::parser::bed::BedParser;
+
+pub(in crate::cli) struct ContigExtractParser<'a> {
+    args: &'a ContigExtractArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl InputCli for ContigExtractParser<'_> {}
+impl OutputCli for ContigExtractParser<'_> {}
+impl ContigInputCli for ContigExtractParser<'_> {}
+
+impl<'a> ContigExtractParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ContigExtractArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn extract(&mut self) {
+        let input_fmt = ContigFmt::Fasta;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Extract contig regions";
+        ContigLogger::new(self.input_dir.as_deref(), &input_fmt, fcounts).log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let regions = self.parse_regions();
+        ContigExtractor::new(&files, &self.args.output, &regions).extract();
+    }
+
+    fn parse_regions(&self) -> Vec<ContigRegion> {
+        log::info!("{}", "Parameters".yellow());
+        let mut regions: Vec<ContigRegion> = self
+            .args
+            .region
+            .iter()
+            .flatten()
+            .map(|region| region.parse().expect("Failed parsing region"))
+            .collect();
+        if let Some(bed) = &self.args.bed {
+            let records = BedParser::new(bed)
+                .parse()
+                .expect("Failed parsing BED file");
+            log::info!("{:18}: {}", "BED file", bed.display());
+            regions.extend(records.iter().map(ContigRegion::from_bed));
+        }
+        log::info!(
+            "{:18}: {}\n",
+            "Region counts",
+            utils::fmt_num(&regions.len())
+        );
+        regions
+    }
+}
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::cli::args::genomics::ContigFilterArgs;
+use crate::cli::{ContigInputCli, InputCli, OutputCli, collect_paths};
+use crate::core::contig::filter::{ContigFilter, ContigFilterParameters};
+use crate::helper::{logger::ContigLogger, utils};
+
+pub(in crate::cli) struct ContigFilterParser<'a> {
+    args: &'a ContigFilterArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl InputCli for ContigFilterParser<'_> {}
+impl OutputCli for ContigFilterParser<'_> {}
+impl ContigInputCli for ContigFilterParser<'_> {}
+
+impl<'a> ContigFilterParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ContigFilterArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn filter(&mut self) {
+        let input_fmt = &self.args.input_format;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Filter contig sequences";
+        ContigLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        ContigFilter::new(&files, input_fmt, &self.args.output, &params).filter();
+    }
+
+    fn parse_params(&self) -> ContigFilterParameters {
+        let params = ContigFilterParameters {
+            min_length: self.args.min_length,
+            max_length: self.args.max_length,
+            max_n: self.args.max_n,
+            min_gc: self.args.min_gc,
+            max_gc: self.args.max_gc,
+            top_n: self.args.top_n,
+        };
+        log::info!("{}", "Parameters".yellow());
+        if let Some(min_length) = params.min_length {
+            log::info!("{:18}: {}", "Min length", utils::fmt_num(&min_length));
+        }
+        if let Some(max_length) = params.max_length {
+            log::info!("{:18}: {}", "Max length", utils::fmt_num(&max_length));
+        }
+        if let Some(max_n) = params.max_n {
+            log::info!("{:18}: {}", "Max N proportion", max_n);
+        }
+        if let Some(min_gc) = params.min_gc {
+            log::info!("{:18}: {}", "Min GC content", min_gc);
+        }
+        if let Some(max_gc) = params.max_gc {
+            log::info!("{:18}: {}", "Max GC content", max_gc);
+        }
+        if let Some(top_n) = params.top_n {
+            log::info!("{:18}: {}", "Top N longest", utils::fmt_num(&top_n));
+        }
+        log::info!("");
+        params
+    }
+}
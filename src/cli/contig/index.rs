@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use crate::cli::args::genomics::ContigIndexArgs;
+use crate::cli::{ContigInputCli, InputCli, collect_paths};
+use crate::core::contig::index::ContigIndexer;
+use crate::helper::{logger::ContigLogger, types::ContigFmt};
+
+pub(in crate::cli) struct ContigIndexParser<'a> {
+    args: &'a ContigIndexArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl InputCli for ContigIndexParser<'_> {}
+impl ContigInputCli for ContigIndexParser<'_> {}
+
+impl<'a> ContigIndexParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ContigIndexArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn index(&mut self) {
+        let input_fmt = ContigFmt::Fasta;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Contig indexing";
+        ContigLogger::new(self.input_dir.as_deref(), &input_fmt, fcounts).log(task);
+        ContigIndexer::new(&files).index();
+    }
+}
@@ -1 +1,5 @@
+pub(crate) mod extract;
+pub(crate) mod filter;
+pub(crate) mod index;
+pub(crate) mod rename;
 pub(crate) mod summarize;
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use crate::cli::args::genomics::ContigRenameArgs;
+use crate::cli::{ContigInputCli, InputCli, OutputCli, collect_paths};
+use crate::core::contig::rename::ContigRename;
+use crate::helper::logger::ContigLogger;
+
+pub(in crate::cli) struct ContigRenameParser<'a> {
+    args: &'a ContigRenameArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl InputCli for ContigRenameParser<'_> {}
+impl OutputCli for ContigRenameParser<'_> {}
+impl ContigInputCli for ContigRenameParser<'_> {}
+
+impl<'a> ContigRenameParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ContigRenameArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn rename(&mut self) {
+        let input_fmt = &self.args.input_format;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Rename contig sequences";
+        ContigLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        ContigRename::new(&files, input_fmt, &self.args.output).rename();
+    }
+}
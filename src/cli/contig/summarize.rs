@@ -1,9 +1,12 @@
 use std::path::PathBuf;
 
+use colored::Colorize;
+
 use crate::cli::args::genomics::ContigSummaryArgs;
 use crate::cli::{collect_paths, ContigInputCli, InputCli, OutputCli};
 use crate::core::contig::summarize::ContigSummaryHandler;
-use crate::helper::logger::ContigLogger;
+use crate::helper::{logger::ContigLogger, utils};
+use crate::stats::contigs::ContigSummaryParameters;
 
 pub(in crate::cli) struct ContigCliParser<'a> {
     args: &'a ContigSummaryArgs,
@@ -30,12 +33,35 @@ impl<'a> ContigCliParser<'a> {
         let task = "Summarize contig sequences";
         ContigLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
         self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
         ContigSummaryHandler::new(
             &files,
             input_fmt,
             &self.args.output,
             self.args.prefix.as_deref(),
+            &params,
         )
         .summarize();
     }
+
+    fn parse_params(&self) -> ContigSummaryParameters {
+        let mut thresholds = self.args.length_thresholds.clone();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        log::info!("{}", "Parameters".yellow());
+        let bins: Vec<String> = thresholds.iter().map(|t| t.to_string()).collect();
+        log::info!("{:18}: {}", "Length thresholds", bins.join(", "));
+        if let Some(genome_size) = self.args.genome_size {
+            log::info!("{:18}: {}", "Genome size", utils::fmt_num(&genome_size));
+        }
+        if self.args.per_contig {
+            log::info!("{:18}: {}", "Per-contig output", "yes");
+        }
+        log::info!("");
+        ContigSummaryParameters {
+            thresholds,
+            genome_size: self.args.genome_size,
+            per_contig: self.args.per_contig,
+        }
+    }
 }
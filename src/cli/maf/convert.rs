@@ -36,13 +36,16 @@ impl<'a> MafConvertParser<'a> {
         log::info!("{:18}: {}", "Input format:", "MAF");
         log::info!("{:18}: {}\n", "Task", task);
         self.check_output_dir_exist(&self.args.output, self.args.io.force);
-        let convert = MafConverter::new(
+        let mut convert = MafConverter::new(
             &files,
             &self.args.reference,
             self.args.from_bed,
             &self.args.output,
             &output_fmt,
         );
+        if self.args.species_names {
+            convert.set_species_names();
+        }
         convert.convert();
     }
 }
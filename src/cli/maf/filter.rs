@@ -0,0 +1,66 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, args::genomics::MafFilterArgs},
+    core::maf::filter::{MafFilter, MafFilterParameters},
+    helper::{finder::MafFileFinder, utils},
+};
+
+pub(in crate::cli) struct MafFilterParser<'a> {
+    args: &'a MafFilterArgs,
+}
+
+impl InputCli for MafFilterParser<'_> {}
+
+impl OutputCli for MafFilterParser<'_> {}
+
+impl<'a> MafFilterParser<'a> {
+    pub(in crate::cli) fn new(args: &'a MafFilterArgs) -> Self {
+        Self { args }
+    }
+
+    pub(in crate::cli) fn filter(&mut self) {
+        let files = match &self.args.io.dir {
+            Some(dir) => {
+                log::info!("{:18}: {}", "Input dir", &dir);
+                MafFileFinder::new(Path::new(dir)).find_recursive()
+            }
+            None => {
+                log::info!("{:18}: {}", "Input path", "STDIN");
+                self.collect_paths(&self.args.io.input)
+            }
+        };
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&files.len()));
+        let task = "MAF block filtering";
+        log::info!("{:18}: {}", "Input format:", "MAF");
+        log::info!("{:18}: {}\n", "Task", task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        MafFilter::new(&files, &self.args.output, &params).filter();
+    }
+
+    fn parse_params(&self) -> MafFilterParameters {
+        log::info!("{}", "Parameters".yellow());
+        if let Some(min_score) = self.args.min_score {
+            log::info!("{:18}: {}", "Min score", min_score);
+        }
+        if let Some(min_length) = self.args.min_length {
+            log::info!("{:18}: {}", "Min length", utils::fmt_num(&min_length));
+        }
+        if let Some(species) = &self.args.species {
+            log::info!("{:18}: {}", "Required species", species.join(", "));
+        }
+        if let Some(projection) = &self.args.project {
+            log::info!("{:18}: {}", "Projection", projection.join(", "));
+        }
+        log::info!("");
+        MafFilterParameters {
+            min_score: self.args.min_score,
+            min_length: self.args.min_length,
+            species: self.args.species.clone(),
+            projection: self.args.project.clone(),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{AlignSeqInput, InputCli, OutputCli, args::genomics::MafFromAlignArgs, collect_paths},
+    core::maf::from_align::AlignmentToMaf,
+    helper::logger::AlignSeqLogger,
+};
+
+impl InputCli for FromAlignParser<'_> {}
+impl OutputCli for FromAlignParser<'_> {}
+impl AlignSeqInput for FromAlignParser<'_> {}
+
+pub(in crate::cli) struct FromAlignParser<'a> {
+    args: &'a MafFromAlignArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> FromAlignParser<'a> {
+    pub(in crate::cli) fn new(args: &'a MafFromAlignArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn convert(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Alignment to MAF conversion";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        log::info!("{}", "Parameters".yellow());
+        log::info!("{:18}: {}", "Reference", self.args.reference);
+        match &self.args.chrom_sizes {
+            Some(path) => log::info!("{:18}: {}\n", "Chrom sizes", path.display()),
+            None => log::info!("{:18}: {}\n", "Chrom sizes", "Alignment end"),
+        }
+        let mut handle = AlignmentToMaf::new(
+            &input_fmt,
+            &datatype,
+            &self.args.reference,
+            &self.args.output,
+        );
+        if let Some(path) = &self.args.chrom_sizes {
+            handle.set_chrom_sizes(path);
+        }
+        handle.convert(&files);
+    }
+}
@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use crate::{
+    cli::{InputCli, args::genomics::MafIndexArgs},
+    core::maf::index::MafIndexer,
+    helper::{finder::MafFileFinder, utils},
+};
+
+pub(in crate::cli) struct MafIndexParser<'a> {
+    args: &'a MafIndexArgs,
+}
+
+impl InputCli for MafIndexParser<'_> {}
+
+impl<'a> MafIndexParser<'a> {
+    pub(in crate::cli) fn new(args: &'a MafIndexArgs) -> Self {
+        Self { args }
+    }
+
+    pub(in crate::cli) fn index(&mut self) {
+        let files = match &self.args.io.dir {
+            Some(dir) => {
+                log::info!("{:18}: {}", "Input dir", &dir);
+                MafFileFinder::new(Path::new(dir)).find_recursive()
+            }
+            None => {
+                log::info!("{:18}: {}", "Input path", "STDIN");
+                self.collect_paths(&self.args.io.input)
+            }
+        };
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&files.len()));
+        let task = "MAF block indexing";
+        log::info!("{:18}: {}", "Input format:", "MAF");
+        log::info!("{:18}: {}\n", "Task", task);
+        MafIndexer::new(&files).index();
+    }
+}
@@ -1 +1,7 @@
 pub(crate) mod convert;
+pub(crate) mod filter;
+pub(crate) mod from_align;
+pub(crate) mod index;
+pub(crate) mod rename;
+pub(crate) mod stitch;
+pub(crate) mod summary;
@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, args::genomics::MafRenameArgs},
+    core::maf::rename::MafRenaming,
+    helper::{finder::MafFileFinder, utils},
+    parser::delimited,
+};
+
+pub(in crate::cli) struct MafRenameParser<'a> {
+    args: &'a MafRenameArgs,
+}
+
+impl InputCli for MafRenameParser<'_> {}
+
+impl OutputCli for MafRenameParser<'_> {}
+
+impl<'a> MafRenameParser<'a> {
+    pub(in crate::cli) fn new(args: &'a MafRenameArgs) -> Self {
+        Self { args }
+    }
+
+    pub(in crate::cli) fn rename(&mut self) {
+        let files = match &self.args.io.dir {
+            Some(dir) => {
+                log::info!("{:18}: {}", "Input dir", &dir);
+                MafFileFinder::new(Path::new(dir)).find_recursive()
+            }
+            None => {
+                log::info!("{:18}: {}", "Input path", "STDIN");
+                self.collect_paths(&self.args.io.input)
+            }
+        };
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&files.len()));
+        let task = "MAF species renaming";
+        log::info!("{:18}: {}", "Input format:", "MAF");
+        log::info!("{:18}: {}\n", "Task", task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let names = delimited::parse_delimited_text(&self.args.replace_id);
+        log::info!("{}", "Parameters".yellow());
+        log::info!("{:18}: {}", "File", self.args.replace_id.display());
+        log::info!(
+            "{:18}: {}\n",
+            "Species counts",
+            utils::fmt_num(&names.len())
+        );
+        MafRenaming::new(&files, &self.args.output, &names).rename();
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, args::genomics::MafStitchArgs},
+    core::maf::stitch::{MafStitcher, StitchParameters},
+    helper::{finder::MafFileFinder, utils},
+    parser::bed::BedParser,
+};
+
+pub(in crate::cli) struct MafStitchParser<'a> {
+    args: &'a MafStitchArgs,
+}
+
+impl InputCli for MafStitchParser<'_> {}
+
+impl OutputCli for MafStitchParser<'_> {}
+
+impl<'a> MafStitchParser<'a> {
+    pub(in crate::cli) fn new(args: &'a MafStitchArgs) -> Self {
+        Self { args }
+    }
+
+    pub(in crate::cli) fn stitch(&mut self) {
+        let output_fmt = self.parse_output_fmt(&self.args.output_fmt);
+        let files = match &self.args.io.dir {
+            Some(dir) => {
+                log::info!("{:18}: {}", "Input dir", &dir);
+                MafFileFinder::new(Path::new(dir)).find_recursive()
+            }
+            None => {
+                log::info!("{:18}: {}", "Input path", "STDIN");
+                self.collect_paths(&self.args.io.input)
+            }
+        };
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&files.len()));
+        let task = "MAF block stitching";
+        log::info!("{:18}: {}", "Input format:", "MAF");
+        log::info!("{:18}: {}\n", "Task", task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        MafStitcher::new(
+            &files,
+            self.args.ref_species.as_deref(),
+            &self.args.output,
+            &output_fmt,
+        )
+        .stitch(&params);
+    }
+
+    fn parse_params(&self) -> StitchParameters {
+        log::info!("{}", "Parameters".yellow());
+        match &self.args.ref_species {
+            Some(reference) => log::info!("{:18}: {}", "Reference", reference),
+            None => log::info!("{:18}: {}", "Reference", "First sequence"),
+        }
+        match &self.args.bed {
+            Some(bed) => {
+                let records = BedParser::new(bed)
+                    .parse()
+                    .expect("Failed parsing BED file");
+                log::info!("{:18}: {}", "BED file", bed.display());
+                log::info!(
+                    "{:18}: {}\n",
+                    "Region counts",
+                    utils::fmt_num(&records.len())
+                );
+                StitchParameters::Region(records)
+            }
+            None => {
+                log::info!("{:18}: {}\n", "Output", "Per chromosome");
+                StitchParameters::Chromosome
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, args::genomics::MafSummaryArgs},
+    core::maf::summary::MafSummary,
+    helper::{finder::MafFileFinder, utils},
+};
+
+pub(in crate::cli) struct MafSummaryParser<'a> {
+    args: &'a MafSummaryArgs,
+}
+
+impl InputCli for MafSummaryParser<'_> {}
+
+impl OutputCli for MafSummaryParser<'_> {}
+
+impl<'a> MafSummaryParser<'a> {
+    pub(in crate::cli) fn new(args: &'a MafSummaryArgs) -> Self {
+        Self { args }
+    }
+
+    pub(in crate::cli) fn summarize(&mut self) {
+        let files = match &self.args.io.dir {
+            Some(dir) => {
+                log::info!("{:18}: {}", "Input dir", &dir);
+                MafFileFinder::new(Path::new(dir)).find_recursive()
+            }
+            None => {
+                log::info!("{:18}: {}", "Input path", "STDIN");
+                self.collect_paths(&self.args.io.input)
+            }
+        };
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&files.len()));
+        let task = "MAF summary statistics";
+        log::info!("{:18}: {}", "Input format:", "MAF");
+        log::info!("{:18}: {}\n", "Task", task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        log::info!("{}", "Parameters".yellow());
+        match &self.args.ref_species {
+            Some(reference) => log::info!("{:18}: {}\n", "Reference", reference),
+            None => log::info!("{:18}: {}\n", "Reference", "First sequence"),
+        }
+        MafSummary::new(&files, self.args.ref_species.as_deref(), &self.args.output).summarize();
+    }
+}
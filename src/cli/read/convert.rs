@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, RawInputCli, args::genomics::ReadConvertArgs, collect_paths},
+    core::read::convert::ReadConverter,
+    helper::logger::ReadLogger,
+};
+
+impl InputCli for ReadConvertParser<'_> {}
+impl OutputCli for ReadConvertParser<'_> {}
+impl RawInputCli for ReadConvertParser<'_> {}
+
+pub(in crate::cli) struct ReadConvertParser<'a> {
+    args: &'a ReadConvertArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> ReadConvertParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ReadConvertArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn convert(&mut self) {
+        let input_fmt = &self.args.input_format;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Convert raw read sequences";
+        ReadLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        log::info!("{}", "Parameters".yellow());
+        log::info!("{:18}: {}\n", "Mode", self.args.mode);
+        ReadConverter::new(&files, input_fmt, &self.args.output, &self.args.mode).convert();
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, RawInputCli, args::genomics::ReadFilterArgs, collect_paths},
+    core::read::filter::{ReadFilter, ReadFilterParameters},
+    helper::{logger::ReadLogger, utils},
+};
+
+impl InputCli for ReadFilterParser<'_> {}
+impl OutputCli for ReadFilterParser<'_> {}
+impl RawInputCli for ReadFilterParser<'_> {}
+
+pub(in crate::cli) struct ReadFilterParser<'a> {
+    args: &'a ReadFilterArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> ReadFilterParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ReadFilterArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn filter(&mut self) {
+        let input_fmt = &self.args.input_format;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Filter and trim raw read sequences";
+        ReadLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        ReadFilter::new(&files, input_fmt, &self.args.output, &params).filter();
+    }
+
+    fn parse_params(&self) -> ReadFilterParameters {
+        log::info!("{}", "Parameters".yellow());
+        log::info!("{:18}: {}", "QScore encoding", self.args.qscore_encoding);
+        let mut params = ReadFilterParameters {
+            encoding: self.args.qscore_encoding,
+            min_adapter_overlap: self.args.min_adapter_overlap,
+            window_size: self.args.window_size,
+            window_quality: self.args.window_quality,
+            min_length: self.args.min_length,
+            max_n: self.args.max_n,
+            min_mean_quality: self.args.min_mean_quality,
+            ..Default::default()
+        };
+        if let Some(adapters) = &self.args.adapters {
+            params.parse_adapters(adapters);
+            log::info!("{:18}: {}", "Adapters", adapters.display());
+            log::info!(
+                "{:18}: {}",
+                "Adapter counts",
+                utils::fmt_num(&params.adapters.len())
+            );
+        }
+        if let Some(window_quality) = self.args.window_quality {
+            log::info!("{:18}: {}", "Window size", self.args.window_size);
+            log::info!("{:18}: {}", "Window quality", window_quality);
+        }
+        if let Some(min_length) = self.args.min_length {
+            log::info!("{:18}: {}", "Min length", utils::fmt_num(&min_length));
+        }
+        if let Some(max_n) = self.args.max_n {
+            log::info!("{:18}: {}", "Max N proportion", max_n);
+        }
+        if let Some(min_mean_quality) = self.args.min_mean_quality {
+            log::info!("{:18}: {}", "Min mean quality", min_mean_quality);
+        }
+        log::info!("");
+        params
+    }
+}
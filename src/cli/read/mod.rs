@@ -1 +1,4 @@
+pub(crate) mod convert;
+pub(crate) mod filter;
+pub(crate) mod subsample;
 pub(crate) mod summarize;
@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use colored::Colorize;
+
+use crate::{
+    cli::{InputCli, OutputCli, RawInputCli, args::genomics::ReadSubsampleArgs, collect_paths},
+    core::read::subsample::{ReadSubsample, SubsampleMode},
+    helper::{logger::ReadLogger, utils},
+};
+
+impl InputCli for ReadSubsampleParser<'_> {}
+impl OutputCli for ReadSubsampleParser<'_> {}
+impl RawInputCli for ReadSubsampleParser<'_> {}
+
+pub(in crate::cli) struct ReadSubsampleParser<'a> {
+    args: &'a ReadSubsampleArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> ReadSubsampleParser<'a> {
+    pub(in crate::cli) fn new(args: &'a ReadSubsampleArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn subsample(&mut self) {
+        let input_fmt = &self.args.input_format;
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let fcounts = files.len();
+        let task = "Subsample raw read sequences";
+        ReadLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let mode = self.parse_mode();
+        let seed = self.parse_seed();
+        ReadSubsample::new(&files, input_fmt, &self.args.output, &mode, seed).subsample();
+    }
+
+    fn parse_mode(&self) -> SubsampleMode {
+        log::info!("{}", "Parameters".yellow());
+        match (self.args.fraction, self.args.count) {
+            (Some(fraction), _) => {
+                assert!(
+                    (0.0..=1.0).contains(&fraction),
+                    "Fraction must be between 0 and 1"
+                );
+                log::info!("{:18}: {}", "Fraction", fraction);
+                SubsampleMode::Fraction(fraction)
+            }
+            (None, Some(count)) => {
+                log::info!("{:18}: {}", "Read count", utils::fmt_num(&count));
+                SubsampleMode::Count(count)
+            }
+            (None, None) => {
+                log::info!("{:18}: {}", "Mode", "Normalize to the smallest sample");
+                SubsampleMode::Normalize
+            }
+        }
+    }
+
+    // Use the current time if the seed is not specified.
+    // The seed is logged to reproduce the results.
+    fn parse_seed(&self) -> u64 {
+        let seed = self.args.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Failed getting system time")
+                .as_secs()
+        });
+        log::info!("{:18}: {}\n", "Seed", seed);
+        seed
+    }
+}
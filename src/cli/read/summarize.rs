@@ -30,11 +30,13 @@ impl<'a> ReadSummaryCliParser<'a> {
         let fcounts = files.len();
         let task = "Summarize raw read sequences";
         ReadLogger::new(self.input_dir.as_deref(), input_fmt, fcounts).log(task);
+        log::info!("{:18}: {}\n", "QScore encoding", self.args.qscore_encoding);
         self.check_output_dir_exist(&self.args.output, self.args.io.force);
         GenomicReadSummary::new(
             &mut files,
             &self.args.input_format,
             &self.args.mode,
+            &self.args.qscore_encoding,
             &self.args.output,
             self.args.prefix.as_deref(),
         )
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{
+        AlignSeqInput, InputCli, OutputCli, args::sequence::SequenceConsensusArgs, collect_paths,
+    },
+    core::sequence::consensus::{
+        ConsensusGrouping, ConsensusParameters, GapHandling, SequenceConsensus,
+    },
+    helper::{logger::AlignSeqLogger, utils},
+    parser::delimited,
+};
+
+impl InputCli for ConsensusParser<'_> {}
+impl OutputCli for ConsensusParser<'_> {}
+impl AlignSeqInput for ConsensusParser<'_> {}
+
+pub(in crate::cli) struct ConsensusParser<'a> {
+    args: &'a SequenceConsensusArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> ConsensusParser<'a> {
+    pub(in crate::cli) fn new(args: &'a SequenceConsensusArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn generate(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        let output_fmt = self.parse_output_fmt(&self.args.out_fmt.output_fmt);
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Generate consensus sequences";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        SequenceConsensus::new(
+            &input_fmt,
+            &datatype,
+            &self.args.output,
+            &output_fmt,
+            &params,
+        )
+        .generate(&files);
+    }
+
+    fn parse_params(&self) -> ConsensusParameters {
+        log::info!("{}", "Consensus Parameters".yellow());
+        if let Some(threshold) = self.args.threshold {
+            assert!(
+                threshold > 0.0 && threshold <= 1.0,
+                "Threshold must be greater than 0 and less than or equal to 1"
+            );
+            log::info!("{:18}: {}", "Threshold", threshold);
+        } else {
+            log::info!("{:18}: {}", "Threshold", "Majority rule");
+        }
+        let gap = self
+            .args
+            .gap
+            .parse::<GapHandling>()
+            .expect("Invalid gap option");
+        log::info!("{:18}: {}", "Gaps", gap);
+        let grouping = self.parse_grouping();
+        ConsensusParameters {
+            threshold: self.args.threshold,
+            gap,
+            grouping,
+        }
+    }
+
+    fn parse_grouping(&self) -> ConsensusGrouping {
+        if let Some(re) = &self.args.re {
+            log::info!("{:18}: {}\n", "Group regex", re);
+            ConsensusGrouping::Regex(re.to_string())
+        } else if let Some(map) = &self.args.map {
+            let names = delimited::parse_delimited_text(map);
+            log::info!("{:18}: {}", "Group file", map.display());
+            log::info!("{:18}: {}\n", "ID counts", utils::fmt_num(&names.len()));
+            ConsensusGrouping::Mapping(names)
+        } else {
+            log::info!("{:18}: {}\n", "Group", "Alignment");
+            ConsensusGrouping::Alignment
+        }
+    }
+}
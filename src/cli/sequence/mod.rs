@@ -1,7 +1,10 @@
 pub(crate) mod addition;
+pub(crate) mod consensus;
 pub(crate) mod extract;
 pub(crate) mod filter;
 pub(crate) mod id;
+pub(crate) mod outlier;
 pub(crate) mod remove;
 pub(crate) mod rename;
+pub(crate) mod select;
 pub(crate) mod translate;
@@ -0,0 +1,70 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{AlignSeqInput, InputCli, OutputCli, args::sequence::SequenceOutlierArgs, collect_paths},
+    core::sequence::outlier::{OutlierDetection, OutlierParameters},
+    helper::logger::AlignSeqLogger,
+};
+
+const DEFAULT_IQR: f64 = 1.5;
+const DEFAULT_ZSCORE: f64 = 3.5;
+
+impl InputCli for OutlierParser<'_> {}
+impl OutputCli for OutlierParser<'_> {}
+impl AlignSeqInput for OutlierParser<'_> {}
+
+pub(in crate::cli) struct OutlierParser<'a> {
+    args: &'a SequenceOutlierArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> OutlierParser<'a> {
+    pub(in crate::cli) fn new(args: &'a SequenceOutlierArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn detect(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        let output_fmt = self.parse_output_fmt(&self.args.out_fmt.output_fmt);
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Detect outlier sequences";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let params = self.parse_params();
+        OutlierDetection::new(
+            &files,
+            &input_fmt,
+            &datatype,
+            &self.args.output,
+            &output_fmt,
+            &params,
+        )
+        .detect(self.args.remove);
+    }
+
+    fn parse_params(&self) -> OutlierParameters {
+        log::info!("{}", "Outlier Parameters".yellow());
+        let params = match self.args.method.as_str() {
+            "iqr" => OutlierParameters::Iqr(self.args.threshold.unwrap_or(DEFAULT_IQR)),
+            "zscore" => OutlierParameters::ZScore(self.args.threshold.unwrap_or(DEFAULT_ZSCORE)),
+            _ => unreachable!("Unknown outlier method. Supported methods: iqr, zscore"),
+        };
+        log::info!("{:18}: {}", "Method", self.args.method);
+        log::info!("{:18}: {}", "Rule", params);
+        log::info!("{:18}: {}\n", "Remove outliers", self.args.remove);
+        params
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::{
+    cli::{AlignSeqInput, InputCli, OutputCli, args::sequence::SequenceSelectArgs, collect_paths},
+    core::sequence::select::SampleSelection,
+    helper::{logger::AlignSeqLogger, utils},
+    parser::delimited,
+};
+
+impl InputCli for SelectParser<'_> {}
+impl OutputCli for SelectParser<'_> {}
+impl AlignSeqInput for SelectParser<'_> {}
+
+pub(in crate::cli) struct SelectParser<'a> {
+    args: &'a SequenceSelectArgs,
+    input_dir: Option<PathBuf>,
+}
+
+impl<'a> SelectParser<'a> {
+    pub(in crate::cli) fn new(args: &'a SequenceSelectArgs) -> Self {
+        Self {
+            args,
+            input_dir: None,
+        }
+    }
+
+    pub(in crate::cli) fn select(&mut self) {
+        let input_fmt = self.parse_input_fmt(&self.args.in_fmt.input_fmt);
+        let datatype = self.parse_datatype(&self.args.in_fmt.datatype);
+        let output_fmt = self.parse_output_fmt(&self.args.out_fmt.output_fmt);
+        let dir = &self.args.io.dir;
+        let files = collect_paths!(self, dir, input_fmt);
+        let task = "Select the most complete sample per species";
+        AlignSeqLogger::new(
+            self.input_dir.as_deref(),
+            &input_fmt,
+            &datatype,
+            files.len(),
+        )
+        .log(task);
+        self.check_output_dir_exist(&self.args.output, self.args.io.force);
+        let species = delimited::parse_delimited_text(&self.args.map);
+        self.print_species_info(&species);
+        SampleSelection::new(
+            &input_fmt,
+            &datatype,
+            &self.args.output,
+            &output_fmt,
+            &species,
+        )
+        .select(&files);
+    }
+
+    fn print_species_info(&self, species: &[(String, String)]) {
+        let mut names: Vec<&str> = species.iter().map(|(_, name)| name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        log::info!("{}", "Species".yellow());
+        log::info!("{:18}: {}", "Mapping file", self.args.map.display());
+        log::info!("{:18}: {}", "Sample counts", utils::fmt_num(&species.len()));
+        log::info!(
+            "{:18}: {}\n",
+            "Species counts",
+            utils::fmt_num(&names.len())
+        );
+    }
+}
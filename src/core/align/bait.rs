@@ -0,0 +1,413 @@
+//! Design hybridization capture baits from DNA alignments.
+//!
+//! Each alignment is tiled with windows of the bait length.
+//! A new window starts every `bait length - overlap` columns.
+//! Windows are kept as baits if they meet all the thresholds:
+//! - Conservation: the proportion of conserved sites in the window.
+//!   A site is conserved if it has a single character state,
+//!   ignoring ambiguous and missing data characters.
+//! - Missing data: the mean proportion of gaps, missing characters,
+//!   and ambiguous bases (including `N`) per site.
+//! - GC content: the GC proportion of the bait sequence, excluding `N`.
+//!
+//! The bait sequence is the majority-rule consensus of the window.
+//! Ties are resolved to the first base in alphabetical order.
+//! Sites without any unambiguous base are written as `N`.
+//!
+//! Baits from all alignments are written to a single FASTA file
+//! and a BED file of bait positions. Positions are alignment columns
+//! and the alignment file name (without extension) is the chromosome name.
+//! Bait IDs use 1-based inclusive coordinates, e.g. `locus_1:1-120`.
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use rayon::prelude::*;
+
+use crate::{
+    helper::{
+        sequence::SeqParser,
+        types::{DataType, InputFmt, SeqMatrix},
+        utils,
+    },
+    parser::{bed::BedRecord, fasta::Records},
+    stats::sequence::Sites,
+    writer::{bed::BedWriter, contigs::ContigWriter},
+};
+
+const BAIT_OUTPUT_FNAME: &str = "baits";
+const MISSING_BASE: u8 = b'N';
+
+/// Bait tiling and window selection parameters.
+#[derive(Debug, Clone)]
+pub struct BaitParameters {
+    /// Bait length in alignment columns
+    pub bait_length: usize,
+    /// Number of columns shared by adjacent baits
+    pub overlap: usize,
+    /// Minimum proportion of conserved sites in a bait
+    pub min_conservation: f64,
+    /// Maximum mean proportion of missing data in a bait
+    pub max_missing: f64,
+    /// Minimum GC content of a bait (0-1)
+    pub min_gc: f64,
+    /// Maximum GC content of a bait (0-1)
+    pub max_gc: f64,
+}
+
+impl Default for BaitParameters {
+    fn default() -> Self {
+        Self {
+            bait_length: 120,
+            overlap: 60,
+            min_conservation: 0.8,
+            max_missing: 0.2,
+            min_gc: 0.3,
+            max_gc: 0.7,
+        }
+    }
+}
+
+impl BaitParameters {
+    /// Number of columns between the start of adjacent baits.
+    pub fn step(&self) -> usize {
+        self.bait_length - self.overlap
+    }
+}
+
+/// A bait tiled from an alignment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bait {
+    /// Alignment name
+    pub alignment: String,
+    /// 0-based start column
+    pub start: usize,
+    /// 0-based exclusive end column
+    pub end: usize,
+    /// Consensus sequence of the bait
+    pub seq: String,
+}
+
+impl Bait {
+    /// Bait ID in 1-based inclusive coordinates.
+    /// # Example
+    /// ```
+    /// use segul::core::align::bait::Bait;
+    ///
+    /// let bait = Bait {
+    ///     alignment: String::from("locus_1"),
+    ///     start: 0,
+    ///     end: 120,
+    ///     seq: String::new(),
+    /// };
+    /// assert_eq!(bait.id(), "locus_1:1-120");
+    /// ```
+    pub fn id(&self) -> String {
+        format!("{}:{}-{}", self.alignment, self.start + 1, self.end)
+    }
+}
+
+pub struct AlignmentBait<'a> {
+    input_fmt: &'a InputFmt,
+    output_dir: &'a Path,
+    params: &'a BaitParameters,
+}
+
+impl<'a> AlignmentBait<'a> {
+    pub fn new(input_fmt: &'a InputFmt, output_dir: &'a Path, params: &'a BaitParameters) -> Self {
+        assert!(
+            params.bait_length > 0 && params.overlap < params.bait_length,
+            "Bait overlap must be shorter than the bait length"
+        );
+        Self {
+            input_fmt,
+            output_dir,
+            params,
+        }
+    }
+
+    /// Tile baits from the alignments and write them to the output directory.
+    /// Returns the number of baits.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::InputFmt;
+    /// use segul::core::align::bait::{AlignmentBait, BaitParameters};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/consensus/locus_1.fas")];
+    /// let params = BaitParameters {
+    ///     bait_length: 4,
+    ///     overlap: 2,
+    ///     min_conservation: 0.75,
+    ///     max_missing: 0.1,
+    ///     ..Default::default()
+    /// };
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let counts = AlignmentBait::new(&InputFmt::Fasta, output.path(), &params).design(&files);
+    /// assert_eq!(counts, 2);
+    /// ```
+    pub fn design(&self, files: &[PathBuf]) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Designing baits...");
+        let mut baits: Vec<Bait> = files
+            .par_iter()
+            .flat_map(|file| {
+                let (matrix, header) =
+                    SeqParser::new(file, &DataType::Dna).get_alignment(self.input_fmt);
+                self.tile_baits(&self.get_alignment_name(file), &matrix, header.nchar)
+            })
+            .collect();
+        baits.sort_by(|a, b| {
+            a.alignment
+                .cmp(&b.alignment)
+                .then_with(|| a.start.cmp(&b.start))
+        });
+        let fasta_path = self
+            .output_dir
+            .join(BAIT_OUTPUT_FNAME)
+            .with_extension("fasta");
+        let bed_path = self
+            .output_dir
+            .join(BAIT_OUTPUT_FNAME)
+            .with_extension("bed");
+        self.write_baits(&baits, &fasta_path, &bed_path);
+        spin.finish_with_message("Finished designing baits!\n");
+        self.print_output_info(files.len(), baits.len(), &fasta_path, &bed_path);
+        baits.len()
+    }
+
+    fn tile_baits(&self, name: &str, matrix: &SeqMatrix, nchar: usize) -> Vec<Bait> {
+        let sites = Sites::default();
+        let mut conserved = vec![false; nchar];
+        sites
+            .get_conserved_sites(matrix, &DataType::Dna)
+            .iter()
+            .filter(|idx| **idx < nchar)
+            .for_each(|idx| conserved[*idx] = true);
+        // Sites only contain unambiguous bases, so anything else,
+        // including N, is counted as missing.
+        let site_matrix = sites.index_site_without_ambiguity(matrix, &DataType::Dna);
+        let missing: Vec<f64> = (0..nchar)
+            .map(|idx| {
+                let bases = site_matrix.get(&idx).map_or(0, |site| site.len());
+                1.0 - bases as f64 / matrix.len() as f64
+            })
+            .collect();
+        let consensus = self.build_consensus(matrix, nchar);
+
+        let length = self.params.bait_length;
+        (0..nchar)
+            .step_by(self.params.step())
+            .take_while(|start| start + length <= nchar)
+            .filter(|start| {
+                let end = start + length;
+                let cons_prop =
+                    conserved[*start..end].iter().filter(|c| **c).count() as f64 / length as f64;
+                let missing_prop = missing[*start..end].iter().sum::<f64>() / length as f64;
+                cons_prop >= self.params.min_conservation
+                    && missing_prop <= self.params.max_missing
+                    && self.is_gc_match(&consensus[*start..end])
+            })
+            .map(|start| Bait {
+                alignment: name.to_string(),
+                start,
+                end: start + length,
+                seq: String::from_utf8_lossy(&consensus[start..start + length]).to_string(),
+            })
+            .collect()
+    }
+
+    // Majority-rule consensus of the unambiguous bases.
+    fn build_consensus(&self, matrix: &SeqMatrix, nchar: usize) -> Vec<u8> {
+        let site_matrix = Sites::default().index_site_without_ambiguity(matrix, &DataType::Dna);
+        (0..nchar)
+            .map(|idx| match site_matrix.get(&idx) {
+                Some(site) => self.majority_base(site),
+                None => MISSING_BASE,
+            })
+            .collect()
+    }
+
+    fn majority_base(&self, site: &[u8]) -> u8 {
+        let mut counts = [0usize; 4];
+        site.iter().for_each(|ch| match ch.to_ascii_uppercase() {
+            b'A' => counts[0] += 1,
+            b'C' => counts[1] += 1,
+            b'G' => counts[2] += 1,
+            b'T' => counts[3] += 1,
+            _ => (),
+        });
+        let max = counts.iter().max().copied().unwrap_or(0);
+        if max == 0 {
+            return MISSING_BASE;
+        }
+        let idx = counts
+            .iter()
+            .position(|count| *count == max)
+            .expect("Failed finding majority base");
+        b"ACGT"[idx]
+    }
+
+    fn is_gc_match(&self, seq: &[u8]) -> bool {
+        let bases = seq.iter().filter(|ch| **ch != MISSING_BASE).count();
+        if bases == 0 {
+            return false;
+        }
+        let gc = seq.iter().filter(|ch| matches!(ch, b'G' | b'C')).count() as f64 / bases as f64;
+        gc >= self.params.min_gc && gc <= self.params.max_gc
+    }
+
+    fn write_baits(&self, baits: &[Bait], fasta_path: &Path, bed_path: &Path) {
+        let records: Vec<Records> = baits
+            .iter()
+            .map(|bait| Records::new(&bait.id(), &bait.seq))
+            .collect();
+        ContigWriter::new(fasta_path)
+            .write(&records)
+            .expect("Failed writing bait sequences");
+        let bed: Vec<BedRecord> = baits
+            .iter()
+            .map(|bait| {
+                BedRecord::new(
+                    bait.alignment.clone(),
+                    bait.start,
+                    bait.end,
+                    Some(bait.id()),
+                )
+            })
+            .collect();
+        BedWriter::new(bed_path, &bed)
+            .write()
+            .expect("Failed writing bait positions");
+    }
+
+    fn get_alignment_name(&self, file: &Path) -> String {
+        file.file_stem()
+            .expect("Failed to get file stem")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn print_output_info(
+        &self,
+        file_counts: usize,
+        bait_counts: usize,
+        fasta_path: &Path,
+        bed_path: &Path,
+    ) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&file_counts));
+        log::info!("{:18}: {}", "Bait counts", utils::fmt_num(&bait_counts));
+        log::info!("{:18}: {}", "Bait sequences", fasta_path.display());
+        log::info!("{:18}: {}", "Bait positions", bed_path.display());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs;
+
+    use indexmap::IndexMap;
+    use tempdir::TempDir;
+
+    fn get_matrix(seqs: &[&str]) -> SeqMatrix {
+        let mut matrix = IndexMap::new();
+        seqs.iter().enumerate().for_each(|(i, seq)| {
+            matrix.insert(format!("seq_{}", i), seq.to_string());
+        });
+        matrix
+    }
+
+    #[test]
+    fn test_tile_baits() {
+        let params = BaitParameters {
+            bait_length: 4,
+            overlap: 0,
+            min_conservation: 1.0,
+            max_missing: 0.0,
+            min_gc: 0.0,
+            max_gc: 1.0,
+        };
+        let handle = AlignmentBait::new(&InputFmt::Fasta, Path::new("."), &params);
+        let matrix = get_matrix(&["ATGCAGTTAC-G", "ATGCTGTTACAG"]);
+        let baits = handle.tile_baits("locus", &matrix, 12);
+        // Windows start at 0, 4, and 8.
+        // The second window has a variable site and the last has a gap.
+        assert_eq!(1, baits.len());
+        assert_eq!("locus:1-4", baits[0].id());
+        assert_eq!("ATGC", baits[0].seq);
+    }
+
+    #[test]
+    fn test_tile_baits_n_only_taxa() {
+        let params = BaitParameters {
+            bait_length: 4,
+            overlap: 0,
+            min_conservation: 1.0,
+            max_missing: 0.0,
+            min_gc: 0.0,
+            max_gc: 1.0,
+        };
+        let handle = AlignmentBait::new(&InputFmt::Fasta, Path::new("."), &params);
+        let matrix = get_matrix(&[
+            "ATGCATGCATGC",
+            "NNNNNNNNNNNN",
+            "nnnnnnnnnnnn",
+            "RYNNNNNNNNNN",
+        ]);
+        assert!(handle.tile_baits("locus", &matrix, 12).is_empty());
+        // Three of four taxa are missing at every site.
+        let params = BaitParameters {
+            max_missing: 0.75,
+            ..params
+        };
+        let handle = AlignmentBait::new(&InputFmt::Fasta, Path::new("."), &params);
+        assert_eq!(3, handle.tile_baits("locus", &matrix, 12).len());
+    }
+
+    #[test]
+    fn test_majority_base() {
+        let params = BaitParameters::default();
+        let handle = AlignmentBait::new(&InputFmt::Fasta, Path::new("."), &params);
+        assert_eq!(b'G', handle.majority_base(b"GGat"));
+        assert_eq!(b'A', handle.majority_base(b"TA"));
+        let consensus = handle.build_consensus(&get_matrix(&["A-", "T?"]), 2);
+        assert_eq!(b"AN".to_vec(), consensus);
+    }
+
+    #[test]
+    fn test_design_output() {
+        let files = vec![PathBuf::from("tests/files/consensus/locus_1.fas")];
+        let params = BaitParameters {
+            bait_length: 4,
+            overlap: 2,
+            min_conservation: 0.75,
+            max_missing: 0.1,
+            ..Default::default()
+        };
+        let output = TempDir::new("temp").unwrap();
+        let counts = AlignmentBait::new(&InputFmt::Fasta, output.path(), &params).design(&files);
+        assert_eq!(2, counts);
+        let fasta = fs::read_to_string(output.path().join("baits.fasta")).unwrap();
+        assert_eq!(">locus_1:1-4\nATGC\n>locus_1:3-6\nGCAT\n", fasta);
+        let bed = fs::read_to_string(output.path().join("baits.bed")).unwrap();
+        assert_eq!(
+            "locus_1\t0\t4\tlocus_1:1-4\nlocus_1\t2\t6\tlocus_1:3-6\n",
+            bed
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_overlap() {
+        let params = BaitParameters {
+            bait_length: 4,
+            overlap: 4,
+            ..Default::default()
+        };
+        AlignmentBait::new(&InputFmt::Fasta, Path::new("."), &params);
+    }
+}
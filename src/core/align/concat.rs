@@ -7,7 +7,7 @@ use std::path::{Path, PathBuf};
 
 use colored::Colorize;
 
-use crate::helper::concat::Concat;
+use crate::helper::concat::{Concat, NameNormalization, Supermatrix, TaxonList};
 use crate::helper::types::{DataType, Header, InputFmt, OutputFmt, PartitionFmt};
 use crate::helper::{files, utils};
 use crate::writer::{partition::PartWriter, sequences::SeqWriter};
@@ -18,6 +18,9 @@ pub struct AlignmentConcatenation<'a> {
     output_fmt: &'a OutputFmt,
     part_fmt: &'a PartitionFmt,
     output_prefix: &'a Path,
+    normalization: Option<&'a NameNormalization>,
+    taxon_list: Option<&'a TaxonList>,
+    supermatrix: Option<&'a Supermatrix>,
 }
 
 impl<'a> AlignmentConcatenation<'a> {
@@ -34,11 +37,42 @@ impl<'a> AlignmentConcatenation<'a> {
             output_fmt,
             part_fmt,
             output_prefix,
+            normalization: None,
+            taxon_list: None,
+            supermatrix: None,
         }
     }
 
+    /// Set the taxon name normalization.
+    /// Names are normalized before concatenation.
+    pub fn set_name_normalization(&mut self, normalization: &'a NameNormalization) {
+        self.normalization = Some(normalization);
+    }
+
+    /// Set an explicit taxon list.
+    /// Only the listed taxa are written, in the list order.
+    pub fn set_taxon_list(&mut self, taxon_list: &'a TaxonList) {
+        self.taxon_list = Some(taxon_list);
+    }
+
+    /// Set an existing concatenated alignment to append the alignments to.
+    /// The output contains the existing and new loci with updated partitions.
+    pub fn set_supermatrix(&mut self, supermatrix: &'a Supermatrix) {
+        self.supermatrix = Some(supermatrix);
+    }
+
     pub fn concat(&mut self, files: &mut [PathBuf], datatype: &DataType) {
+        let file_counts = files.len();
         let mut concat = Concat::new(files, self.input_fmt, datatype);
+        if let Some(normalization) = self.normalization {
+            concat.set_name_normalization(normalization);
+        }
+        if let Some(taxon_list) = self.taxon_list {
+            concat.set_taxon_list(taxon_list);
+        }
+        if let Some(supermatrix) = self.supermatrix {
+            concat.set_supermatrix(supermatrix);
+        }
         let output_path =
             files::create_output_fname(self.output_dir, self.output_prefix, self.output_fmt);
         let spin = utils::set_spinner();
@@ -52,7 +86,12 @@ impl<'a> AlignmentConcatenation<'a> {
             .expect("Failed writing the output file");
         part_writer.write_partition();
         spin.finish_with_message("Finished concatenating alignments!\n");
-        self.print_output_info(concat.partition.len(), &concat.header);
+        self.print_output_info(
+            concat.partition.len(),
+            file_counts,
+            &concat.header,
+            concat.name_conflicts,
+        );
     }
 
     fn construct_part_fpath(&mut self, output_path: &Path) -> PathBuf {
@@ -83,15 +122,31 @@ impl<'a> AlignmentConcatenation<'a> {
             .join(Path::new(&fname))
     }
 
-    fn print_output_info(&self, count: usize, header: &Header) {
+    fn print_output_info(
+        &self,
+        count: usize,
+        file_counts: usize,
+        header: &Header,
+        name_conflicts: usize,
+    ) {
         log::info!("{}", "Output".yellow());
         log::info!("{:18}: {}", "Taxa", utils::fmt_num(&header.ntax));
         log::info!("{:18}: {}", "Loci", utils::fmt_num(&count));
+        if self.supermatrix.is_some() {
+            log::info!("{:18}: {}", "Appended loci", utils::fmt_num(&file_counts));
+        }
         log::info!(
             "{:18}: {}",
             "Alignment length",
             utils::fmt_num(&header.nchar)
         );
+        if self.normalization.is_some() {
+            log::info!(
+                "{:18}: {}",
+                "Name conflicts",
+                utils::fmt_num(&name_conflicts)
+            );
+        }
         log::info!("{:18}: {}", "Directory", self.output_dir.display());
     }
 }
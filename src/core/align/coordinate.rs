@@ -0,0 +1,379 @@
+//! Map alignment columns to the coordinates of a reference sequence.
+//!
+//! The reference coordinates are the ungapped positions
+//! of a chosen sequence in the alignment. Gaps (`-`) in the reference
+//! are insertions relative to the reference and do not have a position.
+//! Missing characters (`?`) keep their positions.
+//!
+//! Column maps are written per alignment as CSV using 1-based
+//! columns and positions. Partitions and site lists are converted
+//! to reference coordinates and written as a single BED file
+//! using the alignment file name (without extension) as the chromosome name.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helper::{
+        sequence::SeqParser,
+        types::{DataType, InputFmt, Partition},
+        utils,
+    },
+    parser::bed::BedRecord,
+    stats::sequence::Sites,
+    writer::bed::BedWriter,
+};
+
+const MAP_SUFFIX: &str = "map";
+const PARTITION_FNAME: &str = "partition";
+const SITE_FNAME: &str = "sites";
+const PARS_INF_FNAME: &str = "pis";
+
+/// A map between alignment columns and reference positions.
+/// All indices are 0-based.
+pub struct ReferenceMap {
+    /// Reference position of each alignment column.
+    positions: Vec<Option<usize>>,
+    /// Alignment column of each reference position.
+    columns: Vec<usize>,
+}
+
+impl ReferenceMap {
+    /// Create a map from an aligned reference sequence.
+    /// # Example
+    /// ```
+    /// use segul::core::align::coordinate::ReferenceMap;
+    ///
+    /// let map = ReferenceMap::new("AT--GC");
+    /// assert_eq!(map.position(4), Some(2));
+    /// assert_eq!(map.position(2), None);
+    /// assert_eq!(map.column(3), Some(5));
+    /// ```
+    pub fn new(reference: &str) -> Self {
+        let mut positions = Vec::with_capacity(reference.len());
+        let mut columns = Vec::with_capacity(reference.len());
+        reference.bytes().enumerate().for_each(|(idx, c)| {
+            if c == b'-' {
+                positions.push(None);
+            } else {
+                positions.push(Some(columns.len()));
+                columns.push(idx);
+            }
+        });
+        Self { positions, columns }
+    }
+
+    /// Reference position of an alignment column.
+    /// Returns `None` if the reference has a gap in the column.
+    pub fn position(&self, column: usize) -> Option<usize> {
+        self.positions.get(column).copied().flatten()
+    }
+
+    /// Alignment column of a reference position.
+    pub fn column(&self, position: usize) -> Option<usize> {
+        self.columns.get(position).copied()
+    }
+
+    /// Ungapped length of the reference sequence.
+    pub fn reference_len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Convert a 0-based half-open column interval
+    /// to a 0-based half-open reference interval.
+    /// Returns `None` if the reference only has gaps in the interval.
+    /// # Example
+    /// ```
+    /// use segul::core::align::coordinate::ReferenceMap;
+    ///
+    /// let map = ReferenceMap::new("AT--GC");
+    /// assert_eq!(map.map_interval(1, 5), Some((1, 3)));
+    /// assert_eq!(map.map_interval(2, 4), None);
+    /// ```
+    pub fn map_interval(&self, start: usize, end: usize) -> Option<(usize, usize)> {
+        let end = end.min(self.positions.len());
+        let mut positions = self.positions.get(start..end)?.iter().flatten();
+        let first = positions.next()?;
+        let last = positions.last().unwrap_or(first);
+        Some((*first, last + 1))
+    }
+}
+
+/// Column features to convert to reference coordinates.
+pub enum CoordinateParameters {
+    /// Write the column-to-position map of each alignment.
+    Map,
+    /// Convert partitions to BED.
+    Partition(Vec<Partition>),
+    /// Convert a list of 1-based columns to BED.
+    Sites(Vec<usize>),
+    /// Convert parsimony informative sites to BED.
+    ParsInf,
+}
+
+pub struct ReferenceCoordinate<'a> {
+    input_fmt: &'a InputFmt,
+    datatype: &'a DataType,
+    output_dir: &'a Path,
+    reference: &'a str,
+    params: &'a CoordinateParameters,
+}
+
+impl<'a> ReferenceCoordinate<'a> {
+    pub fn new(
+        input_fmt: &'a InputFmt,
+        datatype: &'a DataType,
+        output_dir: &'a Path,
+        reference: &'a str,
+        params: &'a CoordinateParameters,
+    ) -> Self {
+        Self {
+            input_fmt,
+            datatype,
+            output_dir,
+            reference,
+            params,
+        }
+    }
+
+    /// Map alignment columns to reference coordinates.
+    /// Alignments without the reference sequence are skipped.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::{DataType, InputFmt};
+    /// use segul::core::align::coordinate::{CoordinateParameters, ReferenceCoordinate};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/slice/chr1.fas")];
+    /// let params = CoordinateParameters::ParsInf;
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let handle = ReferenceCoordinate::new(
+    ///     &InputFmt::Fasta, &DataType::Dna, output.path(), "ref", &params
+    /// );
+    /// handle.convert(&files);
+    /// ```
+    pub fn convert(&self, files: &[PathBuf]) {
+        let spin = utils::set_spinner();
+        spin.set_message("Mapping reference coordinates...");
+        let (tx, rx) = mpsc::channel();
+        files.par_iter().for_each_with(tx, |tx, file| {
+            let (matrix, _) = SeqParser::new(file, self.datatype).get_alignment(self.input_fmt);
+            let reference = match matrix.get(self.reference) {
+                Some(seq) => seq,
+                None => {
+                    log::warn!(
+                        "{} Reference sequence {} is not found in {}. Skipping it.",
+                        "WARNING!".red(),
+                        self.reference,
+                        file.display()
+                    );
+                    return;
+                }
+            };
+            let map = ReferenceMap::new(reference);
+            let records = match self.params {
+                CoordinateParameters::Map => {
+                    self.write_column_map(file, &map);
+                    return;
+                }
+                CoordinateParameters::Partition(partitions) => {
+                    self.convert_partitions(file, &map, partitions)
+                }
+                CoordinateParameters::Sites(sites) => {
+                    let columns: Vec<usize> = sites.iter().map(|s| s - 1).collect();
+                    self.convert_sites(file, &map, &columns)
+                }
+                CoordinateParameters::ParsInf => {
+                    let columns: Vec<usize> = Sites::default()
+                        .get_site_with_pars_informative(&matrix, self.datatype, 2)
+                        .iter()
+                        .map(|(site, _)| *site)
+                        .collect();
+                    self.convert_sites(file, &map, &columns)
+                }
+            };
+            tx.send(records).expect("Failed sending BED records");
+        });
+        let mut records: Vec<BedRecord> = rx.iter().flatten().collect();
+        records.sort_by(|a, b| {
+            a.chrom
+                .cmp(&b.chrom)
+                .then_with(|| a.chrom_start.cmp(&b.chrom_start))
+        });
+        self.write_bed(&records);
+        spin.finish_with_message("Finished mapping reference coordinates!\n");
+        self.print_output_info(files.len(), records.len());
+    }
+
+    fn convert_partitions(
+        &self,
+        file: &Path,
+        map: &ReferenceMap,
+        partitions: &[Partition],
+    ) -> Vec<BedRecord> {
+        let chrom = self.get_chrom_name(file);
+        partitions
+            .iter()
+            .filter_map(|part| {
+                let (start, end) = map.map_interval(part.start - 1, part.end)?;
+                Some(BedRecord::new(
+                    chrom.clone(),
+                    start,
+                    end,
+                    Some(part.gene.to_string()),
+                ))
+            })
+            .collect()
+    }
+
+    // Convert 0-based columns to BED intervals.
+    // Adjacent reference positions are merged into a single interval.
+    fn convert_sites(&self, file: &Path, map: &ReferenceMap, columns: &[usize]) -> Vec<BedRecord> {
+        let chrom = self.get_chrom_name(file);
+        let mut positions: Vec<usize> = columns.iter().filter_map(|c| map.position(*c)).collect();
+        positions.sort_unstable();
+        positions.dedup();
+        let mut records: Vec<BedRecord> = Vec::new();
+        positions.iter().for_each(|pos| match records.last_mut() {
+            Some(rec) if rec.chrom_end == *pos => rec.chrom_end += 1,
+            _ => records.push(BedRecord::new(chrom.clone(), *pos, pos + 1, None)),
+        });
+        records
+    }
+
+    fn write_column_map(&self, file: &Path, map: &ReferenceMap) {
+        let fname = format!("{}_{}", self.get_chrom_name(file), MAP_SUFFIX);
+        let output_path = self.output_dir.join(fname).with_extension("csv");
+        fs::create_dir_all(self.output_dir).expect("Failed to create output directory");
+        let mut writer = csv::Writer::from_path(output_path).expect("Failed to create CSV writer");
+        map.positions.iter().enumerate().for_each(|(idx, pos)| {
+            let record = ColumnMap {
+                column: idx + 1,
+                position: pos.map(|p| p + 1),
+            };
+            writer
+                .serialize(record)
+                .expect("Failed to write column map");
+        });
+        writer.flush().expect("Failed to write column map");
+    }
+
+    fn write_bed(&self, records: &[BedRecord]) {
+        let fname = match self.params {
+            CoordinateParameters::Map => return,
+            CoordinateParameters::Partition(_) => PARTITION_FNAME,
+            CoordinateParameters::Sites(_) => SITE_FNAME,
+            CoordinateParameters::ParsInf => PARS_INF_FNAME,
+        };
+        let output_path = self.output_dir.join(fname).with_extension("bed");
+        BedWriter::new(&output_path, records)
+            .write()
+            .expect("Failed writing BED file");
+    }
+
+    fn get_chrom_name(&self, file: &Path) -> String {
+        file.file_stem()
+            .expect("Failed to get file stem")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn print_output_info(&self, file_counts: usize, record_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Directory", self.output_dir.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&file_counts));
+        if !matches!(self.params, CoordinateParameters::Map) {
+            log::info!("{:18}: {}", "BED records", utils::fmt_num(&record_counts));
+        }
+    }
+}
+
+/// Reference position of an alignment column.
+#[derive(Debug, Serialize, Deserialize)]
+struct ColumnMap {
+    /// 1-based alignment column
+    column: usize,
+    /// 1-based reference position.
+    /// Empty if the reference has a gap in the column.
+    position: Option<usize>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = "tests/files/slice/chr1.fas";
+
+    #[test]
+    fn test_reference_map() {
+        let map = ReferenceMap::new("AT--G?C");
+        assert_eq!(5, map.reference_len());
+        assert_eq!(Some(3), map.position(5));
+        assert_eq!(None, map.position(3));
+        assert_eq!(Some(4), map.column(2));
+        assert_eq!(None, map.column(5));
+    }
+
+    #[test]
+    fn test_map_interval() {
+        let map = ReferenceMap::new("--ATG--C--");
+        assert_eq!(Some((0, 4)), map.map_interval(0, 10));
+        assert_eq!(Some((2, 4)), map.map_interval(4, 8));
+        assert_eq!(None, map.map_interval(8, 10));
+    }
+
+    #[test]
+    fn test_convert_sites() {
+        let params = CoordinateParameters::Sites(vec![1, 2, 5, 7]);
+        let handle = ReferenceCoordinate::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            "ref",
+            &params,
+        );
+        let map = ReferenceMap::new("ATGC--ATGCAT");
+        let records = handle.convert_sites(Path::new(INPUT), &map, &[0, 1, 4, 6]);
+        assert_eq!(2, records.len());
+        assert_eq!("chr1", records[0].chrom);
+        assert_eq!((0, 2), (records[0].chrom_start, records[0].chrom_end));
+        assert_eq!((4, 5), (records[1].chrom_start, records[1].chrom_end));
+    }
+
+    #[test]
+    fn test_convert_partitions() {
+        let params = CoordinateParameters::Map;
+        let handle = ReferenceCoordinate::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            "ref",
+            &params,
+        );
+        let map = ReferenceMap::new("ATGC--ATGCAT");
+        let partitions = vec![
+            Partition {
+                gene: String::from("gene_1"),
+                start: 1,
+                end: 6,
+            },
+            Partition {
+                gene: String::from("gene_2"),
+                start: 7,
+                end: 12,
+            },
+        ];
+        let records = handle.convert_partitions(Path::new(INPUT), &map, &partitions);
+        assert_eq!((0, 4), (records[0].chrom_start, records[0].chrom_end));
+        assert_eq!((4, 10), (records[1].chrom_start, records[1].chrom_end));
+        assert_eq!(Some(String::from("gene_2")), records[1].name);
+    }
+}
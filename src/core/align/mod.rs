@@ -1,8 +1,11 @@
 //! Alignment modules that handle alignment operation.
+pub mod bait;
 pub mod concat;
 pub mod convert;
+pub mod coordinate;
 pub mod filter;
 pub mod partition;
+pub mod slice;
 pub mod split;
 pub mod summarize;
 pub mod trim;
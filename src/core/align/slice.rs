@@ -0,0 +1,431 @@
+//! Slice alignments into regions.
+//!
+//! Regions are either column ranges of the alignments
+//! or BED intervals in the coordinates of a reference sequence.
+//! Column ranges are 1-based and inclusive, similar to partitions.
+//! BED intervals are 0-based and half-open.
+//! For BED intervals, the reference coordinates are mapped to alignment columns
+//! by skipping gaps in the reference sequence. Insertions relative to
+//! the reference within an interval are kept. An interval is applied
+//! to the alignment whose file name (without extension) matches
+//! the interval chromosome name.
+//! Empty or reversed regions and regions outside the alignment
+//! are skipped with a warning.
+//! Each region is written to its own file.
+//! Duplicate region names in an alignment get the 1-based alignment columns
+//! appended to keep the output file names unique.
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ahash::AHashSet as HashSet;
+use colored::Colorize;
+use rayon::prelude::*;
+
+use crate::core::OutputPrint;
+use crate::core::align::coordinate::ReferenceMap;
+use crate::helper::files;
+use crate::helper::sequence::SeqParser;
+use crate::helper::types::{DataType, Header, InputFmt, OutputFmt, SeqMatrix};
+use crate::helper::utils;
+use crate::parser::bed::BedRecord;
+use crate::writer::sequences::SeqWriter;
+
+impl OutputPrint for AlignmentSlicing<'_> {}
+
+/// Regions to slice the alignments.
+pub enum SliceParameters {
+    /// 1-based inclusive column ranges applied to every alignment.
+    Range(Vec<(usize, usize)>),
+    /// BED intervals in the coordinates of the reference sequence.
+    Bed {
+        records: Vec<BedRecord>,
+        reference: String,
+    },
+}
+
+/// A slice of an alignment ready to write.
+struct Region {
+    /// Region name used for the output file.
+    name: String,
+    /// 0-based start column.
+    start: usize,
+    /// 0-based exclusive end column.
+    end: usize,
+}
+
+pub struct AlignmentSlicing<'a> {
+    input_fmt: &'a InputFmt,
+    datatype: &'a DataType,
+    output: &'a Path,
+    output_fmt: &'a OutputFmt,
+    params: &'a SliceParameters,
+}
+
+impl<'a> AlignmentSlicing<'a> {
+    pub fn new(
+        input_fmt: &'a InputFmt,
+        datatype: &'a DataType,
+        output: &'a Path,
+        output_fmt: &'a OutputFmt,
+        params: &'a SliceParameters,
+    ) -> Self {
+        Self {
+            input_fmt,
+            datatype,
+            output,
+            output_fmt,
+            params,
+        }
+    }
+
+    /// Slice each alignment and write one file per region.
+    /// Returns the number of written files.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::{DataType, InputFmt, OutputFmt};
+    /// use segul::core::align::slice::{AlignmentSlicing, SliceParameters};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/slice/chr1.fas")];
+    /// let params = SliceParameters::Range(vec![(1, 4), (5, 8)]);
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let handle = AlignmentSlicing::new(
+    ///     &InputFmt::Fasta, &DataType::Dna, output.path(), &OutputFmt::Fasta, &params
+    /// );
+    /// let counts = handle.slice(&files);
+    /// assert_eq!(counts, 2);
+    /// ```
+    pub fn slice(&self, files: &[PathBuf]) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Slicing alignments...");
+        let file_counts = AtomicUsize::new(0);
+        files.par_iter().for_each(|file| {
+            let (matrix, header) =
+                SeqParser::new(file, self.datatype).get_alignment(self.input_fmt);
+            let regions = self.get_regions(file, &matrix, header.nchar);
+            regions.iter().for_each(|region| {
+                let sliced = self.slice_matrix(&matrix, region.start, region.end);
+                if sliced.is_empty() {
+                    return;
+                }
+                self.write_region(file, &region.name, &sliced, &header);
+                file_counts.fetch_add(1, Ordering::Relaxed);
+            });
+        });
+        spin.finish_with_message("Finished slicing alignments!\n");
+        let file_counts = file_counts.load(Ordering::Relaxed);
+        self.print_output_info(file_counts);
+        file_counts
+    }
+
+    fn get_regions(&self, file: &Path, matrix: &SeqMatrix, nchar: usize) -> Vec<Region> {
+        let mut regions = self.parse_regions(file, matrix, nchar);
+        self.make_unique_names(file, &mut regions);
+        regions
+    }
+
+    fn parse_regions(&self, file: &Path, matrix: &SeqMatrix, nchar: usize) -> Vec<Region> {
+        match self.params {
+            SliceParameters::Range(ranges) => ranges
+                .iter()
+                .filter_map(|(start, end)| {
+                    if *start == 0 || start > end {
+                        log::warn!(
+                            "{} Range {}-{} is invalid. Ranges are 1-based \
+                            and the start must not exceed the end. Skipping it.",
+                            "WARNING!".red(),
+                            start,
+                            end
+                        );
+                        return None;
+                    }
+                    if *end > nchar {
+                        log::warn!(
+                            "{} Range {}-{} is outside {} ({} sites). Skipping it.",
+                            "WARNING!".red(),
+                            start,
+                            end,
+                            file.display(),
+                            nchar
+                        );
+                        return None;
+                    }
+                    Some(Region {
+                        name: format!("{}-{}", start, end),
+                        start: start - 1,
+                        end: *end,
+                    })
+                })
+                .collect(),
+            SliceParameters::Bed { records, reference } => {
+                let chrom = file
+                    .file_stem()
+                    .expect("Failed to get file stem")
+                    .to_string_lossy();
+                let records: Vec<&BedRecord> =
+                    records.iter().filter(|r| r.chrom == chrom).collect();
+                if records.is_empty() {
+                    return Vec::new();
+                }
+                let ref_seq = matrix.get(reference).unwrap_or_else(|| {
+                    panic!(
+                        "Reference sequence {} is not found in {}",
+                        reference,
+                        file.display()
+                    )
+                });
+                let map = ReferenceMap::new(ref_seq);
+                records
+                    .iter()
+                    .filter_map(|r| {
+                        if r.chrom_start >= r.chrom_end {
+                            log::warn!(
+                                "{} Interval {}:{}-{} is empty or reversed. Skipping it.",
+                                "WARNING!".red(),
+                                r.chrom,
+                                r.chrom_start,
+                                r.chrom_end
+                            );
+                            return None;
+                        }
+                        if r.chrom_end > map.reference_len() {
+                            log::warn!(
+                                "{} Interval {}:{}-{} is outside the reference sequence \
+                                ({} bp). Skipping it.",
+                                "WARNING!".red(),
+                                r.chrom,
+                                r.chrom_start,
+                                r.chrom_end,
+                                map.reference_len()
+                            );
+                            return None;
+                        }
+                        let name = match &r.name {
+                            Some(name) => name.to_string(),
+                            None => format!("{}-{}", r.chrom_start, r.chrom_end),
+                        };
+                        Some(Region {
+                            name,
+                            start: map.column(r.chrom_start)?,
+                            end: map.column(r.chrom_end - 1)? + 1,
+                        })
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    // Region names are used as output file names and must be unique.
+    // Duplicates get the 1-based alignment columns appended
+    // and a counter if the columns are duplicated too.
+    fn make_unique_names(&self, file: &Path, regions: &mut [Region]) {
+        let mut names: HashSet<String> = HashSet::new();
+        regions.iter_mut().for_each(|region| {
+            let name = sanitize_name(&region.name);
+            let mut unique = name.clone();
+            let mut counter = 1;
+            while names.contains(&unique) {
+                unique = format!("{}_{}-{}", name, region.start + 1, region.end);
+                if counter > 1 {
+                    unique = format!("{}_{}", unique, counter);
+                }
+                counter += 1;
+            }
+            if unique != name {
+                log::warn!(
+                    "{} Region {} is duplicated in {}. Renaming it to {}.",
+                    "WARNING!".red(),
+                    region.name,
+                    file.display(),
+                    unique
+                );
+            }
+            names.insert(unique.clone());
+            region.name = unique;
+        });
+    }
+
+    // Sequences that only contain gaps or missing data
+    // in the region are excluded.
+    fn slice_matrix(&self, matrix: &SeqMatrix, start: usize, end: usize) -> SeqMatrix {
+        matrix
+            .iter()
+            .filter_map(|(id, seq)| {
+                let part = seq
+                    .get(start..end)
+                    .expect("Errors in slicing the alignment");
+                if part.bytes().all(|c| c == b'-' || c == b'?') {
+                    None
+                } else {
+                    Some((id.to_string(), part.to_string()))
+                }
+            })
+            .collect()
+    }
+
+    fn write_region(&self, file: &Path, region: &str, matrix: &SeqMatrix, header: &Header) {
+        let file_stem = file
+            .file_stem()
+            .expect("Failed to get file stem")
+            .to_string_lossy();
+        let fname = format!("{}_{}", file_stem, region);
+        let output_path =
+            files::create_output_fname_from_path(&self.output.join(fname), self.output_fmt);
+        let mut header = header.clone();
+        header.ntax = matrix.len();
+        header.nchar = matrix.values().next().map_or(0, |s| s.len());
+        let mut writer = SeqWriter::new(&output_path, matrix, &header);
+        writer
+            .write_sequence(self.output_fmt)
+            .expect("Failed writing the output file");
+    }
+
+    fn print_output_info(&self, file_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&file_counts));
+        log::info!("{:18}: {}", "Output dir", self.output.display());
+        self.print_output_fmt(self.output_fmt);
+    }
+}
+
+// Keep only alphanumeric characters, underscores, and dashes
+// to create a valid file name.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    const INPUT: &str = "tests/files/slice/chr1.fas";
+
+    fn bed_params() -> SliceParameters {
+        SliceParameters::Bed {
+            records: vec![
+                BedRecord::new(String::from("chr1"), 2, 6, Some(String::from("gene1"))),
+                BedRecord::new(String::from("chr2"), 0, 4, None),
+            ],
+            reference: String::from("ref"),
+        }
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!("gene_1-a", sanitize_name("gene.1-a"));
+    }
+
+    #[test]
+    fn test_range_regions() {
+        let params = SliceParameters::Range(vec![(1, 4), (9, 20)]);
+        let handle = AlignmentSlicing::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            &OutputFmt::Fasta,
+            &params,
+        );
+        let (matrix, header) =
+            SeqParser::new(Path::new(INPUT), &DataType::Dna).get_alignment(&InputFmt::Fasta);
+        let regions = handle.get_regions(Path::new(INPUT), &matrix, header.nchar);
+        assert_eq!(1, regions.len());
+        assert_eq!("1-4", regions[0].name);
+        assert_eq!((0, 4), (regions[0].start, regions[0].end));
+    }
+
+    #[test]
+    fn test_invalid_regions() {
+        let params = SliceParameters::Range(vec![(0, 4), (5, 3), (2, 2)]);
+        let handle = AlignmentSlicing::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            &OutputFmt::Fasta,
+            &params,
+        );
+        let (matrix, header) =
+            SeqParser::new(Path::new(INPUT), &DataType::Dna).get_alignment(&InputFmt::Fasta);
+        let regions = handle.get_regions(Path::new(INPUT), &matrix, header.nchar);
+        assert_eq!(1, regions.len());
+        assert_eq!((1, 2), (regions[0].start, regions[0].end));
+
+        let params = SliceParameters::Bed {
+            records: vec![
+                BedRecord::new(String::from("chr1"), 0, 0, None),
+                BedRecord::new(String::from("chr1"), 6, 2, None),
+                BedRecord::new(String::from("chr1"), 2, 6, None),
+            ],
+            reference: String::from("ref"),
+        };
+        let handle = AlignmentSlicing::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            &OutputFmt::Fasta,
+            &params,
+        );
+        let regions = handle.get_regions(Path::new(INPUT), &matrix, header.nchar);
+        assert_eq!(1, regions.len());
+        assert_eq!("2-6", regions[0].name);
+    }
+
+    #[test]
+    fn test_duplicate_region_names() {
+        let params = SliceParameters::Bed {
+            records: vec![
+                BedRecord::new(String::from("chr1"), 0, 2, Some(String::from("gene.1"))),
+                BedRecord::new(String::from("chr1"), 2, 6, Some(String::from("gene_1"))),
+                BedRecord::new(String::from("chr1"), 2, 6, Some(String::from("gene_1"))),
+            ],
+            reference: String::from("ref"),
+        };
+        let output = TempDir::new("temp").unwrap();
+        let handle = AlignmentSlicing::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            output.path(),
+            &OutputFmt::Fasta,
+            &params,
+        );
+        let (matrix, header) =
+            SeqParser::new(Path::new(INPUT), &DataType::Dna).get_alignment(&InputFmt::Fasta);
+        let regions = handle.get_regions(Path::new(INPUT), &matrix, header.nchar);
+        let names: Vec<&str> = regions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(vec!["gene_1", "gene_1_3-8", "gene_1_3-8_2"], names);
+        assert_eq!(3, handle.slice(&[PathBuf::from(INPUT)]));
+        assert_eq!(3, output.path().read_dir().unwrap().count());
+    }
+
+    #[test]
+    fn test_bed_regions() {
+        let params = bed_params();
+        let handle = AlignmentSlicing::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            &OutputFmt::Fasta,
+            &params,
+        );
+        let (matrix, header) =
+            SeqParser::new(Path::new(INPUT), &DataType::Dna).get_alignment(&InputFmt::Fasta);
+        let regions = handle.get_regions(Path::new(INPUT), &matrix, header.nchar);
+        assert_eq!(1, regions.len());
+        assert_eq!("gene1", regions[0].name);
+        let sliced = handle.slice_matrix(&matrix, regions[0].start, regions[0].end);
+        assert_eq!("GC--AT", sliced["ref"]);
+        assert_eq!("GCTTAT", sliced["sample_1"]);
+        assert!(!sliced.contains_key("sample_3"));
+    }
+}
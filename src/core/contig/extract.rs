@@ -0,0 +1,376 @@
+//! Extract regions from contig files by coordinates.
+//!
+//! Regions are read from plain FASTA files using their `.fai` index.
+//! Files without an index are indexed in memory.
+//! Compressed files are not supported, because they do not allow
+//! random access to the contigs.
+//! Region strings are matched as whole contig names first,
+//! so contig names may contain colons, e.g. `HLA-A*01:01`.
+//! Regions on the reverse strand are reverse complemented.
+//! All regions are written to a single FASTA file.
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+
+use super::index::{FaiRecord, FastaIndex};
+use crate::{
+    helper::{
+        alphabet,
+        files::{self, Compression},
+        types::DnaStrand,
+        utils,
+    },
+    parser::{bed::BedRecord, fasta::Records},
+    writer::contigs::ContigWriter,
+};
+
+const EXTRACT_OUTPUT_FNAME: &str = "contig-regions";
+
+/// A contig region to extract.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContigRegion {
+    pub chrom: String,
+    /// 0-based start position
+    pub start: usize,
+    /// 0-based exclusive end position.
+    /// None extracts to the end of the contig.
+    pub end: Option<usize>,
+    pub strand: DnaStrand,
+    /// Name of the extracted sequence
+    pub name: Option<String>,
+    /// Region string as given. Used as a contig name
+    /// if a contig has the same name.
+    pub query: Option<String>,
+}
+
+impl ContigRegion {
+    pub fn from_bed(record: &BedRecord) -> Self {
+        Self {
+            chrom: record.chrom.clone(),
+            start: record.chrom_start,
+            end: Some(record.chrom_end),
+            strand: record.strand.unwrap_or_default(),
+            name: record.name.clone(),
+            query: None,
+        }
+    }
+
+    /// Name of the extracted sequence.
+    /// Use the region name if available. Otherwise,
+    /// use the 1-based coordinates, e.g. `chr1:11-20(-)`.
+    pub fn label(&self, length: usize) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let end = self.end.unwrap_or(length).min(length);
+        let strand = match self.strand {
+            DnaStrand::Reverse => "(-)",
+            _ => "",
+        };
+        format!("{}:{}-{}{}", self.chrom, self.start + 1, end, strand)
+    }
+}
+
+/// Parse a samtools-style region string.
+/// Coordinates are 1-based and inclusive.
+/// An optional strand can be appended after a colon.
+/// Strings without coordinates after the last colon
+/// are whole contig names.
+/// # Example
+/// ```
+/// use segul::core::contig::extract::ContigRegion;
+/// use segul::helper::types::DnaStrand;
+///
+/// let region: ContigRegion = "chr1:11-20:-".parse().unwrap();
+/// assert_eq!(region.start, 10);
+/// assert_eq!(region.end, Some(20));
+/// assert_eq!(region.strand, DnaStrand::Reverse);
+/// let contig: ContigRegion = "chr1".parse().unwrap();
+/// assert_eq!(contig.end, None);
+/// let contig: ContigRegion = "HLA-A*01:01".parse().unwrap();
+/// assert_eq!(contig.chrom, "HLA-A*01:01");
+/// ```
+impl std::str::FromStr for ContigRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (region, strand) = match s.rsplit_once(':') {
+            Some((region, "+")) => (region, DnaStrand::Forward),
+            Some((region, "-")) => (region, DnaStrand::Reverse),
+            _ => (s, DnaStrand::Missing),
+        };
+        let invalid = || format!("{} is not a valid region", s);
+        let (chrom, start, end) = match region.rsplit_once(':') {
+            Some((chrom, coords)) if coords.contains('-') => {
+                let (start, end) = coords.split_once('-').ok_or_else(invalid)?;
+                let start: usize = start.replace(',', "").parse().map_err(|_| invalid())?;
+                let end: usize = end.replace(',', "").parse().map_err(|_| invalid())?;
+                if start == 0 || start > end {
+                    return Err(invalid());
+                }
+                (chrom, start - 1, Some(end))
+            }
+            _ => (region, 0, None),
+        };
+        if chrom.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            chrom: chrom.to_string(),
+            start,
+            end,
+            strand,
+            name: None,
+            query: Some(s.to_string()),
+        })
+    }
+}
+
+pub struct ContigExtractor<'a> {
+    files: &'a [PathBuf],
+    output: &'a Path,
+    regions: &'a [ContigRegion],
+}
+
+impl<'a> ContigExtractor<'a> {
+    pub fn new(files: &'a [PathBuf], output: &'a Path, regions: &'a [ContigRegion]) -> Self {
+        Self {
+            files,
+            output,
+            regions,
+        }
+    }
+
+    /// Extract the regions and write them to the output directory.
+    /// Returns the number of extracted regions.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::contig::extract::{ContigExtractor, ContigRegion};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/contigs/contigs2.fa")];
+    /// let regions: Vec<ContigRegion> = vec!["TR3|c0_g1_i1:1-10".parse().unwrap()];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let counts = ContigExtractor::new(&files, output.path(), &regions).extract();
+    /// assert_eq!(counts, 1);
+    /// ```
+    pub fn extract(&self) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Indexing contig files...");
+        let contigs = self.index_contigs();
+        spin.set_message("Extracting regions...");
+        let mut readers: HashMap<usize, File> = HashMap::new();
+        let sequences: Vec<Records> = self
+            .regions
+            .iter()
+            .filter_map(|region| {
+                let region = self.match_contig_name(region, &contigs);
+                let Some((file_idx, record)) = contigs.get(region.chrom.as_str()) else {
+                    log::warn!(
+                        "{} Contig {} is not found. Skipping it.",
+                        "WARNING!".red(),
+                        region.chrom
+                    );
+                    return None;
+                };
+                if region.start >= record.length {
+                    log::warn!(
+                        "{} Region {} is outside the contig. Skipping it.",
+                        "WARNING!".red(),
+                        region.label(record.length)
+                    );
+                    return None;
+                }
+                let reader = readers.entry(*file_idx).or_insert_with(|| {
+                    File::open(&self.files[*file_idx]).expect("Failed opening contig file")
+                });
+                let end = region.end.unwrap_or(record.length);
+                let mut seq = record
+                    .fetch(reader, region.start, end)
+                    .expect("Failed reading contig file");
+                if region.strand == DnaStrand::Reverse {
+                    seq = alphabet::reverse_complement(&seq);
+                }
+                Some(Records::new(
+                    &region.label(record.length),
+                    &String::from_utf8_lossy(&seq),
+                ))
+            })
+            .collect();
+        let output = self
+            .output
+            .join(EXTRACT_OUTPUT_FNAME)
+            .with_extension("fasta");
+        ContigWriter::new(&output)
+            .write(&sequences)
+            .expect("Failed writing extracted regions");
+        spin.finish_with_message("Finished extracting regions!\n");
+        self.print_output_info(&output, sequences.len());
+        sequences.len()
+    }
+
+    // Use the whole region string as the contig name
+    // if a contig has that name, e.g. `HLA-A*01:01:1-10`.
+    fn match_contig_name(
+        &self,
+        region: &ContigRegion,
+        contigs: &HashMap<String, (usize, FaiRecord)>,
+    ) -> ContigRegion {
+        match &region.query {
+            Some(query) if *query != region.chrom && contigs.contains_key(query) => ContigRegion {
+                chrom: query.clone(),
+                start: 0,
+                end: None,
+                strand: DnaStrand::Missing,
+                name: region.name.clone(),
+                query: None,
+            },
+            _ => region.clone(),
+        }
+    }
+
+    // Map contig names to their file and index record.
+    // The first file is used for duplicate contig names.
+    fn index_contigs(&self) -> HashMap<String, (usize, FaiRecord)> {
+        let mut contigs = HashMap::new();
+        self.files.iter().enumerate().for_each(|(i, file)| {
+            // Check before loading an index, because the offsets
+            // of an existing index are invalid for compressed files.
+            if files::detect_compression(file) != Compression::None {
+                panic!(
+                    "{} is compressed. Decompress the contig files to extract regions.",
+                    file.display()
+                );
+            }
+            let index = FastaIndex::from_fasta_path(file)
+                .or_else(|| FastaIndex::build(file))
+                .expect("Failed indexing contig file");
+            index.records.into_iter().for_each(|record| {
+                if contigs.contains_key(&record.name) {
+                    log::warn!(
+                        "{} Contig {} is found in multiple files. Using the first file.",
+                        "WARNING!".red(),
+                        record.name
+                    );
+                } else {
+                    contigs.insert(record.name.clone(), (i, record));
+                }
+            });
+        });
+        contigs
+    }
+
+    fn print_output_info(&self, output: &Path, region_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output file", output.display());
+        log::info!("{:18}: {}", "Region counts", utils::fmt_num(&region_counts));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    use crate::helper::types::ContigFmt;
+    use crate::parser::fasta::FastaReader;
+    use crate::stats::contigs::open_contigs;
+
+    #[test]
+    fn test_parse_region() {
+        let region: ContigRegion = "TR1|c0:1,001-2,000".parse().unwrap();
+        assert_eq!(
+            ("TR1|c0", 1000, Some(2000)),
+            (region.chrom.as_str(), region.start, region.end)
+        );
+        assert!("chr1:0-10".parse::<ContigRegion>().is_err());
+        assert!("chr1:20-10".parse::<ContigRegion>().is_err());
+        assert!("chr1:a-10".parse::<ContigRegion>().is_err());
+        let region: ContigRegion = "HLA-A*01:01:1-10:-".parse().unwrap();
+        assert_eq!(
+            ("HLA-A*01:01", 0, Some(10), DnaStrand::Reverse),
+            (
+                region.chrom.as_str(),
+                region.start,
+                region.end,
+                region.strand
+            )
+        );
+    }
+
+    #[test]
+    fn test_extract_colon_names() {
+        let input = TempDir::new("input").unwrap();
+        let file = input.path().join("contigs.fa");
+        std::fs::write(&file, ">HLA-A*01:01\nACGTACGT\n>chr1:1-4\nTTTTGGGG\n").unwrap();
+        let files = vec![file];
+        let regions: Vec<ContigRegion> = ["HLA-A*01:01", "HLA-A*01:01:2-3", "chr1:1-4"]
+            .iter()
+            .map(|r| r.parse().unwrap())
+            .collect();
+        let output = TempDir::new("temp").unwrap();
+        let counts = ContigExtractor::new(&files, output.path(), &regions).extract();
+        assert_eq!(3, counts);
+        let reader = FastaReader::new(open_contigs(
+            &output.path().join("contig-regions.fasta"),
+            &ContigFmt::Fasta,
+        ));
+        let records: Vec<Records> = reader.collect();
+        assert_eq!("ACGTACGT", records[0].seq);
+        assert_eq!("CG", records[1].seq);
+        // Whole contig names take precedence over coordinates.
+        assert_eq!("TTTTGGGG", records[2].seq);
+    }
+
+    #[test]
+    #[should_panic(expected = "is compressed")]
+    fn test_extract_compressed_with_index() {
+        use flate2::{Compression as GzCompression, write::GzEncoder};
+        use std::io::Write;
+
+        let input = TempDir::new("input").unwrap();
+        let file = input.path().join("contigs.fa.gz");
+        let mut encoder = GzEncoder::new(
+            std::fs::File::create(&file).unwrap(),
+            GzCompression::default(),
+        );
+        encoder.write_all(b">chr1\nACGTACGT\n").unwrap();
+        encoder.finish().unwrap();
+        std::fs::write(input.path().join("contigs.fa.gz.fai"), "chr1\t8\t6\t8\t9\n").unwrap();
+        let regions: Vec<ContigRegion> = vec!["chr1:1-4".parse().unwrap()];
+        let output = TempDir::new("temp").unwrap();
+        ContigExtractor::new(&[file], output.path(), &regions).extract();
+    }
+
+    #[test]
+    fn test_extract_reverse() {
+        let files = vec![PathBuf::from("tests/files/contigs/contigs1.fa")];
+        let mut bed = BedRecord::new(String::from("TR1|c0_g1_i1"), 56, 62, None);
+        bed.strand = Some(DnaStrand::Reverse);
+        let regions = vec![
+            ContigRegion::from_bed(&bed),
+            "TR1|c0_g1_i1:57-62".parse().unwrap(),
+            "missing:1-10".parse().unwrap(),
+        ];
+        let output = TempDir::new("temp").unwrap();
+        let counts = ContigExtractor::new(&files, output.path(), &regions).extract();
+        assert_eq!(2, counts);
+        let reader = FastaReader::new(open_contigs(
+            &output.path().join("contig-regions.fasta"),
+            &ContigFmt::Fasta,
+        ));
+        let records: Vec<Records> = reader.collect();
+        // The region spans two sequence lines.
+        assert_eq!("TR1|c0_g1_i1:57-62(-)", records[0].id);
+        assert_eq!("AATTTG", records[0].seq);
+        assert_eq!("TR1|c0_g1_i1:57-62", records[1].id);
+        assert_eq!("CAAATT", records[1].seq);
+    }
+}
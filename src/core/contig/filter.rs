@@ -0,0 +1,245 @@
+//! Filter contigs by length, ambiguous bases, and GC content.
+//!
+//! Support FASTA and compressed FASTA in gunzip format.
+//! Contigs are kept if they pass all the given filters.
+//! The top-N filter is applied last to the remaining contigs.
+//! Kept contigs are written in their input order
+//! to one FASTA file per input file.
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::{check_unique_contig_outputs, contig_output_path};
+use crate::{
+    helper::{types::ContigFmt, utils},
+    parser::fasta::{FastaReader, Records},
+    stats::contigs::open_contigs,
+    writer::contigs::ContigWriter,
+};
+
+const FILTER_SUMMARY_FNAME: &str = "contig-filter-summary";
+
+/// Contig filtering parameters.
+/// Filters with no value are skipped.
+#[derive(Debug, Clone, Default)]
+pub struct ContigFilterParameters {
+    /// Minimum contig length
+    pub min_length: Option<usize>,
+    /// Maximum contig length
+    pub max_length: Option<usize>,
+    /// Maximum proportion of N bases in a contig
+    pub max_n: Option<f64>,
+    /// Minimum GC content (0-1)
+    pub min_gc: Option<f64>,
+    /// Maximum GC content (0-1)
+    pub max_gc: Option<f64>,
+    /// Number of the longest contigs to keep
+    pub top_n: Option<usize>,
+}
+
+impl ContigFilterParameters {
+    /// Check whether a contig passes the length, N, and GC filters.
+    pub fn is_match(&self, seq: &str) -> bool {
+        let length = seq.len();
+        if self.min_length.is_some_and(|min| length < min)
+            || self.max_length.is_some_and(|max| length > max)
+        {
+            return false;
+        }
+        let (gc_count, n_count) = count_gc_n(seq);
+        let n_prop = if length == 0 {
+            0.0
+        } else {
+            n_count as f64 / length as f64
+        };
+        if self.max_n.is_some_and(|max| n_prop > max) {
+            return false;
+        }
+        // GC content of the unambiguous bases
+        let bases = length - n_count;
+        let gc = if bases == 0 {
+            0.0
+        } else {
+            gc_count as f64 / bases as f64
+        };
+        !(self.min_gc.is_some_and(|min| gc < min) || self.max_gc.is_some_and(|max| gc > max))
+    }
+}
+
+/// Contig counts of a filtered file.
+#[derive(Debug, Serialize)]
+pub struct ContigFilterRecord {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub contig_count: usize,
+    pub kept_count: usize,
+}
+
+pub struct ContigFilter<'a> {
+    files: &'a [PathBuf],
+    input_fmt: &'a ContigFmt,
+    output: &'a Path,
+    params: &'a ContigFilterParameters,
+}
+
+impl<'a> ContigFilter<'a> {
+    pub fn new(
+        files: &'a [PathBuf],
+        input_fmt: &'a ContigFmt,
+        output: &'a Path,
+        params: &'a ContigFilterParameters,
+    ) -> Self {
+        Self {
+            files,
+            input_fmt,
+            output,
+            params,
+        }
+    }
+
+    /// Filter contigs and write the kept contigs to the output directory.
+    /// Returns the number of kept contigs.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::contig::filter::{ContigFilter, ContigFilterParameters};
+    /// use segul::helper::types::ContigFmt;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/contigs/contigs1.fa")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let params = ContigFilterParameters {
+    ///     min_length: Some(250),
+    ///     ..Default::default()
+    /// };
+    /// let counts = ContigFilter::new(&files, &ContigFmt::Auto, output.path(), &params)
+    ///     .filter();
+    /// assert_eq!(counts, 1);
+    /// ```
+    pub fn filter(&self) -> usize {
+        check_unique_contig_outputs(self.output, self.files);
+        let spin = utils::set_spinner();
+        spin.set_message("Filtering contigs...");
+        let mut records: Vec<ContigFilterRecord> = self
+            .files
+            .par_iter()
+            .map(|file| self.filter_contigs(file))
+            .collect();
+        records.sort_by(|a, b| a.input.cmp(&b.input));
+        self.write_summary(&records);
+        spin.finish_with_message("Finished filtering contigs!\n");
+        let kept_counts = records.iter().map(|rec| rec.kept_count).sum();
+        self.print_output_info(&records, kept_counts);
+        kept_counts
+    }
+
+    fn filter_contigs(&self, file: &Path) -> ContigFilterRecord {
+        let reader = FastaReader::new(open_contigs(file, self.input_fmt));
+        let mut contig_count = 0;
+        let mut contigs: Vec<Records> = reader
+            .inspect(|_| contig_count += 1)
+            .filter(|contig| self.params.is_match(&contig.seq))
+            .collect();
+        if let Some(top_n) = self.params.top_n {
+            contigs = self.select_longest(contigs, top_n);
+        }
+        let output = contig_output_path(self.output, file);
+        ContigWriter::new(&output)
+            .write(&contigs)
+            .expect("Failed writing contig file");
+        ContigFilterRecord {
+            input: file.to_path_buf(),
+            output,
+            contig_count,
+            kept_count: contigs.len(),
+        }
+    }
+
+    // Keep the longest contigs in their input order.
+    // Ties are broken by the input order.
+    fn select_longest(&self, contigs: Vec<Records>, top_n: usize) -> Vec<Records> {
+        if contigs.len() <= top_n {
+            return contigs;
+        }
+        let mut ranks: Vec<usize> = (0..contigs.len()).collect();
+        ranks.sort_by(|a, b| contigs[*b].seq.len().cmp(&contigs[*a].seq.len()));
+        let mut selected = ranks[..top_n].to_vec();
+        selected.sort_unstable();
+        let mut contigs: Vec<Option<Records>> = contigs.into_iter().map(Some).collect();
+        selected.iter().filter_map(|i| contigs[*i].take()).collect()
+    }
+
+    fn write_summary(&self, records: &[ContigFilterRecord]) {
+        let output = self.output.join(FILTER_SUMMARY_FNAME).with_extension("csv");
+        let mut writer = csv::Writer::from_path(output).expect("Failed to create CSV writer");
+        records.iter().for_each(|rec| {
+            writer.serialize(rec).expect("Failed to write CSV record");
+        });
+        writer.flush().expect("Failed to flush CSV writer");
+    }
+
+    fn print_output_info(&self, records: &[ContigFilterRecord], kept_counts: usize) {
+        let contig_counts: usize = records.iter().map(|rec| rec.contig_count).sum();
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&records.len()));
+        log::info!("{:18}: {}", "Contig counts", utils::fmt_num(&contig_counts));
+        log::info!("{:18}: {}", "Kept contigs", utils::fmt_num(&kept_counts));
+        log::info!("{:18}: {}", "Output format", "FASTA");
+    }
+}
+
+// Count GC and N bases in a sequence.
+fn count_gc_n(seq: &str) -> (usize, usize) {
+    seq.bytes().fold((0, 0), |(gc, n), base| match base {
+        b'G' | b'g' | b'C' | b'c' => (gc + 1, n),
+        b'N' | b'n' => (gc, n + 1),
+        _ => (gc, n),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_is_match() {
+        let params = ContigFilterParameters {
+            min_length: Some(4),
+            max_n: Some(0.25),
+            min_gc: Some(0.5),
+            ..Default::default()
+        };
+        assert!(params.is_match("GCATNGCA"));
+        assert!(!params.is_match("GCA"));
+        assert!(!params.is_match("GCNNAT"));
+        assert!(!params.is_match("ATATAT"));
+    }
+
+    #[test]
+    fn test_filter_top_n() {
+        let files = vec![
+            PathBuf::from("tests/files/contigs/contigs1.fa"),
+            PathBuf::from("tests/files/contigs/contigs2.fa"),
+        ];
+        let output = TempDir::new("temp").unwrap();
+        let params = ContigFilterParameters {
+            top_n: Some(1),
+            ..Default::default()
+        };
+        let handle = ContigFilter::new(&files, &ContigFmt::Auto, output.path(), &params);
+        assert_eq!(2, handle.filter());
+        let reader = FastaReader::new(open_contigs(
+            &output.path().join("contigs1.fasta"),
+            &ContigFmt::Fasta,
+        ));
+        let ids: Vec<String> = reader.map(|contig| contig.id).collect();
+        assert_eq!(1, ids.len());
+        assert!(ids[0].starts_with("TR2"));
+    }
+}
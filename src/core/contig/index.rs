@@ -0,0 +1,303 @@
+//! Index contig files in the samtools `.fai` format.
+//!
+//! The index is a tab-separated text file written next to the FASTA file
+//! with the `.fai` extension appended, e.g. `contigs.fa.fai`.
+//! Each line records a contig: name, length, byte offset of the first base,
+//! bases per line, and bytes per line including the line ending.
+//! Only plain FASTA files can be indexed. Sequence lines of a contig
+//! must have the same length, except the last line.
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use colored::Colorize;
+use rayon::prelude::*;
+
+use crate::helper::{
+    files::{self, Compression},
+    utils,
+};
+
+const INDEX_EXTENSION: &str = "fai";
+
+/// A contig entry in a FASTA index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaiRecord {
+    /// Contig name without the description
+    pub name: String,
+    /// Number of bases
+    pub length: usize,
+    /// Byte offset of the first base
+    pub offset: u64,
+    /// Number of bases per line
+    pub line_bases: usize,
+    /// Number of bytes per line including the line ending
+    pub line_width: usize,
+}
+
+pub struct FastaIndex {
+    pub records: Vec<FaiRecord>,
+}
+
+impl FastaIndex {
+    /// Path of the index file of a FASTA file.
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use segul::core::contig::index::FastaIndex;
+    ///
+    /// let path = FastaIndex::index_path(Path::new("contigs.fa"));
+    /// assert_eq!(path, Path::new("contigs.fa.fai"));
+    /// ```
+    pub fn index_path(fasta_path: &Path) -> PathBuf {
+        let mut path = fasta_path.as_os_str().to_owned();
+        path.push(".");
+        path.push(INDEX_EXTENSION);
+        PathBuf::from(path)
+    }
+
+    /// Build an index by scanning a plain FASTA file.
+    /// Returns `None` for compressed files.
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use segul::core::contig::index::FastaIndex;
+    ///
+    /// let index = FastaIndex::build(Path::new("tests/files/contigs/contigs1.fa")).unwrap();
+    /// assert_eq!(index.records.len(), 2);
+    /// assert_eq!(index.records[0].length, 227);
+    /// assert_eq!(index.records[0].line_bases, 60);
+    /// ```
+    pub fn build(fasta_path: &Path) -> Option<Self> {
+        if files::detect_compression(fasta_path) != Compression::None {
+            return None;
+        }
+        let mut reader = BufReader::new(File::open(fasta_path).expect("Unable to open file"));
+        let records = scan_contigs(&mut reader)
+            .unwrap_or_else(|e| panic!("Failed indexing {}: {}", fasta_path.display(), e));
+        Some(Self { records })
+    }
+
+    /// Read the index of a FASTA file if it exists.
+    pub fn from_fasta_path(fasta_path: &Path) -> Option<Self> {
+        let index_path = Self::index_path(fasta_path);
+        if !index_path.exists() {
+            return None;
+        }
+        let reader = BufReader::new(File::open(&index_path).expect("Unable to open index"));
+        let records = reader
+            .lines()
+            .map_while(Result::ok)
+            .filter(|line| !line.is_empty())
+            .map(|line| parse_record(&line).expect("Invalid FASTA index entry"))
+            .collect();
+        Some(Self { records })
+    }
+
+    pub fn write(&self, output: &Path) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(output)?);
+        for record in &self.records {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}",
+                record.name, record.length, record.offset, record.line_bases, record.line_width
+            )?;
+        }
+        writer.flush()
+    }
+}
+
+impl FaiRecord {
+    /// Read bases of a 0-based, end-exclusive region of the contig.
+    /// The region is clipped to the contig length.
+    pub fn fetch<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        start: usize,
+        end: usize,
+    ) -> std::io::Result<Vec<u8>> {
+        let end = end.min(self.length);
+        if start >= end {
+            return Ok(Vec::new());
+        }
+        let line_start = start / self.line_bases;
+        let line_end = (end - 1) / self.line_bases;
+        let first =
+            self.offset + (line_start * self.line_width) as u64 + (start % self.line_bases) as u64;
+        let last = self.offset
+            + (line_end * self.line_width) as u64
+            + ((end - 1) % self.line_bases) as u64;
+        let mut bytes = vec![0; (last - first + 1) as usize];
+        reader.seek(SeekFrom::Start(first))?;
+        reader.read_exact(&mut bytes)?;
+        bytes.retain(|b| !b.is_ascii_whitespace());
+        Ok(bytes)
+    }
+}
+
+/// Build FASTA indexes and write them next to the contig files.
+pub struct ContigIndexer<'a> {
+    input_files: &'a [PathBuf],
+}
+
+impl<'a> ContigIndexer<'a> {
+    pub fn new(input_files: &'a [PathBuf]) -> Self {
+        Self { input_files }
+    }
+
+    /// Index the contig files. Existing indexes are replaced.
+    /// Returns the number of indexed files.
+    pub fn index(&self) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Indexing contig files...");
+        let file_counts = AtomicUsize::new(0);
+        let contig_counts = AtomicUsize::new(0);
+        self.input_files
+            .par_iter()
+            .for_each(|file| match FastaIndex::build(file) {
+                Some(index) => {
+                    index
+                        .write(&FastaIndex::index_path(file))
+                        .expect("Failed writing FASTA index");
+                    file_counts.fetch_add(1, Ordering::Relaxed);
+                    contig_counts.fetch_add(index.records.len(), Ordering::Relaxed);
+                }
+                None => log::warn!(
+                    "{} {} is compressed and cannot be indexed. \
+                    Decompress it to index it. Skipping it.",
+                    "WARNING!".red(),
+                    file.display()
+                ),
+            });
+        spin.finish_with_message("Finished indexing contig files!\n");
+        let file_counts = file_counts.load(Ordering::Relaxed);
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Indexed files", utils::fmt_num(&file_counts));
+        log::info!(
+            "{:18}: {}",
+            "Contig counts",
+            utils::fmt_num(&contig_counts.load(Ordering::Relaxed))
+        );
+        file_counts
+    }
+}
+
+// Record the offset and line layout of each contig.
+fn scan_contigs<R: BufRead>(reader: &mut R) -> Result<Vec<FaiRecord>, String> {
+    let mut records = Vec::new();
+    let mut line = Vec::new();
+    let mut offset: u64 = 0;
+    let mut current: Option<FaiRecord> = None;
+    // Length of the previous sequence line to check the line layout
+    let mut last_line: Option<usize> = None;
+    loop {
+        line.clear();
+        let bytes = reader
+            .read_until(b'\n', &mut line)
+            .map_err(|e| e.to_string())?;
+        if bytes == 0 {
+            break;
+        }
+        offset += bytes as u64;
+        if let Some(header) = line.strip_prefix(b">") {
+            records.extend(current.take());
+            let name = String::from_utf8_lossy(header)
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            current = Some(FaiRecord {
+                name,
+                length: 0,
+                offset,
+                line_bases: 0,
+                line_width: 0,
+            });
+            last_line = None;
+            continue;
+        }
+        let Some(record) = current.as_mut() else {
+            continue;
+        };
+        let bases = line.iter().filter(|b| !b.is_ascii_whitespace()).count();
+        if bases == 0 {
+            continue;
+        }
+        if let Some(previous) = last_line {
+            // Only the last line can be shorter.
+            if previous != record.line_bases || bases > record.line_bases {
+                return Err(format!(
+                    "Contig {} has sequence lines of different lengths",
+                    record.name
+                ));
+            }
+        } else {
+            record.line_bases = bases;
+            record.line_width = bytes;
+        }
+        record.length += bases;
+        last_line = Some(bases);
+    }
+    records.extend(current);
+    Ok(records)
+}
+
+fn parse_record(line: &str) -> Option<FaiRecord> {
+    let mut parts = line.split('\t');
+    Some(FaiRecord {
+        name: parts.next()?.to_string(),
+        length: parts.next()?.parse().ok()?,
+        offset: parts.next()?.parse().ok()?,
+        line_bases: parts.next()?.parse().ok()?,
+        line_width: parts.next()?.parse().ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    #[test]
+    fn test_scan_contigs() {
+        let fasta = b">c1 desc\nACGT\nACGT\nAC\n>c2\nGG\n";
+        let records = scan_contigs(&mut Cursor::new(&fasta[..])).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(
+            FaiRecord {
+                name: String::from("c1"),
+                length: 10,
+                offset: 9,
+                line_bases: 4,
+                line_width: 5,
+            },
+            records[0]
+        );
+        assert_eq!((2, 26), (records[1].length, records[1].offset));
+        let mut reader = Cursor::new(&fasta[..]);
+        assert_eq!(
+            b"GTACGTA".to_vec(),
+            records[0].fetch(&mut reader, 2, 9).unwrap()
+        );
+        assert_eq!(b"C".to_vec(), records[0].fetch(&mut reader, 9, 20).unwrap());
+    }
+
+    #[test]
+    fn test_inconsistent_lines() {
+        let fasta = b">c1\nACG\nACGT\nAC\n";
+        assert!(scan_contigs(&mut Cursor::new(&fasta[..])).is_err());
+        let fasta = b">c1\nACG\nACGT\n";
+        assert!(scan_contigs(&mut Cursor::new(&fasta[..])).is_err());
+    }
+
+    #[test]
+    fn test_parse_record() {
+        let record = parse_record("chr1\t100\t6\t60\t61").unwrap();
+        assert_eq!(record.line_width, 61);
+        assert!(parse_record("chr1\t100").is_none());
+    }
+}
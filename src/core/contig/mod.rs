@@ -1,2 +1,67 @@
 //! A module for handling contigs
+pub mod extract;
+pub mod filter;
+pub mod index;
+pub mod rename;
 pub mod summarize;
+
+use std::path::{Path, PathBuf};
+
+use crate::helper::files::check_unique_outputs;
+
+/// Output path of a contig file named after the input file
+/// with the `.fasta` extension.
+fn contig_output_path(output_dir: &Path, input: &Path) -> PathBuf {
+    output_dir.join(format!("{}.fasta", contig_file_stem(input)))
+}
+
+/// Check the outputs named after each input file by [`contig_output_path`].
+fn check_unique_contig_outputs(output_dir: &Path, inputs: &[PathBuf]) {
+    check_unique_outputs(inputs.iter().map(|input| {
+        (
+            contig_output_path(output_dir, input),
+            input.display().to_string(),
+        )
+    }));
+}
+
+// File name without the FASTA and compression extensions.
+fn contig_file_stem(input: &Path) -> &str {
+    let file_name = input
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("Failed parsing file name");
+    let lower = file_name.to_ascii_lowercase();
+    let mut end = file_name.len();
+    for ext in [".gz", ".gzip"] {
+        if lower[..end].ends_with(ext) {
+            end -= ext.len();
+        }
+    }
+    for ext in [".fasta", ".fas", ".fna", ".fsa", ".fa"] {
+        if lower[..end].ends_with(ext) {
+            end -= ext.len();
+            break;
+        }
+    }
+    &file_name[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contig_file_stem() {
+        assert_eq!("contigs", contig_file_stem(Path::new("a/contigs.fa.gz")));
+        assert_eq!("contigs", contig_file_stem(Path::new("contigs.FASTA")));
+        assert_eq!("contigs.txt", contig_file_stem(Path::new("contigs.txt")));
+    }
+
+    #[test]
+    #[should_panic(expected = "are both written to")]
+    fn test_check_unique_contig_outputs() {
+        let inputs = [PathBuf::from("a/x.fa"), PathBuf::from("b/x.fa.gz")];
+        check_unique_contig_outputs(Path::new("output"), &inputs);
+    }
+}
@@ -0,0 +1,161 @@
+//! Rename contigs with sequential IDs.
+//!
+//! Support FASTA and compressed FASTA in gunzip format.
+//! Contigs are renamed to `{sample}_{n}`, where the sample name
+//! is the input file name without extensions and `n` starts from 1.
+//! Descriptions in the contig headers are removed.
+//! The original and new names are recorded in a TSV mapping file.
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::{check_unique_contig_outputs, contig_file_stem, contig_output_path};
+use crate::{
+    helper::{types::ContigFmt, utils},
+    parser::fasta::{FastaReader, Records},
+    stats::contigs::open_contigs,
+    writer::contigs::ContigWriter,
+};
+
+const RENAME_MAP_FNAME: &str = "contig-rename-map";
+
+/// Original and new name of a contig.
+#[derive(Debug, Serialize)]
+pub struct ContigRenameRecord {
+    pub file: PathBuf,
+    pub original_name: String,
+    pub new_name: String,
+}
+
+pub struct ContigRename<'a> {
+    files: &'a [PathBuf],
+    input_fmt: &'a ContigFmt,
+    output: &'a Path,
+}
+
+impl<'a> ContigRename<'a> {
+    pub fn new(files: &'a [PathBuf], input_fmt: &'a ContigFmt, output: &'a Path) -> Self {
+        Self {
+            files,
+            input_fmt,
+            output,
+        }
+    }
+
+    /// Rename contigs and write them to the output directory.
+    /// Returns the number of renamed contigs.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::contig::rename::ContigRename;
+    /// use segul::helper::types::ContigFmt;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/contigs/contigs1.fa")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let counts = ContigRename::new(&files, &ContigFmt::Auto, output.path()).rename();
+    /// assert_eq!(counts, 2);
+    /// ```
+    pub fn rename(&self) -> usize {
+        check_unique_contig_outputs(self.output, self.files);
+        let spin = utils::set_spinner();
+        spin.set_message("Renaming contigs...");
+        let mut records: Vec<Vec<ContigRenameRecord>> = self
+            .files
+            .par_iter()
+            .map(|file| self.rename_contigs(file))
+            .collect();
+        records.sort_by(|a, b| a.first().map(|r| &r.file).cmp(&b.first().map(|r| &r.file)));
+        let records: Vec<ContigRenameRecord> = records.into_iter().flatten().collect();
+        self.write_map(&records);
+        spin.finish_with_message("Finished renaming contigs!\n");
+        self.print_output_info(records.len());
+        records.len()
+    }
+
+    fn rename_contigs(&self, file: &Path) -> Vec<ContigRenameRecord> {
+        let sample = contig_file_stem(file);
+        let reader = FastaReader::new(open_contigs(file, self.input_fmt));
+        let mut records = Vec::new();
+        let contigs: Vec<Records> = reader
+            .enumerate()
+            .map(|(i, contig)| {
+                let new_name = format!("{}_{}", sample, i + 1);
+                let original_name = contig
+                    .id
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                records.push(ContigRenameRecord {
+                    file: file.to_path_buf(),
+                    original_name,
+                    new_name: new_name.clone(),
+                });
+                Records::new(&new_name, &contig.seq)
+            })
+            .collect();
+        let output = contig_output_path(self.output, file);
+        ContigWriter::new(&output)
+            .write(&contigs)
+            .expect("Failed writing contig file");
+        records
+    }
+
+    fn write_map(&self, records: &[ContigRenameRecord]) {
+        let output = self.output.join(RENAME_MAP_FNAME).with_extension("tsv");
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(b'\t')
+            .from_path(output)
+            .expect("Failed to create TSV writer");
+        records.iter().for_each(|rec| {
+            writer.serialize(rec).expect("Failed to write TSV record");
+        });
+        writer.flush().expect("Failed to flush TSV writer");
+    }
+
+    fn print_output_info(&self, contig_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output.display());
+        log::info!(
+            "{:18}: {}",
+            "File counts",
+            utils::fmt_num(&self.files.len())
+        );
+        log::info!(
+            "{:18}: {}",
+            "Renamed contigs",
+            utils::fmt_num(&contig_counts)
+        );
+        log::info!("{:18}: {}", "Output format", "FASTA");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_rename() {
+        let files = vec![PathBuf::from("tests/files/contigs/contigs2.fa")];
+        let output = TempDir::new("temp").unwrap();
+        let handle = ContigRename::new(&files, &ContigFmt::Auto, output.path());
+        assert_eq!(2, handle.rename());
+        let reader = FastaReader::new(open_contigs(
+            &output.path().join("contigs2.fasta"),
+            &ContigFmt::Fasta,
+        ));
+        let ids: Vec<String> = reader.map(|contig| contig.id).collect();
+        assert_eq!(vec!["contigs2_1", "contigs2_2"], ids);
+        let map = fs::read_to_string(output.path().join("contig-rename-map.tsv")).unwrap();
+        assert!(map.starts_with("file\toriginal_name\tnew_name\n"));
+        assert_eq!(3, map.lines().count());
+    }
+}
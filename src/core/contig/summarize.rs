@@ -8,7 +8,7 @@ use rayon::prelude::*;
 use crate::writer::contigs::ContigSummaryWriter;
 use crate::{
     helper::{types::ContigFmt, utils::set_spinner},
-    stats::contigs::ContigSummary,
+    stats::contigs::{ContigSummary, ContigSummaryParameters},
 };
 
 pub struct ContigSummaryHandler<'a> {
@@ -20,6 +20,8 @@ pub struct ContigSummaryHandler<'a> {
     output: &'a Path,
     /// Output file prefix
     prefix: Option<&'a str>,
+    /// Summary parameters
+    params: &'a ContigSummaryParameters,
 }
 
 impl<'a> ContigSummaryHandler<'a> {
@@ -28,12 +30,14 @@ impl<'a> ContigSummaryHandler<'a> {
         input_fmt: &'a ContigFmt,
         output: &'a Path,
         prefix: Option<&'a str>,
+        params: &'a ContigSummaryParameters,
     ) -> Self {
         Self {
             files,
             input_fmt,
             output,
             prefix,
+            params,
         }
     }
 
@@ -44,6 +48,11 @@ impl<'a> ContigSummaryHandler<'a> {
         let writer = ContigSummaryWriter::new(&contig_summary, self.output, self.prefix);
         spin.set_message("Writing records\n");
         writer.write().expect("Failed writing to file");
+        if self.params.per_contig {
+            writer
+                .write_per_contig()
+                .expect("Failed writing per-contig statistics");
+        }
         spin.finish_with_message("Finished processing contig files\n");
         self.print_input_info();
     }
@@ -68,7 +77,7 @@ impl<'a> ContigSummaryHandler<'a> {
     }
 
     fn process_contigs(&self, input: &Path) -> ContigSummary {
-        let mut summary = ContigSummary::new();
+        let mut summary = ContigSummary::with_parameters(self.params);
         summary.summarize(input, self.input_fmt);
         summary
     }
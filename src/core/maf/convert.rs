@@ -1,10 +1,24 @@
 //! Convert MAF to a different alignment format.
 //!
-//! Include support to match name with a reference sequence.
+//! Include support to extract regions in a BED file.
+//! The BED coordinates follow the first sequence of each block.
+//! BED chromosomes can be either the chromosome name (`chr1`)
+//! or the full MAF source name (`hg38.chr1`).
+//! Without a block index, the whole file is read and blocks that
+//! do not overlap any region are written to the `missing-refs` directory.
+//! If the MAF file has a block index (see `core::maf::index`),
+//! only the blocks overlapping the regions are read and
+//! the `missing-refs` directory is not written.
+//! Each region contains the clipped parts of all blocks overlapping it.
+//! Gaps between blocks are filled with missing data.
+//!
+//! Sequences are named by the full MAF source name, e.g. `hg18.chr7`.
+//! Use species names (the part of the source name before the first dot)
+//! to join the rows of a species across blocks
+//! from different chromosomes or scaffolds.
 use std::{
-    collections::HashMap,
-    fs::{self, File},
-    io::BufReader,
+    collections::{BTreeSet, HashMap},
+    fs,
     path::{Path, PathBuf},
 };
 
@@ -14,8 +28,13 @@ use rayon::prelude::*;
 
 use crate::{
     core::OutputPrint,
+    core::maf::{
+        index::MafIndex,
+        stitch::{self, StitchBlock, chrom_name},
+    },
     helper::{
         files,
+        interval::IntervalIndex,
         types::{Header, OutputFmt, SeqMatrix},
         utils,
     },
@@ -32,6 +51,8 @@ pub struct MafConverter<'a> {
     name_from_bed: bool,
     output_dir: &'a Path,
     output_fmt: &'a OutputFmt,
+    /// Name sequences by species instead of the full MAF source name
+    species_names: bool,
 }
 
 impl OutputPrint for MafConverter<'_> {}
@@ -50,9 +71,15 @@ impl<'a> MafConverter<'a> {
             name_from_bed,
             output_fmt,
             output_dir,
+            species_names: false,
         }
     }
 
+    /// Name sequences by species instead of the full MAF source name.
+    pub fn set_species_names(&mut self) {
+        self.species_names = true;
+    }
+
     pub fn convert(&self) {
         if self.name_from_bed {
             self.parse_maf_from_bed();
@@ -66,9 +93,7 @@ impl<'a> MafConverter<'a> {
         let spin = utils::set_spinner();
         spin.set_message("Parsing MAF files...");
         self.input_files.par_iter().for_each(|file| {
-            let file = File::open(file).expect("Unable to open file");
-            let buff = BufReader::new(file);
-            let maf = MafReader::new(buff);
+            let maf = MafReader::from_path(file);
             maf.into_iter().for_each(|paragraph| {
                 if let MafParagraph::Alignment(aln) = paragraph {
                     let matrix = self.convert_to_seqmatrix(&aln);
@@ -87,53 +112,163 @@ impl<'a> MafConverter<'a> {
         );
     }
 
+    // Extract regions in a BED file from MAF blocks.
+    // Each region contains the clipped parts of all blocks overlapping it.
+    // Files with a block index are read only at the blocks overlapping
+    // the regions. Otherwise, the whole file is read and blocks that
+    // do not overlap any region are written as missing references.
     fn parse_maf_from_bed(&self) {
         let spin = utils::set_spinner();
         spin.set_message("Converting MAF format...");
-        let names = self.get_name_from_bed(self.name_source);
-        self.input_files.par_iter().for_each(|file| {
-            let file = File::open(file).expect("Unable to open file");
-            let buff = BufReader::new(file);
-            let maf = MafReader::new(buff);
-            let mut aln_collection: HashMap<String, SeqMatrix> = HashMap::new();
-            let mut missing_refs = HashMap::new();
-            maf.into_iter().for_each(|paragraph| {
-                if let MafParagraph::Alignment(aln) = paragraph {
-                    let new_matrix = self.convert_to_seqmatrix(&aln);
-                    // We assume that the first sequence is the target
-                    // and the rest are the samples
-                    let target = &aln.sequences[0];
-                    let bed_name = names.get(&target.start);
-                    match bed_name {
-                        Some(name) => match aln_collection.get_mut(name) {
-                            Some(matrix) => {
-                                matrix.extend(new_matrix.to_owned());
-                            }
-                            None => {
-                                aln_collection.insert(name.to_string(), new_matrix);
-                            }
-                        },
-                        None => {
-                            let name =
-                                format!("{}-{}-{}", target.source, target.start, target.size,);
-                            missing_refs.insert(name.replace(".", "_"), aln);
-                        }
-                    }
+        let records = self.parse_bed(self.name_source);
+        let index = self.index_regions(&records);
+        self.input_files
+            .par_iter()
+            .for_each(|file| match MafIndex::from_maf_path(file) {
+                Some(maf_index) if maf_index.matches(file) => {
+                    self.seek_regions(file, &records, &maf_index);
                 }
+                Some(_) => {
+                    log::warn!(
+                        "{} The index of {} does not match the file. \
+                        Reading the whole file.",
+                        "WARNING!".red(),
+                        file.display()
+                    );
+                    self.stream_regions(file, &records, &index);
+                }
+                None => self.stream_regions(file, &records, &index),
             });
+        spin.finish_with_message("Finished converting MAF format!\n");
+    }
 
-            spin.set_message("Writing output files...");
-            aln_collection.par_iter().for_each(|(target, matrix)| {
-                let output = self.generate_output_path(self.output_dir, Path::new(target));
-                let header = self.get_header(matrix);
-                self.write_matrix(matrix, &header, &output);
-            });
-            spin.finish_with_message("Finished converting MAF format!\n");
-
-            if !missing_refs.is_empty() {
-                self.write_missing_refs(&missing_refs);
+    fn stream_regions(
+        &self,
+        file: &Path,
+        records: &[BedRecord],
+        index: &HashMap<String, IntervalIndex<usize>>,
+    ) {
+        let maf = MafReader::from_path(file);
+        let mut blocks: Vec<(StitchBlock, Vec<usize>)> = Vec::new();
+        let mut missing_refs = HashMap::new();
+        maf.into_iter().for_each(|paragraph| {
+            if let MafParagraph::Alignment(aln) = paragraph {
+                // We assume that the first sequence is the target
+                // and the rest are the samples
+                let block = match stitch::to_stitch_block(&aln, None, self.species_names) {
+                    Some(block) => block,
+                    None => return,
+                };
+                let regions: Vec<usize> = index
+                    .get(&block.chrom)
+                    .or_else(|| index.get(&aln.sequences[0].source))
+                    .map(|idx| {
+                        idx.find_overlaps(block.start, block.end)
+                            .into_iter()
+                            .copied()
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if regions.is_empty() {
+                    let target = &aln.sequences[0];
+                    let name = format!("{}-{}-{}", target.source, target.start, target.size,);
+                    missing_refs.insert(name.replace(".", "_"), aln);
+                } else {
+                    blocks.push((block, regions));
+                }
             }
         });
+
+        let mut aln_collection: HashMap<usize, Vec<&StitchBlock>> = HashMap::new();
+        blocks.iter().for_each(|(block, regions)| {
+            regions.iter().for_each(|idx| {
+                aln_collection.entry(*idx).or_default().push(block);
+            });
+        });
+        self.write_regions(records, aln_collection);
+
+        if !missing_refs.is_empty() {
+            self.write_missing_refs(&missing_refs);
+        }
+    }
+
+    // Read only the blocks overlapping the regions using the block index.
+    fn seek_regions(&self, file: &Path, records: &[BedRecord], maf_index: &MafIndex) {
+        log::info!(
+            "Using the block index of {}. \
+            Blocks outside the regions are not written to missing-refs.",
+            file.display()
+        );
+        let intervals = maf_index.to_intervals();
+        let region_offsets: Vec<(usize, Vec<u64>)> = records
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, record)| {
+                let offsets: Vec<u64> = intervals
+                    .get(record.chrom.as_str())
+                    .or_else(|| intervals.get(chrom_name(&record.chrom)))?
+                    .find_overlaps(record.chrom_start, record.chrom_end)
+                    .into_iter()
+                    .copied()
+                    .collect();
+                Some((idx, offsets))
+            })
+            .collect();
+        // Read each block once in the file order.
+        let offsets: BTreeSet<u64> = region_offsets
+            .iter()
+            .flat_map(|(_, offsets)| offsets.iter().copied())
+            .collect();
+        let mut maf = MafReader::from_path(file);
+        let blocks: HashMap<u64, StitchBlock> = offsets
+            .into_iter()
+            .filter_map(|offset| {
+                maf.seek_paragraph(offset)
+                    .expect("Failed seeking MAF block");
+                match maf.next_paragraph() {
+                    Some(MafParagraph::Alignment(aln)) => {
+                        stitch::to_stitch_block(&aln, None, self.species_names)
+                            .map(|block| (offset, block))
+                    }
+                    _ => panic!(
+                        "Invalid block offset {} in the index of {}",
+                        offset,
+                        file.display()
+                    ),
+                }
+            })
+            .collect();
+
+        let aln_collection: HashMap<usize, Vec<&StitchBlock>> = region_offsets
+            .iter()
+            .filter_map(|(idx, offsets)| {
+                let region_blocks: Vec<&StitchBlock> = offsets
+                    .iter()
+                    .filter_map(|offset| blocks.get(offset))
+                    .collect();
+                (!region_blocks.is_empty()).then_some((*idx, region_blocks))
+            })
+            .collect();
+        self.write_regions(records, aln_collection);
+    }
+
+    fn write_regions(
+        &self,
+        records: &[BedRecord],
+        mut aln_collection: HashMap<usize, Vec<&StitchBlock>>,
+    ) {
+        aln_collection
+            .par_iter_mut()
+            .for_each(|(idx, region_blocks)| {
+                let record = &records[*idx];
+                region_blocks.sort_by_key(|b| b.start);
+                let matrix =
+                    stitch::stitch_blocks(region_blocks, record.chrom_start, record.chrom_end);
+                let output =
+                    self.generate_output_path(self.output_dir, Path::new(&format_bed_name(record)));
+                let header = self.get_header(&matrix);
+                self.write_matrix(&matrix, &header, &output);
+            });
     }
 
     fn write_missing_refs(&self, missing_refs: &HashMap<String, MafAlignment>) {
@@ -159,8 +294,14 @@ impl<'a> MafConverter<'a> {
     fn convert_to_seqmatrix(&self, alignments: &MafAlignment) -> SeqMatrix {
         let mut matrix: SeqMatrix = IndexMap::new();
         alignments.sequences.iter().for_each(|sample| {
+            let name = stitch::sequence_name(&sample.source, self.species_names);
+            // Keep the first sequence if a species has multiple
+            // sequences in the same block.
+            if self.species_names && matrix.contains_key(name) {
+                return;
+            }
             let seq = String::from_utf8_lossy(&sample.text).to_string();
-            matrix.insert(sample.source.to_string(), seq);
+            matrix.insert(name.to_string(), seq);
         });
         matrix
     }
@@ -178,37 +319,25 @@ impl<'a> MafConverter<'a> {
             .expect("Failed writing output files");
     }
 
-    fn get_name_from_bed(&self, bed: &Path) -> HashMap<usize, String> {
+    fn parse_bed(&self, bed: &Path) -> Vec<BedRecord> {
         let mut bed = BedParser::new(bed);
-        let bed = bed.parse().expect("Unable to parse BED file");
-        // Create a hashmap with the start position as key
-        // and the gene name as value
-        let mut names = HashMap::new();
-        bed.iter().for_each(|record| {
-            let gene_name = self.format_bed_name(record);
-            names.insert(record.chrom_start, gene_name);
-        });
-        names
+        bed.parse().expect("Unable to parse BED file")
     }
 
-    fn format_bed_name(&self, record: &BedRecord) -> String {
-        let name = match &record.name {
-            Some(name) => {
-                format!(
-                    "{}-{}-{}-{}",
-                    name, record.chrom, record.chrom_start, record.chrom_end
-                )
-            }
-            None => {
-                format!(
-                    "{}-{}-{}",
-                    record.chrom, record.chrom_start, record.chrom_end
-                )
-            }
-        };
-        // Replace any dots in the name with underscores
-        // to avoid issues with file extensions
-        name.replace(".", "_")
+    // Create an interval index of the BED records for each chromosome.
+    // The index values are the positions of the records.
+    fn index_regions(&self, records: &[BedRecord]) -> HashMap<String, IntervalIndex<usize>> {
+        let mut intervals: HashMap<String, Vec<(usize, usize, usize)>> = HashMap::new();
+        records.iter().enumerate().for_each(|(idx, record)| {
+            intervals
+                .entry(record.chrom.to_string())
+                .or_default()
+                .push((record.chrom_start, record.chrom_end, idx));
+        });
+        intervals
+            .into_iter()
+            .map(|(chrom, intervals)| (chrom, IntervalIndex::new(intervals)))
+            .collect()
     }
 
     fn print_output_info(&self) {
@@ -218,6 +347,27 @@ impl<'a> MafConverter<'a> {
     }
 }
 
+/// Format a BED record as an output name.
+/// Dots are replaced with underscores
+/// to avoid issues with file extensions.
+pub(super) fn format_bed_name(record: &BedRecord) -> String {
+    let name = match &record.name {
+        Some(name) => {
+            format!(
+                "{}-{}-{}-{}",
+                name, record.chrom, record.chrom_start, record.chrom_end
+            )
+        }
+        None => {
+            format!(
+                "{}-{}-{}",
+                record.chrom, record.chrom_start, record.chrom_end
+            )
+        }
+    };
+    name.replace(".", "_")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,9 +377,9 @@ mod tests {
     fn test_convert_to_seqmatrix() {
         let aln = MafAlignment {
             score: None,
-            quality: None,
-            information: None,
-            empty: None,
+            qualities: Vec::new(),
+            information: Vec::new(),
+            empty_lines: Vec::new(),
             sequences: vec![
                 MafSequence {
                     source: "seq1".to_string(),
@@ -250,11 +400,12 @@ mod tests {
             ],
         };
         let converter = MafConverter {
-            input_files: &vec![],
-            name_source: &Path::new(""),
+            input_files: &[],
+            name_source: Path::new(""),
             name_from_bed: false,
             output_fmt: &OutputFmt::Fasta,
-            output_dir: &Path::new(""),
+            output_dir: Path::new(""),
+            species_names: false,
         };
         let matrix = converter.convert_to_seqmatrix(&aln);
         assert_eq!(matrix.len(), 2);
@@ -263,14 +414,15 @@ mod tests {
     #[test]
     fn test_generate_output_path() {
         let converter = MafConverter {
-            input_files: &vec![],
-            name_source: &Path::new(""),
+            input_files: &[],
+            name_source: Path::new(""),
             name_from_bed: false,
             output_fmt: &OutputFmt::Fasta,
-            output_dir: &Path::new("output"),
+            output_dir: Path::new("output"),
+            species_names: false,
         };
         let output_dir = Path::new("output");
-        let output = converter.generate_output_path(output_dir, &Path::new("test.maf"));
+        let output = converter.generate_output_path(output_dir, Path::new("test.maf"));
         assert_eq!(output, Path::new("output/test.fas"));
         let gene_name = String::from("hoxa1-chrom1-1-10");
         let output = converter.generate_output_path(output_dir, Path::new(&gene_name));
@@ -280,14 +432,7 @@ mod tests {
     #[test]
     fn test_format_bed_name() {
         let record = BedRecord::new("chr1".to_string(), 1, 10, Some("gene".to_string()));
-        let converter = MafConverter {
-            input_files: &vec![],
-            name_source: &Path::new(""),
-            name_from_bed: false,
-            output_fmt: &OutputFmt::Fasta,
-            output_dir: &Path::new("output"),
-        };
-        let name = converter.format_bed_name(&record);
+        let name = format_bed_name(&record);
         assert_eq!(name, "gene-chr1-1-10");
     }
 }
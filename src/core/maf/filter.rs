@@ -0,0 +1,302 @@
+//! Filter MAF blocks and project them onto a subset of species.
+//!
+//! Blocks are kept if they pass all the given criteria:
+//! 1. Minimum score. Blocks without a score are removed.
+//! 2. Required species. Blocks must contain all the listed species.
+//! 3. Minimum length. The number of alignment columns
+//!    after the projection.
+//!
+//! The projection keeps only the sequences of the listed species,
+//! including their `q`, `i`, and `e` lines, and removes alignment
+//! columns that contain only gaps.
+//! Blocks without any sequence left are removed.
+//! Species names follow the `species.chromosome` convention.
+//! Compressed inputs are written as plain text MAF files
+//! without the gzip extension.
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use colored::Colorize;
+use rayon::prelude::*;
+
+use crate::{
+    core::maf::stitch::species_name,
+    helper::{files, utils},
+    parser::maf::{MafAlignment, MafParagraph, MafReader},
+    writer::maf::MafWriter,
+};
+
+/// Criteria to keep MAF blocks.
+/// Criteria set to `None` are not applied.
+#[derive(Debug, Default)]
+pub struct MafFilterParameters {
+    /// Minimum alignment score
+    pub min_score: Option<f64>,
+    /// Minimum number of alignment columns
+    pub min_length: Option<usize>,
+    /// Species required in each block
+    pub species: Option<Vec<String>>,
+    /// Species to keep in each block
+    pub projection: Option<Vec<String>>,
+}
+
+pub struct MafFilter<'a> {
+    input_files: &'a [PathBuf],
+    output_dir: &'a Path,
+    params: &'a MafFilterParameters,
+}
+
+impl<'a> MafFilter<'a> {
+    pub fn new(
+        input_files: &'a [PathBuf],
+        output_dir: &'a Path,
+        params: &'a MafFilterParameters,
+    ) -> Self {
+        Self {
+            input_files,
+            output_dir,
+            params,
+        }
+    }
+
+    /// Filter MAF blocks and write one MAF file per input file.
+    /// Returns the number of blocks kept.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::maf::filter::{MafFilter, MafFilterParameters};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/stitch/blocks.maf")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let params = MafFilterParameters {
+    ///     min_score: Some(8.0),
+    ///     ..Default::default()
+    /// };
+    /// let counts = MafFilter::new(&files, output.path(), &params).filter();
+    /// assert_eq!(counts, 3);
+    /// ```
+    pub fn filter(&self) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Filtering MAF blocks...");
+        let block_counts = AtomicUsize::new(0);
+        let kept_counts = AtomicUsize::new(0);
+        self.input_files.par_iter().for_each(|file| {
+            let (blocks, kept) = self.filter_file(file);
+            block_counts.fetch_add(blocks, Ordering::Relaxed);
+            kept_counts.fetch_add(kept, Ordering::Relaxed);
+        });
+        spin.finish_with_message("Finished filtering MAF blocks!\n");
+        let kept_counts = kept_counts.load(Ordering::Relaxed);
+        self.print_output_info(block_counts.load(Ordering::Relaxed), kept_counts);
+        kept_counts
+    }
+
+    // Returns the number of blocks and the number of kept blocks.
+    fn filter_file(&self, file: &Path) -> (usize, usize) {
+        let output = files::create_uncompressed_output_fname(self.output_dir, file);
+        let mut writer = MafWriter::from_path(&output).expect("Failed creating MAF file");
+        let maf = MafReader::from_path(file);
+        let mut block_counts = 0;
+        let mut kept_counts = 0;
+        maf.into_iter().for_each(|paragraph| match paragraph {
+            MafParagraph::Alignment(aln) => {
+                block_counts += 1;
+                if let Some(aln) = self.filter_block(aln) {
+                    writer
+                        .write_alignment(&aln)
+                        .expect("Failed writing MAF block");
+                    kept_counts += 1;
+                }
+            }
+            paragraph => writer
+                .write_paragraph(&paragraph)
+                .expect("Failed writing MAF file"),
+        });
+        writer.flush().expect("Failed writing MAF file");
+        (block_counts, kept_counts)
+    }
+
+    fn filter_block(&self, mut aln: MafAlignment) -> Option<MafAlignment> {
+        if let Some(min_score) = self.params.min_score
+            && !aln.score.is_some_and(|score| score >= min_score)
+        {
+            return None;
+        }
+        if let Some(species) = &self.params.species
+            && !species.iter().all(|name| {
+                aln.sequences
+                    .iter()
+                    .any(|seq| species_name(&seq.source) == name)
+            })
+        {
+            return None;
+        }
+        if let Some(projection) = &self.params.projection {
+            project_block(&mut aln, projection);
+            if aln.sequences.is_empty() {
+                return None;
+            }
+        }
+        if let Some(min_length) = self.params.min_length
+            && block_len(&aln) < min_length
+        {
+            return None;
+        }
+        Some(aln)
+    }
+
+    fn print_output_info(&self, block_counts: usize, kept_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "Total blocks", utils::fmt_num(&block_counts));
+        log::info!("{:18}: {}", "Kept blocks", utils::fmt_num(&kept_counts));
+        log::info!(
+            "{:18}: {}",
+            "Removed blocks",
+            utils::fmt_num(&(block_counts - kept_counts))
+        );
+    }
+}
+
+/// Keep only the sequences of the given species
+/// and remove columns that contain only gaps.
+pub(super) fn project_block(aln: &mut MafAlignment, species: &[String]) {
+    let is_projected = |source: &str| species.iter().any(|name| name == species_name(source));
+    aln.sequences.retain(|seq| is_projected(&seq.source));
+    aln.qualities.retain(|q| is_projected(&q.source));
+    aln.information.retain(|i| is_projected(&i.source));
+    aln.empty_lines.retain(|e| is_projected(&e.source));
+    remove_gap_columns(aln);
+}
+
+/// Remove alignment columns that contain only gaps.
+pub(super) fn remove_gap_columns(aln: &mut MafAlignment) {
+    let columns = block_len(aln);
+    let is_kept: Vec<bool> = (0..columns)
+        .map(|col| {
+            aln.sequences
+                .iter()
+                .any(|seq| seq.text.get(col).is_some_and(|&c| c != b'-'))
+        })
+        .collect();
+    if is_kept.iter().all(|&kept| kept) {
+        return;
+    }
+    aln.sequences.iter_mut().for_each(|seq| {
+        seq.text = seq
+            .text
+            .iter()
+            .zip(is_kept.iter())
+            .filter(|(_, kept)| **kept)
+            .map(|(&c, _)| c)
+            .collect();
+    });
+    // Quality values follow the alignment columns.
+    aln.qualities.iter_mut().for_each(|q| {
+        q.values = q
+            .values
+            .iter()
+            .zip(is_kept.iter())
+            .filter(|(_, kept)| **kept)
+            .map(|(&c, _)| c)
+            .collect();
+    });
+}
+
+// Number of alignment columns in a block.
+pub(super) fn block_len(aln: &MafAlignment) -> usize {
+    aln.sequences.first().map_or(0, |seq| seq.text.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+    use tempdir::TempDir;
+
+    use super::*;
+
+    fn parse_blocks() -> Vec<MafAlignment> {
+        MafReader::from_path(Path::new("tests/files/stitch/blocks.maf"))
+            .filter_map(|paragraph| match paragraph {
+                MafParagraph::Alignment(aln) => Some(aln),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_project_block() {
+        let mut blocks = parse_blocks();
+        let aln = &mut blocks[1];
+        project_block(aln, &["ref".to_string(), "sp2".to_string()]);
+        assert_eq!(2, aln.sequences.len());
+        assert_eq!(b"AAAA", aln.sequences[0].text.as_slice());
+        assert_eq!(b"AA-A", aln.sequences[1].text.as_slice());
+        // The gap column in the reference is removed
+        // after dropping sp1.
+        let aln = &mut blocks[0];
+        project_block(aln, &["ref".to_string()]);
+        assert_eq!(b"ACGT", aln.sequences[0].text.as_slice());
+        assert_eq!(4, aln.sequences[0].size);
+    }
+
+    #[test]
+    fn test_filter_block() {
+        let params = MafFilterParameters {
+            species: Some(vec!["sp2".to_string()]),
+            projection: Some(vec!["ref".to_string(), "sp2".to_string()]),
+            min_length: Some(4),
+            ..Default::default()
+        };
+        let files = [];
+        let handle = MafFilter::new(&files, Path::new("."), &params);
+        let kept: Vec<MafAlignment> = parse_blocks()
+            .into_iter()
+            .filter_map(|aln| handle.filter_block(aln))
+            .collect();
+        assert_eq!(2, kept.len());
+        assert_eq!(Some(20.0), kept[0].score);
+        // Block with only sp1 and sp2 is shorter than four columns.
+        assert_eq!(Some(5.0), kept[1].score);
+    }
+
+    #[test]
+    fn test_filter_gzip_input() {
+        let input = TempDir::new("input").unwrap();
+        let plain = Path::new("tests/files/stitch/blocks.maf");
+        let gzip = input.path().join("blocks.maf.gz");
+        let mut encoder = GzEncoder::new(
+            std::fs::File::create(&gzip).unwrap(),
+            Compression::default(),
+        );
+        encoder.write_all(&std::fs::read(plain).unwrap()).unwrap();
+        encoder.finish().unwrap();
+
+        let params = MafFilterParameters {
+            min_score: Some(8.0),
+            ..Default::default()
+        };
+        let output = TempDir::new("output").unwrap();
+        let counts = MafFilter::new(&[gzip], output.path(), &params).filter();
+        assert_eq!(3, counts);
+        let output_path = output.path().join("blocks.maf");
+        assert!(!output.path().join("blocks.maf.gz").exists());
+        let blocks = MafReader::from_path(&output_path)
+            .filter(|paragraph| matches!(paragraph, MafParagraph::Alignment(_)))
+            .count();
+        assert_eq!(3, blocks);
+
+        let expected = TempDir::new("expected").unwrap();
+        MafFilter::new(&[plain.to_path_buf()], expected.path(), &params).filter();
+        assert_eq!(
+            std::fs::read_to_string(expected.path().join("blocks.maf")).unwrap(),
+            std::fs::read_to_string(&output_path).unwrap()
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Convert reference-anchored alignments back to MAF.
+//!
+//! Reference-anchored alignments are alignments with a reference sequence,
+//! such as the outputs of MAF stitching or conversion. Reference positions
+//! without alignment are missing data (`?`) in the reference sequence.
+//! The alignment is split into MAF blocks at these positions.
+//!
+//! The reference coordinates are taken from the file name
+//! if it follows the BED region naming, i.e. `[name-]chrom-start-end`.
+//! Otherwise, the file name is the chromosome name and the alignment
+//! starts at the first position of the chromosome.
+//!
+//! The reference source is `reference.chrom`. Its source size is taken
+//! from a chromosome sizes file (`chrom<TAB>size`, e.g. UCSC `chrom.sizes`)
+//! if provided. Otherwise, the source size is the end of the alignment
+//! on the reference, which is only a lower bound of the chromosome size.
+//! Other sequences
+//! use their IDs as the source and the coordinates of their
+//! ungapped sequences in the alignment. Missing data in other sequences
+//! is written as gaps and sequences without any base in a block are omitted.
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use colored::Colorize;
+use rayon::prelude::*;
+
+use crate::{
+    core::maf::filter::remove_gap_columns,
+    helper::{
+        sequence::SeqParser,
+        types::{DataType, DnaStrand, InputFmt, SeqMatrix},
+        utils,
+    },
+    parser::maf::{MafAlignment, MafHeader, MafSequence},
+    writer::maf::MafWriter,
+};
+
+const MISSING: u8 = b'?';
+const GAP: u8 = b'-';
+
+/// Reference coordinates of an alignment.
+#[derive(Debug, PartialEq)]
+struct RefRegion {
+    chrom: String,
+    /// 0-based start position
+    start: usize,
+}
+
+pub struct AlignmentToMaf<'a> {
+    input_fmt: &'a InputFmt,
+    datatype: &'a DataType,
+    /// Reference sequence ID
+    reference: &'a str,
+    output_dir: &'a Path,
+    /// Reference chromosome sizes
+    chrom_sizes: Option<HashMap<String, usize>>,
+}
+
+impl<'a> AlignmentToMaf<'a> {
+    pub fn new(
+        input_fmt: &'a InputFmt,
+        datatype: &'a DataType,
+        reference: &'a str,
+        output_dir: &'a Path,
+    ) -> Self {
+        Self {
+            input_fmt,
+            datatype,
+            reference,
+            output_dir,
+            chrom_sizes: None,
+        }
+    }
+
+    /// Use chromosome sizes from a two-column file (`chrom<TAB>size`)
+    /// as the reference source sizes.
+    pub fn set_chrom_sizes(&mut self, path: &Path) {
+        self.chrom_sizes = Some(parse_chrom_sizes(path));
+    }
+
+    /// Convert alignments to MAF files.
+    /// Returns the number of written files.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::{DataType, InputFmt};
+    /// use segul::core::maf::from_align::AlignmentToMaf;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/slice/chr1.fas")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let handle = AlignmentToMaf::new(&InputFmt::Fasta, &DataType::Dna, "ref", output.path());
+    /// let counts = handle.convert(&files);
+    /// assert_eq!(counts, 1);
+    /// ```
+    pub fn convert(&self, files: &[PathBuf]) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Converting alignments to MAF...");
+        let file_counts = AtomicUsize::new(0);
+        let block_counts = AtomicUsize::new(0);
+        files.par_iter().for_each(|file| {
+            let (matrix, _) = SeqParser::new(file, self.datatype).get_alignment(self.input_fmt);
+            let Some(reference) = matrix.get(self.reference) else {
+                log::warn!(
+                    "{} Reference {} is not found in {}. Skipping it.",
+                    "WARNING!".red(),
+                    self.reference,
+                    file.display()
+                );
+                return;
+            };
+            let file_stem = file
+                .file_stem()
+                .expect("Failed getting file stem")
+                .to_string_lossy();
+            let blocks = self.to_blocks(&matrix, reference.as_bytes(), &file_stem);
+            let output = self.output_dir.join(format!("{}.maf", file_stem));
+            self.write_blocks(&output, &blocks);
+            file_counts.fetch_add(1, Ordering::Relaxed);
+            block_counts.fetch_add(blocks.len(), Ordering::Relaxed);
+        });
+        spin.finish_with_message("Finished converting alignments to MAF!\n");
+        let file_counts = file_counts.load(Ordering::Relaxed);
+        self.print_output_info(file_counts, block_counts.load(Ordering::Relaxed));
+        file_counts
+    }
+
+    fn to_blocks(
+        &self,
+        matrix: &SeqMatrix,
+        reference: &[u8],
+        file_stem: &str,
+    ) -> Vec<MafAlignment> {
+        let ref_len = count_positions(reference);
+        let region = match parse_region(file_stem) {
+            Some((region, end)) if end - region.start == ref_len => region,
+            _ => RefRegion {
+                chrom: file_stem.to_string(),
+                start: 0,
+            },
+        };
+        let src_size = self.ref_src_size(&region, ref_len);
+        // Total bases of each sequence to use as the source size.
+        let seq_sizes: Vec<usize> = matrix
+            .values()
+            .map(|seq| count_bases(seq.as_bytes()))
+            .collect();
+
+        let mut blocks = Vec::new();
+        for (col_start, col_end) in split_columns(reference) {
+            let ref_text = to_maf_text(&reference[col_start..col_end]);
+            let ref_size = count_bases(&ref_text);
+            // Skip blocks with only insertions relative to the reference.
+            if ref_size == 0 {
+                continue;
+            }
+            let mut aln = MafAlignment::new();
+            aln.add_sequence(MafSequence {
+                source: format!("{}.{}", self.reference, region.chrom),
+                start: region.start + count_positions(&reference[..col_start]),
+                size: ref_size,
+                strand: DnaStrand::Forward,
+                src_size,
+                text: ref_text,
+            });
+            matrix
+                .iter()
+                .zip(seq_sizes.iter())
+                .filter(|((id, _), _)| *id != self.reference)
+                .for_each(|((id, seq), seq_size)| {
+                    let seq = seq.as_bytes();
+                    let text = to_maf_text(&seq[col_start..col_end]);
+                    let size = count_bases(&text);
+                    if size == 0 {
+                        return;
+                    }
+                    aln.add_sequence(MafSequence {
+                        source: id.to_string(),
+                        start: count_bases(&seq[..col_start]),
+                        size,
+                        strand: DnaStrand::Forward,
+                        src_size: *seq_size,
+                        text,
+                    });
+                });
+            remove_gap_columns(&mut aln);
+            blocks.push(aln);
+        }
+        blocks
+    }
+
+    // Chromosome size if known. Otherwise, the alignment end
+    // as the lower bound of the chromosome size.
+    fn ref_src_size(&self, region: &RefRegion, ref_len: usize) -> usize {
+        let aln_end = region.start + ref_len;
+        match self
+            .chrom_sizes
+            .as_ref()
+            .and_then(|sizes| sizes.get(&region.chrom))
+        {
+            Some(&size) if size >= aln_end => size,
+            Some(&size) => panic!(
+                "Chromosome {} size {} is shorter than the alignment end {}",
+                region.chrom, size, aln_end
+            ),
+            None => {
+                if self.chrom_sizes.is_some() {
+                    log::warn!(
+                        "{} Chromosome {} is not found in the chromosome sizes. \
+                        Using the alignment end as its size.",
+                        "WARNING!".red(),
+                        region.chrom
+                    );
+                }
+                aln_end
+            }
+        }
+    }
+
+    fn write_blocks(&self, output: &Path, blocks: &[MafAlignment]) {
+        let mut writer = MafWriter::from_path(output).expect("Failed creating MAF file");
+        let header = MafHeader {
+            version: String::from("1"),
+            scoring: None,
+            program: Some(String::from("segul")),
+        };
+        writer
+            .write_header(&header)
+            .expect("Failed writing MAF header");
+        blocks.iter().for_each(|aln| {
+            writer
+                .write_alignment(aln)
+                .expect("Failed writing MAF block");
+        });
+        writer.flush().expect("Failed writing MAF file");
+    }
+
+    fn print_output_info(&self, file_counts: usize, block_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&file_counts));
+        log::info!("{:18}: {}", "Block counts", utils::fmt_num(&block_counts));
+        log::info!("{:18}: {}", "Output format", "MAF");
+    }
+}
+
+// Parse a chromosome sizes file.
+// Each line is `chrom<TAB>size`. Extra columns are ignored.
+fn parse_chrom_sizes(path: &Path) -> HashMap<String, usize> {
+    let file = File::open(path).expect("Failed opening chromosome sizes file");
+    BufReader::new(file)
+        .lines()
+        .map(|line| line.expect("Failed reading chromosome sizes file"))
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.split_whitespace();
+            let chrom = parts.next().expect("Missing chromosome name");
+            let size = parts
+                .next()
+                .and_then(|size| size.parse::<usize>().ok())
+                .unwrap_or_else(|| panic!("Invalid chromosome size: {}", line));
+            (chrom.to_string(), size)
+        })
+        .collect()
+}
+
+// Parse the reference region from a file stem
+// named `[name-]chrom-start-end`.
+// Returns the region and the end position.
+fn parse_region(file_stem: &str) -> Option<(RefRegion, usize)> {
+    let mut parts = file_stem.rsplitn(4, '-');
+    let end = parts.next()?.parse::<usize>().ok()?;
+    let start = parts.next()?.parse::<usize>().ok()?;
+    let chrom = parts.next()?;
+    if chrom.is_empty() || start >= end {
+        return None;
+    }
+    let region = RefRegion {
+        chrom: chrom.to_string(),
+        start,
+    };
+    Some((region, end))
+}
+
+// Split alignment columns at missing data in the reference.
+// Returns 0-based half-open column ranges.
+fn split_columns(reference: &[u8]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    reference
+        .iter()
+        .enumerate()
+        .for_each(|(col, &c)| match (c == MISSING, start) {
+            (true, Some(col_start)) => {
+                ranges.push((col_start, col));
+                start = None;
+            }
+            (false, None) => start = Some(col),
+            _ => (),
+        });
+    if let Some(col_start) = start {
+        ranges.push((col_start, reference.len()));
+    }
+    ranges
+}
+
+// Missing data is written as gaps.
+fn to_maf_text(text: &[u8]) -> Vec<u8> {
+    text.iter()
+        .map(|&c| if c == MISSING { GAP } else { c })
+        .collect()
+}
+
+// Reference positions including missing data.
+fn count_positions(reference: &[u8]) -> usize {
+    reference.iter().filter(|&&c| c != GAP).count()
+}
+
+fn count_bases(text: &[u8]) -> usize {
+    text.iter().filter(|&&c| c != GAP && c != MISSING).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_parse_region() {
+        let (region, end) = parse_region("r1-chr1-2-12").unwrap();
+        assert_eq!("chr1", region.chrom);
+        assert_eq!(2, region.start);
+        assert_eq!(12, end);
+        assert_eq!(10, parse_region("chr1-10-20").unwrap().0.start);
+        assert!(parse_region("chr1").is_none());
+    }
+
+    #[test]
+    fn test_split_columns() {
+        assert_eq!(vec![(0, 4), (10, 13)], split_columns(b"AAAA??????AC-"));
+        assert_eq!(vec![(2, 4)], split_columns(b"??AA"));
+    }
+
+    #[test]
+    fn test_to_blocks() {
+        let matrix: SeqMatrix = [
+            ("ref", "AA??????AC-"),
+            ("sp1", "TA??????ACT"),
+            ("sp2", "-A?????????"),
+        ]
+        .into_iter()
+        .map(|(id, seq)| (id.to_string(), seq.to_string()))
+        .collect();
+        let handle = AlignmentToMaf::new(&InputFmt::Fasta, &DataType::Dna, "ref", Path::new("."));
+        let blocks = handle.to_blocks(&matrix, matrix["ref"].as_bytes(), "r1-chr1-2-12");
+        assert_eq!(2, blocks.len());
+        let reference = &blocks[0].sequences[0];
+        assert_eq!("ref.chr1", reference.source);
+        assert_eq!(
+            (2, 2, 12),
+            (reference.start, reference.size, reference.src_size)
+        );
+        assert_eq!(3, blocks[0].sequences.len());
+        let reference = &blocks[1].sequences[0];
+        assert_eq!((10, 2), (reference.start, reference.size));
+        // sp2 has no base in the second block
+        assert_eq!(2, blocks[1].sequences.len());
+        let sp1 = &blocks[1].sequences[1];
+        assert_eq!((2, 3, 5), (sp1.start, sp1.size, sp1.src_size));
+        assert_eq!(b"ACT", sp1.text.as_slice());
+    }
+
+    #[test]
+    fn test_chrom_sizes() {
+        let input = TempDir::new("input").unwrap();
+        let sizes = input.path().join("chrom.sizes");
+        std::fs::write(&sizes, "chr1\t1000\nchr2\t500\n").unwrap();
+        let matrix: SeqMatrix = [("ref", "AAAC"), ("sp1", "TAAC")]
+            .into_iter()
+            .map(|(id, seq)| (id.to_string(), seq.to_string()))
+            .collect();
+        let mut handle =
+            AlignmentToMaf::new(&InputFmt::Fasta, &DataType::Dna, "ref", Path::new("."));
+        handle.set_chrom_sizes(&sizes);
+        let blocks = handle.to_blocks(&matrix, matrix["ref"].as_bytes(), "chr1-10-14");
+        assert_eq!(1000, blocks[0].sequences[0].src_size);
+        // Unknown chromosomes fall back to the alignment end.
+        let blocks = handle.to_blocks(&matrix, matrix["ref"].as_bytes(), "chr3-10-14");
+        assert_eq!(14, blocks[0].sequences[0].src_size);
+    }
+
+    #[test]
+    fn test_convert_missing_reference() {
+        let input = TempDir::new("input").unwrap();
+        let file = input.path().join("chr1.fas");
+        std::fs::write(&file, ">sp1\nACGT\n>sp2\nACGA\n").unwrap();
+        let output = TempDir::new("output").unwrap();
+        let handle = AlignmentToMaf::new(&InputFmt::Fasta, &DataType::Dna, "ref", output.path());
+        assert_eq!(0, handle.convert(&[file]));
+    }
+}
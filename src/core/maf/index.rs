@@ -0,0 +1,365 @@
+//! Index MAF alignment blocks by the reference coordinates.
+//!
+//! The index maps the reference interval of each block to the block offset
+//! in the MAF file. The reference is the first sequence in each block.
+//! The offset is the byte offset for plain files and the virtual position
+//! for BGZF compressed files. Gzip files that are not BGZF compressed
+//! cannot be indexed, because they do not support random access.
+//!
+//! The index is a tab-separated text file written next to the MAF file
+//! with the `.idx` extension appended, e.g. `chr1.maf.gz.idx`.
+//! The first line records the compression, size, and modification time
+//! (nanoseconds since the Unix epoch) of the MAF file, followed by
+//! one line per block: chromosome, 0-based start, exclusive end, and offset.
+//! An index is only used if it matches the current MAF file.
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Seek, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::UNIX_EPOCH,
+};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+use noodles::bgzf;
+use rayon::prelude::*;
+
+use crate::{
+    core::maf::stitch::{chrom_name, forward_start},
+    helper::{
+        files::{self, Compression},
+        interval::IntervalIndex,
+        utils,
+    },
+    parser::maf::MafSequence,
+};
+
+const INDEX_EXTENSION: &str = "idx";
+const PLAIN_TAG: &str = "#plain";
+const BGZF_TAG: &str = "#bgzf";
+
+/// A block entry in a MAF index.
+#[derive(Debug, PartialEq)]
+pub struct MafIndexEntry {
+    /// Reference chromosome name
+    pub chrom: String,
+    /// 0-based start position in the forward strand
+    pub start: usize,
+    /// 0-based exclusive end position in the forward strand
+    pub end: usize,
+    /// Byte offset or BGZF virtual position of the block
+    pub offset: u64,
+}
+
+pub struct MafIndex {
+    pub compression: Compression,
+    /// Size of the indexed MAF file in bytes
+    pub file_size: u64,
+    /// Modification time of the indexed MAF file
+    /// in nanoseconds since the Unix epoch
+    pub modified: u128,
+    pub entries: Vec<MafIndexEntry>,
+}
+
+impl MafIndex {
+    /// Path of the index file of a MAF file.
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use segul::core::maf::index::MafIndex;
+    ///
+    /// let path = MafIndex::index_path(Path::new("chr1.maf.gz"));
+    /// assert_eq!(path, Path::new("chr1.maf.gz.idx"));
+    /// ```
+    pub fn index_path(maf_path: &Path) -> PathBuf {
+        let mut path = maf_path.as_os_str().to_owned();
+        path.push(".");
+        path.push(INDEX_EXTENSION);
+        PathBuf::from(path)
+    }
+
+    /// Build an index by scanning a plain or BGZF compressed MAF file.
+    /// Returns `None` for gzip files that are not BGZF compressed.
+    pub fn build(maf_path: &Path) -> Option<Self> {
+        let compression = files::detect_compression(maf_path);
+        let file = File::open(maf_path).expect("Unable to open file");
+        let entries = match compression {
+            Compression::None => {
+                let mut reader = BufReader::new(file);
+                scan_blocks(&mut reader, |r| {
+                    r.stream_position().expect("Failed getting file position")
+                })
+            }
+            Compression::Bgzf => {
+                let mut reader = bgzf::io::Reader::new(file);
+                scan_blocks(&mut reader, |r| u64::from(r.virtual_position()))
+            }
+            Compression::Gzip => return None,
+        };
+        let (file_size, modified) = file_stamp(maf_path);
+        Some(Self {
+            compression,
+            file_size,
+            modified,
+            entries,
+        })
+    }
+
+    /// Check if the index matches the current MAF file.
+    /// The offsets are invalid if the file is edited
+    /// or regenerated after indexing.
+    pub fn matches(&self, maf_path: &Path) -> bool {
+        self.compression == files::detect_compression(maf_path)
+            && (self.file_size, self.modified) == file_stamp(maf_path)
+    }
+
+    /// Read the index of a MAF file if it exists.
+    pub fn from_maf_path(maf_path: &Path) -> Option<Self> {
+        let index_path = Self::index_path(maf_path);
+        if !index_path.exists() {
+            return None;
+        }
+        let reader = BufReader::new(File::open(&index_path).expect("Unable to open index"));
+        let mut lines = reader.lines().map_while(Result::ok);
+        let header = lines.next().unwrap_or_default();
+        let mut header = header.split('\t');
+        let compression = match header.next() {
+            Some(PLAIN_TAG) => Compression::None,
+            Some(BGZF_TAG) => Compression::Bgzf,
+            _ => panic!("Invalid MAF index: {}", index_path.display()),
+        };
+        // Indexes without the file size and modification time
+        // never match the MAF file.
+        let file_size = header.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        let modified = header.next().and_then(|n| n.parse().ok()).unwrap_or(0);
+        let entries = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| parse_entry(&line).expect("Invalid MAF index entry"))
+            .collect();
+        Some(Self {
+            compression,
+            file_size,
+            modified,
+            entries,
+        })
+    }
+
+    pub fn write(&self, output: &Path) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(output)?);
+        let tag = match self.compression {
+            Compression::Bgzf => BGZF_TAG,
+            _ => PLAIN_TAG,
+        };
+        writeln!(writer, "{}\t{}\t{}", tag, self.file_size, self.modified)?;
+        for entry in &self.entries {
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}",
+                entry.chrom, entry.start, entry.end, entry.offset
+            )?;
+        }
+        writer.flush()
+    }
+
+    /// Interval index of the block offsets for each chromosome.
+    pub fn to_intervals(&self) -> HashMap<&str, IntervalIndex<u64>> {
+        let mut intervals: HashMap<&str, Vec<(usize, usize, u64)>> = HashMap::new();
+        self.entries.iter().for_each(|entry| {
+            intervals.entry(entry.chrom.as_str()).or_default().push((
+                entry.start,
+                entry.end,
+                entry.offset,
+            ));
+        });
+        intervals
+            .into_iter()
+            .map(|(chrom, intervals)| (chrom, IntervalIndex::new(intervals)))
+            .collect()
+    }
+}
+
+/// Build MAF indexes and write them next to the MAF files.
+pub struct MafIndexer<'a> {
+    input_files: &'a [PathBuf],
+}
+
+impl<'a> MafIndexer<'a> {
+    pub fn new(input_files: &'a [PathBuf]) -> Self {
+        Self { input_files }
+    }
+
+    /// Index the MAF files. Existing indexes are replaced.
+    /// Returns the number of indexed files.
+    pub fn index(&self) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Indexing MAF files...");
+        let file_counts = AtomicUsize::new(0);
+        let block_counts = AtomicUsize::new(0);
+        self.input_files
+            .par_iter()
+            .for_each(|file| match MafIndex::build(file) {
+                Some(index) => {
+                    index
+                        .write(&MafIndex::index_path(file))
+                        .expect("Failed writing MAF index");
+                    file_counts.fetch_add(1, Ordering::Relaxed);
+                    block_counts.fetch_add(index.entries.len(), Ordering::Relaxed);
+                }
+                None => log::warn!(
+                    "{} {} is not BGZF compressed and cannot be indexed. \
+                    Use bgzip to compress it. Skipping it.",
+                    "WARNING!".red(),
+                    file.display()
+                ),
+            });
+        spin.finish_with_message("Finished indexing MAF files!\n");
+        let file_counts = file_counts.load(Ordering::Relaxed);
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Indexed files", utils::fmt_num(&file_counts));
+        log::info!(
+            "{:18}: {}",
+            "Block counts",
+            utils::fmt_num(&block_counts.load(Ordering::Relaxed))
+        );
+        file_counts
+    }
+}
+
+// Scan alignment blocks and record the reference interval
+// and the offset of the `a` line of each block.
+fn scan_blocks<R, F>(reader: &mut R, mut position: F) -> Vec<MafIndexEntry>
+where
+    R: BufRead,
+    F: FnMut(&mut R) -> u64,
+{
+    let mut entries = Vec::new();
+    let mut line = Vec::new();
+    // Offset of the current block and its first sequence
+    let mut block: Option<(u64, Option<MafSequence>)> = None;
+    loop {
+        let offset = position(reader);
+        line.clear();
+        let bytes = reader
+            .read_until(b'\n', &mut line)
+            .expect("Error reading file");
+        if bytes == 0 {
+            break;
+        }
+        match line.first() {
+            Some(b'a') => {
+                push_entry(&mut entries, block.take());
+                block = Some((offset, None));
+            }
+            Some(b's') => {
+                if let Some((_, reference @ None)) = &mut block {
+                    *reference =
+                        Some(MafSequence::from_buf(&line).expect("Error parsing sequence"));
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => push_entry(&mut entries, block.take()),
+            _ => (),
+        }
+    }
+    push_entry(&mut entries, block);
+    entries
+}
+
+fn push_entry(entries: &mut Vec<MafIndexEntry>, block: Option<(u64, Option<MafSequence>)>) {
+    if let Some((offset, Some(reference))) = block {
+        let start = forward_start(&reference);
+        entries.push(MafIndexEntry {
+            chrom: chrom_name(&reference.source).to_string(),
+            start,
+            end: start + reference.size,
+            offset,
+        });
+    }
+}
+
+// Size and modification time of a file.
+fn file_stamp(path: &Path) -> (u64, u128) {
+    let metadata = fs::metadata(path).expect("Failed reading file metadata");
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |time| time.as_nanos());
+    (metadata.len(), modified)
+}
+
+fn parse_entry(line: &str) -> Option<MafIndexEntry> {
+    let mut parts = line.split('\t');
+    let chrom = parts.next()?.to_string();
+    let start = parts.next()?.parse().ok()?;
+    let end = parts.next()?.parse().ok()?;
+    let offset = parts.next()?.parse().ok()?;
+    Some(MafIndexEntry {
+        chrom,
+        start,
+        end,
+        offset,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::maf::{MafParagraph, MafReader};
+
+    const INPUT: &str = "tests/files/stitch/blocks.maf";
+
+    #[test]
+    fn test_build_index() {
+        let index = MafIndex::build(Path::new(INPUT)).unwrap();
+        assert_eq!(Compression::None, index.compression);
+        assert_eq!(5, index.entries.len());
+        let reverse = &index.entries[2];
+        assert_eq!(
+            ("chr1", 14, 18),
+            (reverse.chrom.as_str(), reverse.start, reverse.end)
+        );
+        // Blocks without ref use the first sequence.
+        assert_eq!("scf3", index.entries[3].chrom);
+    }
+
+    #[test]
+    fn test_seek_blocks() {
+        let index = MafIndex::build(Path::new(INPUT)).unwrap();
+        let mut reader = MafReader::from_path(Path::new(INPUT));
+        let entry = &index.entries[1];
+        reader.seek_paragraph(entry.offset).unwrap();
+        match reader.next() {
+            Some(MafParagraph::Alignment(aln)) => {
+                assert_eq!(Some(20.0), aln.score);
+                assert_eq!("ref.chr1", aln.sequences[0].source);
+            }
+            _ => panic!("Expected an alignment block"),
+        }
+    }
+
+    #[test]
+    fn test_index_matches() {
+        let dir = tempdir::TempDir::new("temp").unwrap();
+        let maf = dir.path().join("blocks.maf");
+        fs::copy(INPUT, &maf).unwrap();
+        MafIndex::build(&maf)
+            .unwrap()
+            .write(&MafIndex::index_path(&maf))
+            .unwrap();
+        let index = MafIndex::from_maf_path(&maf).unwrap();
+        assert_eq!(5, index.entries.len());
+        assert!(index.matches(&maf));
+        // Regenerated files do not match the index.
+        let content = fs::read_to_string(INPUT).unwrap();
+        fs::write(&maf, content.replacen("a score=10.0", "a score=1.0", 1)).unwrap();
+        assert!(!index.matches(&maf));
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = parse_entry("chr1\t10\t20\t1234").unwrap();
+        assert_eq!(entry.offset, 1234);
+        assert!(parse_entry("chr1\t10").is_none());
+    }
+}
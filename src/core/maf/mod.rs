@@ -1 +1,7 @@
 pub mod convert;
+pub mod filter;
+pub mod from_align;
+pub mod index;
+pub mod rename;
+pub mod stitch;
+pub mod summary;
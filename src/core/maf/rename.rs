@@ -0,0 +1,196 @@
+//! Rename species in MAF files.
+//!
+//! Species names are the part of the MAF source before the first dot,
+//! e.g. `hg18` in `hg18.chr7`. The chromosome part is kept as it is.
+//! The names are replaced in all `s`, `q`, `i`, and `e` lines.
+//! Species not in the mapping are not renamed.
+//! Compressed inputs are written as plain text MAF files
+//! without the gzip extension.
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+use rayon::prelude::*;
+
+use crate::{
+    core::maf::stitch::species_name,
+    helper::{files, utils},
+    parser::maf::{MafAlignment, MafParagraph, MafReader},
+    writer::maf::MafWriter,
+};
+
+pub struct MafRenaming<'a> {
+    input_files: &'a [PathBuf],
+    output_dir: &'a Path,
+    /// Original and new species names
+    names: &'a [(String, String)],
+}
+
+impl<'a> MafRenaming<'a> {
+    pub fn new(
+        input_files: &'a [PathBuf],
+        output_dir: &'a Path,
+        names: &'a [(String, String)],
+    ) -> Self {
+        Self {
+            input_files,
+            output_dir,
+            names,
+        }
+    }
+
+    /// Rename species and write one MAF file per input file.
+    /// Returns the number of renamed sequences.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::maf::rename::MafRenaming;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/stitch/blocks.maf")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let names = vec![(String::from("sp1"), String::from("species_1"))];
+    /// let counts = MafRenaming::new(&files, output.path(), &names).rename();
+    /// assert_eq!(counts, 4);
+    /// ```
+    pub fn rename(&self) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Renaming MAF species...");
+        let names: HashMap<&str, &str> = self
+            .names
+            .iter()
+            .map(|(origin, destination)| (origin.as_str(), destination.as_str()))
+            .collect();
+        let renamed_counts = AtomicUsize::new(0);
+        self.input_files.par_iter().for_each(|file| {
+            let counts = self.rename_file(file, &names);
+            renamed_counts.fetch_add(counts, Ordering::Relaxed);
+        });
+        spin.finish_with_message("Finished renaming MAF species!\n");
+        let renamed_counts = renamed_counts.load(Ordering::Relaxed);
+        self.print_output_info(renamed_counts);
+        renamed_counts
+    }
+
+    fn rename_file(&self, file: &Path, names: &HashMap<&str, &str>) -> usize {
+        let output = files::create_uncompressed_output_fname(self.output_dir, file);
+        let mut writer = MafWriter::from_path(&output).expect("Failed creating MAF file");
+        let maf = MafReader::from_path(file);
+        let mut renamed_counts = 0;
+        maf.into_iter().for_each(|paragraph| {
+            let paragraph = match paragraph {
+                MafParagraph::Alignment(mut aln) => {
+                    renamed_counts += rename_block(&mut aln, names);
+                    MafParagraph::Alignment(aln)
+                }
+                paragraph => paragraph,
+            };
+            writer
+                .write_paragraph(&paragraph)
+                .expect("Failed writing MAF file");
+        });
+        writer.flush().expect("Failed writing MAF file");
+        renamed_counts
+    }
+
+    fn print_output_info(&self, renamed_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!(
+            "{:18}: {}",
+            "File counts",
+            utils::fmt_num(&self.input_files.len())
+        );
+        log::info!(
+            "{:18}: {}",
+            "Renamed sequences",
+            utils::fmt_num(&renamed_counts)
+        );
+    }
+}
+
+// Rename species in a block.
+// Returns the number of renamed `s` lines.
+fn rename_block(aln: &mut MafAlignment, names: &HashMap<&str, &str>) -> usize {
+    let mut renamed_counts = 0;
+    aln.sequences.iter_mut().for_each(|seq| {
+        if let Some(source) = rename_source(&seq.source, names) {
+            seq.source = source;
+            renamed_counts += 1;
+        }
+    });
+    aln.qualities.iter_mut().for_each(|q| {
+        if let Some(source) = rename_source(&q.source, names) {
+            q.source = source;
+        }
+    });
+    aln.information.iter_mut().for_each(|i| {
+        if let Some(source) = rename_source(&i.source, names) {
+            i.source = source;
+        }
+    });
+    aln.empty_lines.iter_mut().for_each(|e| {
+        if let Some(source) = rename_source(&e.source, names) {
+            e.source = source;
+        }
+    });
+    renamed_counts
+}
+
+// Replace the species part of a MAF source.
+// Returns `None` if the species is not in the mapping.
+fn rename_source(source: &str, names: &HashMap<&str, &str>) -> Option<String> {
+    let species = species_name(source);
+    let new_name = names.get(species)?;
+    Some(format!("{}{}", new_name, &source[species.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::GzEncoder};
+    use tempdir::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn test_rename_source() {
+        let names: HashMap<&str, &str> = [("hg18", "human"), ("baboon", "papio")]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            Some(String::from("human.chr7")),
+            rename_source("hg18.chr7", &names)
+        );
+        assert_eq!(Some(String::from("papio")), rename_source("baboon", &names));
+        assert_eq!(None, rename_source("mm4.chr6", &names));
+    }
+
+    #[test]
+    fn test_rename_gzip_input() {
+        let input = TempDir::new("input").unwrap();
+        let gzip = input.path().join("blocks.maf.gz");
+        let mut encoder = GzEncoder::new(
+            std::fs::File::create(&gzip).unwrap(),
+            Compression::default(),
+        );
+        encoder
+            .write_all(&std::fs::read("tests/files/stitch/blocks.maf").unwrap())
+            .unwrap();
+        encoder.finish().unwrap();
+
+        let output = TempDir::new("output").unwrap();
+        let names = vec![(String::from("sp1"), String::from("species_1"))];
+        let counts = MafRenaming::new(&[gzip], output.path(), &names).rename();
+        assert_eq!(4, counts);
+        assert!(!output.path().join("blocks.maf.gz").exists());
+        let renamed = std::fs::read_to_string(output.path().join("blocks.maf")).unwrap();
+        assert!(renamed.contains("s species_1."));
+        assert!(!renamed.contains("s sp1."));
+    }
+}
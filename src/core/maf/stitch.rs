@@ -0,0 +1,581 @@
+//! Stitch MAF blocks into contiguous alignments.
+//!
+//! Blocks are grouped by the reference chromosome and ordered
+//! by the reference coordinates. Reference positions not covered
+//! by any block and species absent from a block are filled with
+//! missing data (`?`). Blocks with the reference on the reverse strand
+//! are reverse complemented, so that all blocks follow
+//! the forward strand of the reference.
+//! Overlapping blocks are clipped to the part not covered by
+//! the previous block.
+//!
+//! Blocks are streamed per reference chromosome. The files are read
+//! twice: first to count the blocks of each chromosome, then to stitch
+//! each chromosome as soon as all of its blocks are parsed.
+//! Files sorted by chromosome only keep one chromosome in memory.
+//! Chromosomes split across files are kept until all files are parsed.
+//!
+//! MAF sequence names follow the `species.chromosome` convention.
+//! The species name is used as the sequence ID in the output alignments.
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+use indexmap::IndexSet;
+use rayon::prelude::*;
+
+use crate::{
+    core::{OutputPrint, align::coordinate::ReferenceMap, maf::convert::format_bed_name},
+    helper::{
+        alphabet, files,
+        interval::IntervalIndex,
+        types::{DnaStrand, Header, OutputFmt, SeqMatrix},
+        utils,
+    },
+    parser::{
+        bed::BedRecord,
+        maf::{MafAlignment, MafParagraph, MafReader, MafSequence},
+    },
+    writer::sequences::SeqWriter,
+};
+
+/// Output regions of the stitched alignments.
+pub enum StitchParameters {
+    /// Write one alignment per reference chromosome.
+    Chromosome,
+    /// Write one alignment per BED region in reference coordinates.
+    Region(Vec<BedRecord>),
+}
+
+/// A MAF block in the forward strand of the reference.
+pub(super) struct StitchBlock {
+    /// Reference chromosome name
+    pub(super) chrom: String,
+    /// 0-based start position in the reference
+    pub(super) start: usize,
+    /// 0-based exclusive end position in the reference
+    pub(super) end: usize,
+    /// Aligned reference sequence
+    reference: String,
+    /// Sequence name and aligned sequence
+    rows: Vec<(String, String)>,
+}
+
+/// A part of a stitched alignment.
+enum Segment<'b> {
+    /// Reference positions without any block.
+    Missing(usize),
+    /// Alignment columns from a block.
+    Block(&'b StitchBlock, usize, usize),
+}
+
+impl OutputPrint for MafStitcher<'_> {}
+
+pub struct MafStitcher<'a> {
+    input_files: &'a [PathBuf],
+    /// Reference species name. Use the first sequence
+    /// in each block if not specified.
+    reference: Option<&'a str>,
+    output_dir: &'a Path,
+    output_fmt: &'a OutputFmt,
+}
+
+impl<'a> MafStitcher<'a> {
+    pub fn new(
+        input_files: &'a [PathBuf],
+        reference: Option<&'a str>,
+        output_dir: &'a Path,
+        output_fmt: &'a OutputFmt,
+    ) -> Self {
+        Self {
+            input_files,
+            reference,
+            output_dir,
+            output_fmt,
+        }
+    }
+
+    /// Stitch MAF blocks and write the alignments.
+    /// Returns the number of written files.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::OutputFmt;
+    /// use segul::core::maf::stitch::{MafStitcher, StitchParameters};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/stitch/blocks.maf")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let handle = MafStitcher::new(&files, Some("ref"), output.path(), &OutputFmt::Fasta);
+    /// let counts = handle.stitch(&StitchParameters::Chromosome);
+    /// assert_eq!(counts, 2);
+    /// ```
+    pub fn stitch(&self, params: &StitchParameters) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Counting MAF blocks...");
+        let block_counts = self.count_blocks();
+        // Number of files with blocks of each chromosome.
+        let mut chrom_files: HashMap<String, usize> = HashMap::new();
+        block_counts
+            .iter()
+            .flat_map(|counts| counts.keys())
+            .for_each(|chrom| {
+                *chrom_files.entry(chrom.clone()).or_default() += 1;
+            });
+        let regions = match params {
+            StitchParameters::Chromosome => HashMap::new(),
+            StitchParameters::Region(records) => group_regions(records, &chrom_files),
+        };
+        spin.set_message("Stitching MAF blocks...");
+        let file_counts = AtomicUsize::new(0);
+        // Blocks of chromosomes found in several files,
+        // with the number of files not yet parsed.
+        let pending: Mutex<HashMap<String, (usize, Vec<StitchBlock>)>> = Mutex::new(HashMap::new());
+        self.input_files
+            .par_iter()
+            .zip(block_counts.par_iter())
+            .for_each(|(file, counts)| {
+                self.stream_blocks(file, counts, |chrom, blocks| {
+                    let blocks = match chrom_files[&chrom] {
+                        1 => blocks,
+                        total => {
+                            let mut pending = pending.lock().expect("Failed locking MAF blocks");
+                            let entry = pending
+                                .entry(chrom.clone())
+                                .or_insert_with(|| (total, Vec::new()));
+                            entry.0 -= 1;
+                            entry.1.extend(blocks);
+                            if entry.0 > 0 {
+                                return;
+                            }
+                            pending.remove(&chrom).expect("Missing MAF blocks").1
+                        }
+                    };
+                    let counts = self.stitch_chromosome(&chrom, blocks, params, &regions);
+                    file_counts.fetch_add(counts, Ordering::Relaxed);
+                });
+            });
+        spin.finish_with_message("Finished stitching MAF blocks!\n");
+        let file_counts = file_counts.load(Ordering::Relaxed);
+        self.print_output_info(chrom_files.len(), file_counts);
+        file_counts
+    }
+
+    // Count blocks per reference chromosome in each file
+    // without keeping the blocks.
+    fn count_blocks(&self) -> Vec<HashMap<String, usize>> {
+        let skipped = AtomicUsize::new(0);
+        let counts = self
+            .input_files
+            .par_iter()
+            .map(|file| {
+                let mut counts: HashMap<String, usize> = HashMap::new();
+                MafReader::from_path(file).for_each(|paragraph| {
+                    if let MafParagraph::Alignment(aln) = paragraph {
+                        match find_reference(&aln.sequences, self.reference) {
+                            Some(target) => {
+                                *counts
+                                    .entry(chrom_name(&target.source).to_string())
+                                    .or_default() += 1
+                            }
+                            None => {
+                                skipped.fetch_add(1, Ordering::Relaxed);
+                            }
+                        }
+                    }
+                });
+                counts
+            })
+            .collect();
+        let skipped = skipped.load(Ordering::Relaxed);
+        if skipped > 0 {
+            log::warn!(
+                "{} {} blocks without the reference sequence are skipped.",
+                "WARNING!".red(),
+                utils::fmt_num(&skipped)
+            );
+        }
+        counts
+    }
+
+    // Parse blocks of a file and pass the blocks of each chromosome
+    // to `on_chrom` as soon as all its blocks in the file are parsed.
+    // Only one chromosome is kept in memory if the file is sorted
+    // by chromosome.
+    fn stream_blocks<F>(&self, file: &Path, counts: &HashMap<String, usize>, mut on_chrom: F)
+    where
+        F: FnMut(String, Vec<StitchBlock>),
+    {
+        let mut remaining = counts.clone();
+        let mut blocks: HashMap<String, Vec<StitchBlock>> = HashMap::new();
+        MafReader::from_path(file).for_each(|paragraph| {
+            let MafParagraph::Alignment(aln) = paragraph else {
+                return;
+            };
+            let Some(block) = to_stitch_block(&aln, self.reference, true) else {
+                return;
+            };
+            let chrom = block.chrom.clone();
+            blocks.entry(chrom.clone()).or_default().push(block);
+            let left = remaining
+                .get_mut(&chrom)
+                .expect("MAF file changed while stitching");
+            *left -= 1;
+            if *left == 0 {
+                let chrom_blocks = blocks.remove(&chrom).expect("Missing MAF blocks");
+                on_chrom(chrom, chrom_blocks);
+            }
+        });
+    }
+
+    // Stitch all blocks of a chromosome and write the alignments.
+    // Returns the number of written files.
+    fn stitch_chromosome(
+        &self,
+        chrom: &str,
+        mut blocks: Vec<StitchBlock>,
+        params: &StitchParameters,
+        regions: &HashMap<String, Vec<&BedRecord>>,
+    ) -> usize {
+        blocks.sort_by_key(|b| b.start);
+        match params {
+            StitchParameters::Chromosome => {
+                let start = blocks[0].start;
+                let end = blocks
+                    .iter()
+                    .map(|b| b.end)
+                    .max()
+                    .expect("Empty chromosome");
+                let blocks: Vec<&StitchBlock> = blocks.iter().collect();
+                let matrix = stitch_blocks(&blocks, start, end);
+                self.write_matrix(&matrix, &chrom.replace('.', "_"));
+                1
+            }
+            StitchParameters::Region(_) => {
+                let Some(records) = regions.get(chrom) else {
+                    return 0;
+                };
+                let index =
+                    IntervalIndex::new(blocks.iter().map(|b| (b.start, b.end, b)).collect());
+                records
+                    .par_iter()
+                    .filter(|record| {
+                        let overlaps: Vec<&StitchBlock> = index
+                            .find_overlaps(record.chrom_start, record.chrom_end)
+                            .into_iter()
+                            .copied()
+                            .collect();
+                        let matrix = stitch_blocks(&overlaps, record.chrom_start, record.chrom_end);
+                        if matrix.is_empty() {
+                            warn_missing_region(record);
+                            return false;
+                        }
+                        self.write_matrix(&matrix, &format_bed_name(record));
+                        true
+                    })
+                    .count()
+            }
+        }
+    }
+
+    fn write_matrix(&self, matrix: &SeqMatrix, name: &str) {
+        let output = files::create_output_fname(self.output_dir, Path::new(name), self.output_fmt);
+        let mut header = Header::new();
+        header.from_seq_matrix(matrix, true);
+        let mut writer = SeqWriter::new(&output, matrix, &header);
+        writer
+            .write_sequence(self.output_fmt)
+            .expect("Failed writing output files");
+    }
+
+    fn print_output_info(&self, chrom_counts: usize, file_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "Chromosomes", utils::fmt_num(&chrom_counts));
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&file_counts));
+        self.print_output_fmt(self.output_fmt);
+    }
+}
+
+// Group BED regions by the reference chromosome of the blocks.
+// BED chromosomes may use the chromosome or the full MAF source name.
+fn group_regions<'r>(
+    records: &'r [BedRecord],
+    chrom_files: &HashMap<String, usize>,
+) -> HashMap<String, Vec<&'r BedRecord>> {
+    let mut regions: HashMap<String, Vec<&BedRecord>> = HashMap::new();
+    records.iter().for_each(|record| {
+        let chrom = if chrom_files.contains_key(&record.chrom) {
+            record.chrom.as_str()
+        } else {
+            chrom_name(&record.chrom)
+        };
+        if chrom_files.contains_key(chrom) {
+            regions.entry(chrom.to_string()).or_default().push(record);
+        } else {
+            warn_missing_region(record);
+        }
+    });
+    regions
+}
+
+fn warn_missing_region(record: &BedRecord) {
+    log::warn!(
+        "{} No MAF blocks found for {}:{}-{}. Skipping it.",
+        "WARNING!".red(),
+        record.chrom,
+        record.chrom_start,
+        record.chrom_end
+    );
+}
+
+/// Convert a MAF block to the forward strand of the reference.
+/// Returns `None` if the block does not contain the reference species.
+/// Use the first sequence as the reference if the species is not specified.
+/// Rows are named by species or by the full MAF source name.
+pub(super) fn to_stitch_block(
+    aln: &MafAlignment,
+    reference: Option<&str>,
+    species_names: bool,
+) -> Option<StitchBlock> {
+    let target = find_reference(&aln.sequences, reference)?;
+    let is_reverse = target.strand == DnaStrand::Reverse;
+    let start = forward_start(target);
+    let orient = |text: &[u8]| -> String {
+        if is_reverse {
+            String::from_utf8_lossy(&alphabet::reverse_complement(text)).to_string()
+        } else {
+            String::from_utf8_lossy(text).to_string()
+        }
+    };
+
+    let mut rows: Vec<(String, String)> = Vec::with_capacity(aln.sequences.len());
+    aln.sequences.iter().for_each(|seq| {
+        let name = sequence_name(&seq.source, species_names);
+        // Keep the first sequence if a species has multiple
+        // sequences in the same block.
+        if rows.iter().all(|(row, _)| row != name) {
+            rows.push((name.to_string(), orient(&seq.text)));
+        }
+    });
+    Some(StitchBlock {
+        chrom: chrom_name(&target.source).to_string(),
+        start,
+        end: start + target.size,
+        reference: orient(&target.text),
+        rows,
+    })
+}
+
+/// Stitch blocks within the reference interval [start, end).
+/// The blocks must be sorted by the start position.
+/// Returns an empty matrix if no blocks overlap the interval.
+pub(super) fn stitch_blocks(blocks: &[&StitchBlock], start: usize, end: usize) -> SeqMatrix {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut cursor = start;
+    for block in blocks {
+        if block.end <= cursor || block.start >= end {
+            continue;
+        }
+        if block.start > cursor {
+            segments.push(Segment::Missing(block.start - cursor));
+            cursor = block.start;
+        }
+        let map = ReferenceMap::new(&block.reference);
+        let clip_start = cursor - block.start;
+        let clip_end = end.min(block.end) - block.start;
+        let col_start = if clip_start == 0 {
+            0
+        } else {
+            map.column(clip_start)
+                .expect("Block size does not match the reference sequence")
+        };
+        let col_end = if clip_end >= map.reference_len() {
+            block.reference.len()
+        } else {
+            map.column(clip_end)
+                .expect("Block size does not match the reference sequence")
+        };
+        segments.push(Segment::Block(block, col_start, col_end));
+        cursor = block.start + clip_end;
+    }
+
+    let species: IndexSet<&str> = segments
+        .iter()
+        .filter_map(|s| match s {
+            Segment::Block(block, _, _) => Some(block),
+            Segment::Missing(_) => None,
+        })
+        .flat_map(|block| block.rows.iter().map(|(name, _)| name.as_str()))
+        .collect();
+    if species.is_empty() {
+        return SeqMatrix::new();
+    }
+    if cursor < end {
+        segments.push(Segment::Missing(end - cursor));
+    }
+
+    species
+        .iter()
+        .map(|name| {
+            let mut seq = String::new();
+            segments.iter().for_each(|segment| match segment {
+                Segment::Missing(width) => seq.push_str(&"?".repeat(*width)),
+                Segment::Block(block, col_start, col_end) => {
+                    match block.rows.iter().find(|(species, _)| species == name) {
+                        Some((_, text)) => seq.push_str(&text[*col_start..*col_end]),
+                        None => seq.push_str(&"?".repeat(col_end - col_start)),
+                    }
+                }
+            });
+            (name.to_string(), seq)
+        })
+        .collect()
+}
+
+/// Find the reference sequence of a block.
+/// Use the first sequence if the reference species is not specified.
+pub(super) fn find_reference<'s>(
+    sequences: &'s [MafSequence],
+    reference: Option<&str>,
+) -> Option<&'s MafSequence> {
+    match reference {
+        Some(reference) => sequences
+            .iter()
+            .find(|s| species_name(&s.source) == reference),
+        None => sequences.first(),
+    }
+}
+
+/// Start position of a sequence in the forward strand of its source.
+pub(super) fn forward_start(seq: &MafSequence) -> usize {
+    match seq.strand {
+        DnaStrand::Reverse => seq.src_size - seq.start - seq.size,
+        _ => seq.start,
+    }
+}
+
+// Species name from a MAF source, e.g. hg18 in hg18.chr7.
+pub(super) fn species_name(source: &str) -> &str {
+    source
+        .split_once('.')
+        .map_or(source, |(species, _)| species)
+}
+
+// Output sequence name of a MAF source.
+pub(super) fn sequence_name(source: &str, species_names: bool) -> &str {
+    if species_names {
+        species_name(source)
+    } else {
+        source
+    }
+}
+
+// Chromosome name from a MAF source, e.g. chr7 in hg18.chr7.
+pub(super) fn chrom_name(source: &str) -> &str {
+    source.split_once('.').map_or(source, |(_, chrom)| chrom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helper::{
+        sequence::SeqParser,
+        types::{DataType, InputFmt},
+    };
+    use tempdir::TempDir;
+
+    macro_rules! input {
+        ($handle: ident, $blocks: ident) => {
+            let files = [PathBuf::from("tests/files/stitch/blocks.maf")];
+            let $handle = MafStitcher::new(&files, Some("ref"), Path::new("."), &OutputFmt::Fasta);
+            let counts = $handle.count_blocks();
+            let mut $blocks: HashMap<String, Vec<StitchBlock>> = HashMap::new();
+            $handle.stream_blocks(&files[0], &counts[0], |chrom, mut chrom_blocks| {
+                chrom_blocks.sort_by_key(|b| b.start);
+                $blocks.insert(chrom, chrom_blocks);
+            });
+        };
+    }
+
+    #[test]
+    fn test_source_names() {
+        assert_eq!("hg18", species_name("hg18.chr7"));
+        assert_eq!("chr7", chrom_name("hg18.chr7"));
+        assert_eq!("baboon", chrom_name("baboon"));
+    }
+
+    #[test]
+    fn test_count_blocks() {
+        let files = [PathBuf::from("tests/files/stitch/blocks.maf")];
+        let handle = MafStitcher::new(&files, Some("ref"), Path::new("."), &OutputFmt::Fasta);
+        let counts = handle.count_blocks();
+        assert_eq!((3, 1), (counts[0]["chr1"], counts[0]["chr2"]));
+    }
+
+    #[test]
+    fn test_stitch_split_files() {
+        let input = TempDir::new("input").unwrap();
+        let maf = std::fs::read_to_string("tests/files/stitch/blocks.maf").unwrap();
+        let (first, second) = maf.split_at(maf.find("a score=5.0").unwrap());
+        let files = [input.path().join("a.maf"), input.path().join("b.maf")];
+        std::fs::write(&files[0], first).unwrap();
+        std::fs::write(&files[1], format!("##maf version=1\n{}", second)).unwrap();
+        let output = TempDir::new("output").unwrap();
+        let handle = MafStitcher::new(&files, Some("ref"), output.path(), &OutputFmt::Fasta);
+        assert_eq!(2, handle.stitch(&StitchParameters::Chromosome));
+        let (matrix, _) = SeqParser::new(&output.path().join("chr1.fas"), &DataType::Dna)
+            .get_alignment(&InputFmt::Fasta);
+        assert_eq!("AAAA??????AC-GTTGCC", matrix["ref"]);
+    }
+
+    #[test]
+    fn test_parse_blocks() {
+        input!(handle, blocks);
+        assert_eq!(2, blocks.len());
+        let chr1 = &blocks["chr1"];
+        assert_eq!(
+            vec![0, 10, 14],
+            chr1.iter().map(|b| b.start).collect::<Vec<_>>()
+        );
+        // Reverse strand block
+        assert_eq!("TGCC", chr1[2].reference);
+        assert_eq!(18, chr1[2].end);
+    }
+
+    #[test]
+    fn test_stitch_chromosome() {
+        input!(handle, blocks);
+        let chr1: Vec<&StitchBlock> = blocks["chr1"].iter().collect();
+        let matrix = stitch_blocks(&chr1, 0, 18);
+        assert_eq!(3, matrix.len());
+        assert_eq!("AAAA??????AC-GTTGCC", matrix["ref"]);
+        assert_eq!("AATA??????ACTGT????", matrix["sp1"]);
+        assert_eq!("AA-A???????????TACC", matrix["sp2"]);
+    }
+
+    #[test]
+    fn test_stitch_region() {
+        input!(handle, blocks);
+        let chr1: Vec<&StitchBlock> = blocks["chr1"].iter().collect();
+        let matrix = stitch_blocks(&chr1, 2, 12);
+        assert_eq!("AA??????AC-", matrix["ref"]);
+        assert_eq!("TA??????ACT", matrix["sp1"]);
+        assert_eq!("-A?????????", matrix["sp2"]);
+    }
+
+    #[test]
+    fn test_stitch_empty_region() {
+        input!(handle, blocks);
+        let chr1: Vec<&StitchBlock> = blocks["chr1"].iter().collect();
+        let matrix = stitch_blocks(&chr1, 20, 25);
+        assert!(matrix.is_empty());
+    }
+}
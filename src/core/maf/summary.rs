@@ -0,0 +1,323 @@
+//! Summarize MAF files.
+//!
+//! Write two CSV files to the output directory:
+//! 1. `maf_summary.csv`: block counts, block length
+//!    and block score distribution of each file.
+//! 2. `maf_species_coverage.csv`: the number of reference bases
+//!    aligned to each species and the proportion of the reference
+//!    bases covered by the species.
+//!
+//! Block lengths are the number of alignment columns.
+//! The reference is the first sequence in each block
+//! if the reference species is not specified.
+//! Blocks without the reference sequence are excluded
+//! from the species coverage.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use indexmap::{IndexMap, IndexSet};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    core::maf::{filter::block_len, stitch::species_name},
+    helper::utils,
+    parser::maf::{MafAlignment, MafParagraph, MafReader},
+    stats::common::CommonStats,
+};
+
+const SUMMARY_FNAME: &str = "maf_summary";
+const COVERAGE_FNAME: &str = "maf_species_coverage";
+
+pub struct MafSummary<'a> {
+    input_files: &'a [PathBuf],
+    /// Reference species name. Use the first sequence
+    /// in each block if not specified.
+    reference: Option<&'a str>,
+    output_dir: &'a Path,
+}
+
+impl<'a> MafSummary<'a> {
+    pub fn new(
+        input_files: &'a [PathBuf],
+        reference: Option<&'a str>,
+        output_dir: &'a Path,
+    ) -> Self {
+        Self {
+            input_files,
+            reference,
+            output_dir,
+        }
+    }
+
+    /// Summarize MAF files and write the summary to CSV files.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::maf::summary::MafSummary;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/stitch/blocks.maf")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// MafSummary::new(&files, Some("ref"), output.path()).summarize();
+    /// assert!(output.path().join("maf_summary.csv").exists());
+    /// assert!(output.path().join("maf_species_coverage.csv").exists());
+    /// ```
+    pub fn summarize(&self) {
+        let spin = utils::set_spinner();
+        spin.set_message("Summarizing MAF files...");
+        let mut summaries: Vec<(MafFileSummary, Vec<SpeciesCoverage>)> = self
+            .input_files
+            .par_iter()
+            .map(|file| self.summarize_file(file))
+            .collect();
+        summaries.sort_by(|a, b| a.0.file_name.cmp(&b.0.file_name));
+        spin.set_message("Writing summary files...");
+        self.write_summary(&summaries);
+        spin.finish_with_message("Finished summarizing MAF files!\n");
+        self.print_output_info(&summaries);
+    }
+
+    fn summarize_file(&self, file: &Path) -> (MafFileSummary, Vec<SpeciesCoverage>) {
+        let file_name = file
+            .file_name()
+            .expect("Failed getting file name")
+            .to_string_lossy()
+            .to_string();
+        let maf = MafReader::from_path(file);
+        let mut lengths = Vec::new();
+        let mut scores = Vec::new();
+        let mut reference_bases = 0;
+        // Species name and the number of aligned reference bases
+        let mut aligned_bases: IndexMap<String, (usize, usize)> = IndexMap::new();
+        maf.into_iter().for_each(|paragraph| {
+            if let MafParagraph::Alignment(aln) = paragraph {
+                lengths.push(block_len(&aln));
+                if let Some(score) = aln.score {
+                    scores.push(score);
+                }
+                if let Some(ref_bases) = self.count_aligned_bases(&aln, &mut aligned_bases) {
+                    reference_bases += ref_bases;
+                }
+            }
+        });
+
+        let coverage = aligned_bases
+            .into_iter()
+            .map(|(species, (block_counts, bases))| SpeciesCoverage {
+                file_name: file_name.clone(),
+                species,
+                block_counts,
+                aligned_bases: bases,
+                coverage: proportion(bases, reference_bases),
+            })
+            .collect();
+        let summary = MafFileSummary::new(file_name, &lengths, &scores, reference_bases);
+        (summary, coverage)
+    }
+
+    // Count reference bases aligned to each species in a block.
+    // Returns the number of reference bases in the block or `None`
+    // if the block does not contain the reference.
+    fn count_aligned_bases(
+        &self,
+        aln: &MafAlignment,
+        aligned_bases: &mut IndexMap<String, (usize, usize)>,
+    ) -> Option<usize> {
+        let reference = match self.reference {
+            Some(reference) => aln
+                .sequences
+                .iter()
+                .find(|s| species_name(&s.source) == reference)?,
+            None => aln.sequences.first()?,
+        };
+        let ref_columns: Vec<bool> = reference.text.iter().map(|&c| is_base(c)).collect();
+        let mut species_columns: IndexMap<&str, Vec<bool>> = IndexMap::new();
+        aln.sequences.iter().for_each(|seq| {
+            let columns = species_columns
+                .entry(species_name(&seq.source))
+                .or_insert_with(|| vec![false; ref_columns.len()]);
+            // A species may have multiple sequences (e.g. paralogs)
+            // in the same block. Count each reference base once.
+            seq.text
+                .iter()
+                .zip(columns.iter_mut())
+                .for_each(|(&c, column)| *column |= is_base(c));
+        });
+        species_columns.into_iter().for_each(|(species, columns)| {
+            let bases = columns
+                .iter()
+                .zip(ref_columns.iter())
+                .filter(|(is_aligned, is_ref)| **is_aligned && **is_ref)
+                .count();
+            let counts = aligned_bases.entry(species.to_string()).or_insert((0, 0));
+            counts.0 += 1;
+            counts.1 += bases;
+        });
+        Some(ref_columns.iter().filter(|&&is_ref| is_ref).count())
+    }
+
+    fn write_summary(&self, summaries: &[(MafFileSummary, Vec<SpeciesCoverage>)]) {
+        fs::create_dir_all(self.output_dir).expect("Failed creating output directory");
+        let output = self.output_dir.join(SUMMARY_FNAME).with_extension("csv");
+        let mut writer = csv::Writer::from_path(output).expect("Failed to create CSV writer");
+        summaries.iter().for_each(|(summary, _)| {
+            writer
+                .serialize(summary)
+                .expect("Failed writing MAF summary");
+        });
+        writer.flush().expect("Failed writing MAF summary");
+
+        let output = self.output_dir.join(COVERAGE_FNAME).with_extension("csv");
+        let mut writer = csv::Writer::from_path(output).expect("Failed to create CSV writer");
+        summaries
+            .iter()
+            .flat_map(|(_, coverage)| coverage.iter())
+            .for_each(|coverage| {
+                writer
+                    .serialize(coverage)
+                    .expect("Failed writing species coverage");
+            });
+        writer.flush().expect("Failed writing species coverage");
+    }
+
+    fn print_output_info(&self, summaries: &[(MafFileSummary, Vec<SpeciesCoverage>)]) {
+        let block_counts: usize = summaries.iter().map(|(s, _)| s.block_counts).sum();
+        let species_counts = summaries
+            .iter()
+            .flat_map(|(_, coverage)| coverage.iter().map(|c| c.species.as_str()))
+            .collect::<IndexSet<&str>>()
+            .len();
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&summaries.len()));
+        log::info!("{:18}: {}", "Block counts", utils::fmt_num(&block_counts));
+        log::info!(
+            "{:18}: {}",
+            "Species counts",
+            utils::fmt_num(&species_counts)
+        );
+    }
+}
+
+/// Block summary of a MAF file.
+#[derive(Debug, Serialize)]
+struct MafFileSummary {
+    file_name: String,
+    block_counts: usize,
+    /// Total number of alignment columns
+    total_length: usize,
+    min_length: usize,
+    max_length: usize,
+    mean_length: f64,
+    median_length: f64,
+    /// Number of reference bases in blocks with the reference
+    reference_bases: usize,
+    /// Number of blocks with a score
+    scored_blocks: usize,
+    min_score: Option<f64>,
+    max_score: Option<f64>,
+    mean_score: Option<f64>,
+    median_score: Option<f64>,
+}
+
+impl MafFileSummary {
+    fn new(file_name: String, lengths: &[usize], scores: &[f64], reference_bases: usize) -> Self {
+        let mut length_stats = CommonStats::new();
+        if !lengths.is_empty() {
+            length_stats.calculate(lengths);
+        }
+        let mut scores = scores.to_vec();
+        scores.sort_by(|a, b| a.total_cmp(b));
+        let mean_score = match scores.is_empty() {
+            true => None,
+            false => Some(scores.iter().sum::<f64>() / scores.len() as f64),
+        };
+        Self {
+            file_name,
+            block_counts: lengths.len(),
+            total_length: length_stats.sum,
+            min_length: length_stats.min,
+            max_length: length_stats.max,
+            mean_length: length_stats.mean,
+            median_length: length_stats.median,
+            reference_bases,
+            scored_blocks: scores.len(),
+            min_score: scores.first().copied(),
+            max_score: scores.last().copied(),
+            mean_score,
+            median_score: median(&scores),
+        }
+    }
+}
+
+/// Reference coverage of a species in a MAF file.
+#[derive(Debug, Serialize)]
+struct SpeciesCoverage {
+    file_name: String,
+    species: String,
+    /// Number of blocks with the reference that contain the species
+    block_counts: usize,
+    /// Number of reference bases aligned to a base of the species
+    aligned_bases: usize,
+    /// Proportion of the reference bases covered by the species
+    coverage: f64,
+}
+
+fn is_base(c: u8) -> bool {
+    c != b'-' && c != b'.'
+}
+
+fn proportion(value: usize, total: usize) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => value as f64 / total as f64,
+    }
+}
+
+// Median of sorted values.
+fn median(values: &[f64]) -> Option<f64> {
+    let n = values.len();
+    match n {
+        0 => None,
+        _ if n.is_multiple_of(2) => Some((values[n / 2 - 1] + values[n / 2]) / 2.0),
+        _ => Some(values[n / 2]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_file() {
+        let handle = MafSummary::new(&[], Some("ref"), Path::new("."));
+        let (summary, coverage) = handle.summarize_file(Path::new("tests/files/stitch/blocks.maf"));
+        assert_eq!(5, summary.block_counts);
+        assert_eq!(19, summary.total_length);
+        assert_eq!(3, summary.min_length);
+        assert_eq!(5, summary.max_length);
+        assert_eq!(Some(1.0), summary.min_score);
+        assert_eq!(Some(20.0), summary.max_score);
+        assert_eq!(Some(8.0), summary.median_score);
+        // Blocks with ref: 4 + 4 + 4 + 3
+        assert_eq!(15, summary.reference_bases);
+
+        let sp2 = coverage.iter().find(|c| c.species == "sp2").unwrap();
+        assert_eq!(2, sp2.block_counts);
+        assert_eq!(7, sp2.aligned_bases);
+        let reference = coverage.iter().find(|c| c.species == "ref").unwrap();
+        assert_eq!(1.0, reference.coverage);
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(None, median(&[]));
+        assert_eq!(Some(2.0), median(&[1.0, 3.0]));
+        assert_eq!(Some(3.0), median(&[1.0, 3.0, 4.0]));
+    }
+}
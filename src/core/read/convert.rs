@@ -0,0 +1,414 @@
+//! Convert raw-read sequence files.
+//!
+//! Support FASTQ and compressed FASTQ in gunzip format.
+//! Available conversions:
+//! 1. FASTQ to FASTA. Quality scores are discarded.
+//! 2. Interleave. R1 and R2 files of a sample are written
+//!    to a single file with alternating mates.
+//! 3. Deinterleave. Interleaved files are split into R1 and R2 files.
+//! 4. Merge lanes. Files of the same sample sequenced on multiple lanes
+//!    are concatenated in the lane order.
+//!
+//! Reads are streamed and all outputs are gzip compressed.
+//! Inputs that would be written to the same output file,
+//! e.g. the same sample in two directories, are rejected
+//! before any file is written.
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use noodles::fastq::{Record, io::Reader};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::read_file_stem;
+use crate::{
+    helper::{
+        files::check_unique_outputs, finder::SeqReadFinder, types::ReadConvertMode,
+        types::SeqReadFmt, utils,
+    },
+    stats::fastq::open_reads,
+    writer::fastq::{FastqWriter, ReadFastaWriter},
+};
+
+const CONVERT_SUMMARY_FNAME: &str = "read-convert-summary";
+
+/// Read counts of a converted file.
+#[derive(Debug, Serialize)]
+pub struct ReadConvertRecord {
+    /// Input files separated by semicolons
+    pub inputs: String,
+    pub output: PathBuf,
+    pub read_count: usize,
+}
+
+pub struct ReadConverter<'a> {
+    inputs: &'a [PathBuf],
+    input_fmt: &'a SeqReadFmt,
+    output_dir: &'a Path,
+    mode: &'a ReadConvertMode,
+}
+
+impl<'a> ReadConverter<'a> {
+    pub fn new(
+        inputs: &'a [PathBuf],
+        input_fmt: &'a SeqReadFmt,
+        output_dir: &'a Path,
+        mode: &'a ReadConvertMode,
+    ) -> Self {
+        Self {
+            inputs,
+            input_fmt,
+            output_dir,
+            mode,
+        }
+    }
+
+    /// Convert the input files and write the outputs to the output directory.
+    /// Returns the number of reads written.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::read::convert::ReadConverter;
+    /// use segul::helper::types::{ReadConvertMode, SeqReadFmt};
+    ///
+    /// let files = vec![PathBuf::from("tests/files/paired/sample_R1.fastq")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let mode = ReadConvertMode::Fasta;
+    /// let counts = ReadConverter::new(&files, &SeqReadFmt::Auto, output.path(), &mode)
+    ///     .convert();
+    /// assert_eq!(counts, 3);
+    /// ```
+    pub fn convert(&self) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Converting reads...");
+        let mut records = match self.mode {
+            ReadConvertMode::Fasta => self.to_fasta(),
+            ReadConvertMode::Interleave => self.interleave(),
+            ReadConvertMode::Deinterleave => self.deinterleave(),
+            ReadConvertMode::MergeLanes => self.merge_lanes(),
+        };
+        records.sort_by(|a, b| a.output.cmp(&b.output));
+        self.write_summary(&records);
+        spin.finish_with_message("Finished converting reads!\n");
+        let read_counts = records.iter().map(|rec| rec.read_count).sum();
+        self.print_output_info(&records, read_counts);
+        read_counts
+    }
+
+    fn to_fasta(&self) -> Vec<ReadConvertRecord> {
+        check_unique_outputs(self.inputs.iter().map(|file| {
+            (
+                self.output_path(file, "", "fasta"),
+                file.display().to_string(),
+            )
+        }));
+        self.inputs
+            .par_iter()
+            .map(|file| {
+                let output = self.output_path(file, "", "fasta");
+                let mut writer =
+                    ReadFastaWriter::from_path(&output).expect("Failed creating FASTA file");
+                let mut reader = Reader::new(open_reads(file, self.input_fmt));
+                let mut read_count = 0;
+                reader.records().map_while(Result::ok).for_each(|read| {
+                    writer
+                        .write_record(&read)
+                        .expect("Failed writing FASTA record");
+                    read_count += 1;
+                });
+                writer.finish().expect("Failed writing FASTA file");
+                ReadConvertRecord {
+                    inputs: file.display().to_string(),
+                    output,
+                    read_count,
+                }
+            })
+            .collect()
+    }
+
+    // Read the mates in lockstep and write them to a single file.
+    fn interleave(&self) -> Vec<ReadConvertRecord> {
+        let (pairs, singles) = SeqReadFinder::find_pairs(self.inputs);
+        singles.iter().for_each(|file| {
+            log::warn!(
+                "{} {} has no mate. Skipping the file.",
+                "WARNING!".red(),
+                file.display()
+            );
+        });
+        check_unique_outputs(pairs.iter().map(|pair| {
+            (
+                self.sample_output_path(&pair.sample),
+                format!("{};{}", pair.read_1.display(), pair.read_2.display()),
+            )
+        }));
+        pairs
+            .par_iter()
+            .map(|pair| {
+                let output = self.sample_output_path(&pair.sample);
+                let mut writer =
+                    FastqWriter::from_path(&output).expect("Failed creating FASTQ file");
+                let mut reader_1 = Reader::new(open_reads(&pair.read_1, self.input_fmt));
+                let mut reader_2 = Reader::new(open_reads(&pair.read_2, self.input_fmt));
+                let mut records_1 = reader_1.records();
+                let mut records_2 = reader_2.records();
+                let mut read_count = 0;
+                loop {
+                    let read_1 = records_1.next().and_then(Result::ok);
+                    let read_2 = records_2.next().and_then(Result::ok);
+                    match (read_1, read_2) {
+                        (Some(read_1), Some(read_2)) => {
+                            self.write_fastq(&mut writer, &read_1);
+                            self.write_fastq(&mut writer, &read_2);
+                            read_count += 2;
+                        }
+                        (None, None) => break,
+                        _ => {
+                            log::warn!(
+                                "{} Mates of {} have different read counts. \
+                                Reads without mates are removed.",
+                                "WARNING!".red(),
+                                pair.sample
+                            );
+                            break;
+                        }
+                    }
+                }
+                writer.finish().expect("Failed writing FASTQ file");
+                ReadConvertRecord {
+                    inputs: format!("{};{}", pair.read_1.display(), pair.read_2.display()),
+                    output,
+                    read_count,
+                }
+            })
+            .collect()
+    }
+
+    // Write odd reads to the R1 file and even reads to the R2 file.
+    fn deinterleave(&self) -> Vec<ReadConvertRecord> {
+        check_unique_outputs(self.inputs.iter().map(|file| {
+            (
+                self.output_path(file, "_R1", "fastq"),
+                file.display().to_string(),
+            )
+        }));
+        self.inputs
+            .par_iter()
+            .flat_map(|file| {
+                let outputs = [
+                    self.output_path(file, "_R1", "fastq"),
+                    self.output_path(file, "_R2", "fastq"),
+                ];
+                let mut writers: Vec<FastqWriter> = outputs
+                    .iter()
+                    .map(|output| {
+                        FastqWriter::from_path(output).expect("Failed creating FASTQ file")
+                    })
+                    .collect();
+                let mut reader = Reader::new(open_reads(file, self.input_fmt));
+                let mut records = reader.records().map_while(Result::ok);
+                let mut read_count = 0;
+                while let Some(read_1) = records.next() {
+                    match records.next() {
+                        Some(read_2) => {
+                            self.write_fastq(&mut writers[0], &read_1);
+                            self.write_fastq(&mut writers[1], &read_2);
+                            read_count += 1;
+                        }
+                        None => log::warn!(
+                            "{} {} has an odd number of reads. \
+                            The last read is removed.",
+                            "WARNING!".red(),
+                            file.display()
+                        ),
+                    }
+                }
+                writers
+                    .into_iter()
+                    .for_each(|writer| writer.finish().expect("Failed writing FASTQ file"));
+                outputs
+                    .into_iter()
+                    .map(|output| ReadConvertRecord {
+                        inputs: file.display().to_string(),
+                        output,
+                        read_count,
+                    })
+                    .collect::<Vec<ReadConvertRecord>>()
+            })
+            .collect()
+    }
+
+    // Concatenate lane files in the lane order.
+    fn merge_lanes(&self) -> Vec<ReadConvertRecord> {
+        let (lanes, others) = SeqReadFinder::find_lanes(self.inputs);
+        others.iter().for_each(|file| {
+            log::warn!(
+                "{} {} has no lane number. Skipping the file.",
+                "WARNING!".red(),
+                file.display()
+            );
+        });
+        check_unique_outputs(lanes.iter().map(|lane| {
+            let inputs: Vec<String> = lane
+                .files
+                .iter()
+                .map(|file| file.display().to_string())
+                .collect();
+            (self.sample_output_path(&lane.name), inputs.join(";"))
+        }));
+        lanes
+            .par_iter()
+            .map(|lane| {
+                let output = self.sample_output_path(&lane.name);
+                let mut writer =
+                    FastqWriter::from_path(&output).expect("Failed creating FASTQ file");
+                let mut read_count = 0;
+                lane.files.iter().for_each(|file| {
+                    let mut reader = Reader::new(open_reads(file, self.input_fmt));
+                    reader.records().map_while(Result::ok).for_each(|read| {
+                        self.write_fastq(&mut writer, &read);
+                        read_count += 1;
+                    });
+                });
+                writer.finish().expect("Failed writing FASTQ file");
+                let inputs: Vec<String> = lane
+                    .files
+                    .iter()
+                    .map(|file| file.display().to_string())
+                    .collect();
+                ReadConvertRecord {
+                    inputs: inputs.join(";"),
+                    output,
+                    read_count,
+                }
+            })
+            .collect()
+    }
+
+    fn write_fastq(&self, writer: &mut FastqWriter, read: &Record) {
+        writer
+            .write_record(read)
+            .expect("Failed writing FASTQ record");
+    }
+
+    // Output of reads combined from several files of a sample.
+    fn sample_output_path(&self, name: &str) -> PathBuf {
+        self.output_dir.join(format!("{}.fastq.gz", name))
+    }
+
+    fn output_path(&self, input: &Path, suffix: &str, ext: &str) -> PathBuf {
+        let file_name = input
+            .file_name()
+            .and_then(|name| name.to_str())
+            .expect("Failed parsing file name");
+        self.output_dir.join(format!(
+            "{}{}.{}.gz",
+            read_file_stem(file_name),
+            suffix,
+            ext
+        ))
+    }
+
+    fn write_summary(&self, records: &[ReadConvertRecord]) {
+        let output = self
+            .output_dir
+            .join(CONVERT_SUMMARY_FNAME)
+            .with_extension("csv");
+        let mut writer = csv::Writer::from_path(output).expect("Failed to create CSV writer");
+        records.iter().for_each(|rec| {
+            writer.serialize(rec).expect("Failed to write CSV record");
+        });
+        writer.flush().expect("Failed to flush CSV writer");
+    }
+
+    fn print_output_info(&self, records: &[ReadConvertRecord], read_counts: usize) {
+        let output_fmt = match self.mode {
+            ReadConvertMode::Fasta => "FASTA (gzip)",
+            _ => "FASTQ (gzip)",
+        };
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&records.len()));
+        log::info!("{:18}: {}", "Read counts", utils::fmt_num(&read_counts));
+        log::info!("{:18}: {}", "Output format", output_fmt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs;
+
+    use tempdir::TempDir;
+
+    fn read_names(path: &Path) -> Vec<String> {
+        let mut reader = Reader::new(open_reads(path, &SeqReadFmt::Gzip));
+        reader
+            .records()
+            .map(|r: std::io::Result<Record>| r.unwrap().name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_interleave_roundtrip() {
+        let files = vec![
+            PathBuf::from("tests/files/raw/read_1.fastq"),
+            PathBuf::from("tests/files/raw/read_2.fastq"),
+        ];
+        let output = TempDir::new("temp").unwrap();
+        let interleaved = output.path().join("interleaved");
+        let mode = ReadConvertMode::Interleave;
+        let counts = ReadConverter::new(&files, &SeqReadFmt::Auto, &interleaved, &mode).convert();
+        assert_eq!(4, counts);
+        let input = vec![interleaved.join("read.fastq.gz")];
+        let names = read_names(&input[0]);
+        assert_eq!(names[0], names[1]);
+
+        let split = output.path().join("split");
+        let mode = ReadConvertMode::Deinterleave;
+        let counts = ReadConverter::new(&input, &SeqReadFmt::Gzip, &split, &mode).convert();
+        assert_eq!(4, counts);
+        assert_eq!(2, read_names(&split.join("read_R1.fastq.gz")).len());
+        assert_eq!(2, read_names(&split.join("read_R2.fastq.gz")).len());
+    }
+
+    #[test]
+    fn test_merge_lanes() {
+        let input = TempDir::new("input").unwrap();
+        let files: Vec<PathBuf> = ["s1_L002_R1.fastq", "s1_L001_R1.fastq"]
+            .iter()
+            .map(|name| {
+                let file = input.path().join(name);
+                fs::copy("tests/files/paired/sample_R1.fastq", &file).unwrap();
+                file
+            })
+            .collect();
+        let output = TempDir::new("temp").unwrap();
+        let mode = ReadConvertMode::MergeLanes;
+        let counts = ReadConverter::new(&files, &SeqReadFmt::Fastq, output.path(), &mode).convert();
+        assert_eq!(6, counts);
+        assert_eq!(6, read_names(&output.path().join("s1_R1.fastq.gz")).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "are both written to")]
+    fn test_merge_lanes_output_collision() {
+        let input = TempDir::new("input").unwrap();
+        let files: Vec<PathBuf> = ["run_1", "run_2"]
+            .iter()
+            .map(|dir| {
+                let dir = input.path().join(dir);
+                fs::create_dir(&dir).unwrap();
+                let file = dir.join("s1_L001_R1.fastq");
+                fs::copy("tests/files/paired/sample_R1.fastq", &file).unwrap();
+                file
+            })
+            .collect();
+        let output = TempDir::new("temp").unwrap();
+        let mode = ReadConvertMode::MergeLanes;
+        ReadConverter::new(&files, &SeqReadFmt::Fastq, output.path(), &mode).convert();
+    }
+}
@@ -0,0 +1,502 @@
+//! Filter and trim raw-read sequences.
+//!
+//! Support FASTQ and compressed FASTQ in gunzip format.
+//! Reads are processed in the following order:
+//! 1. Adapter trimming. Reads are cut at the first adapter match,
+//!    including partial adapters at the 3' end.
+//! 2. Sliding-window quality trimming. Reads are cut at the first window
+//!    from the 5' end with a mean quality score below the cutoff.
+//! 3. Minimum length. Reads without any base left are always removed.
+//! 4. Maximum proportion of N bases.
+//! 5. Minimum mean quality score.
+//!
+//! R1 and R2 files of paired-end reads are filtered together.
+//! Both mates are removed if any of them fails the filters.
+//! The filtered reads are written as gzipped FASTQ.
+//! Summary statistics of the reads before and after filtering
+//! are written using the read summary writer.
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use noodles::fastq::{Record, io::Reader};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::{check_unique_fastq_outputs, fastq_output_path};
+use crate::{
+    helper::{
+        files,
+        finder::{ReadPair, SeqReadFinder},
+        types::{QScoreEncoding, SeqReadFmt},
+        utils,
+    },
+    parser::fasta::FastaReader,
+    stats::fastq::{FastqSummary, detect_qscore_encoding, open_reads},
+    writer::{fastq::FastqWriter, read::ReadSummaryWriter},
+};
+
+const FILTER_SUMMARY_FNAME: &str = "read-filter-summary";
+const BEFORE_PREFIX: &str = "before";
+const AFTER_PREFIX: &str = "after";
+
+/// Criteria to filter and trim reads.
+/// Criteria set to `None` are not applied.
+pub struct ReadFilterParameters {
+    /// Quality score encoding.
+    /// In Auto, it is detected for each file.
+    pub encoding: QScoreEncoding,
+    /// Adapter sequences to trim
+    pub adapters: Vec<Vec<u8>>,
+    /// Minimum overlap to trim a partial adapter at the 3' end
+    pub min_adapter_overlap: usize,
+    /// Number of bases in the sliding window
+    pub window_size: usize,
+    /// Minimum mean quality score in the sliding window
+    pub window_quality: Option<f64>,
+    /// Minimum read length after trimming
+    pub min_length: Option<usize>,
+    /// Maximum proportion of N bases
+    pub max_n: Option<f64>,
+    /// Minimum mean quality score of a read
+    pub min_mean_quality: Option<f64>,
+}
+
+impl Default for ReadFilterParameters {
+    fn default() -> Self {
+        Self {
+            encoding: QScoreEncoding::Auto,
+            adapters: Vec::new(),
+            min_adapter_overlap: 5,
+            window_size: 4,
+            window_quality: None,
+            min_length: None,
+            max_n: None,
+            min_mean_quality: None,
+        }
+    }
+}
+
+impl ReadFilterParameters {
+    /// Parse adapter sequences from a FASTA file.
+    pub fn parse_adapters(&mut self, path: &Path) {
+        let reader = FastaReader::new(files::open_file(path));
+        self.adapters = reader
+            .map(|rec| rec.seq.to_ascii_uppercase().into_bytes())
+            .filter(|seq| !seq.is_empty())
+            .collect();
+    }
+}
+
+/// Read counts of a filtered file.
+#[derive(Debug, Serialize)]
+pub struct ReadFilterRecord {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub read_count: usize,
+    pub kept_count: usize,
+    pub adapter_trimmed: usize,
+    pub quality_trimmed: usize,
+    pub too_short: usize,
+    pub too_many_n: usize,
+    pub low_quality: usize,
+    /// Reads removed because their mates failed the filters
+    pub mate_removed: usize,
+}
+
+impl ReadFilterRecord {
+    fn new(input: &Path, output: &Path) -> Self {
+        Self {
+            input: input.to_path_buf(),
+            output: output.to_path_buf(),
+            read_count: 0,
+            kept_count: 0,
+            adapter_trimmed: 0,
+            quality_trimmed: 0,
+            too_short: 0,
+            too_many_n: 0,
+            low_quality: 0,
+            mate_removed: 0,
+        }
+    }
+}
+
+pub struct ReadFilter<'a> {
+    inputs: &'a [PathBuf],
+    input_fmt: &'a SeqReadFmt,
+    output_dir: &'a Path,
+    params: &'a ReadFilterParameters,
+}
+
+impl<'a> ReadFilter<'a> {
+    pub fn new(
+        inputs: &'a [PathBuf],
+        input_fmt: &'a SeqReadFmt,
+        output_dir: &'a Path,
+        params: &'a ReadFilterParameters,
+    ) -> Self {
+        Self {
+            inputs,
+            input_fmt,
+            output_dir,
+            params,
+        }
+    }
+
+    /// Filter reads and write one gzipped FASTQ per input file.
+    /// Returns the number of kept reads.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::read::filter::{ReadFilter, ReadFilterParameters};
+    /// use segul::helper::types::SeqReadFmt;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/paired/sample_R1.fastq")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let params = ReadFilterParameters {
+    ///     min_length: Some(5),
+    ///     ..Default::default()
+    /// };
+    /// let counts = ReadFilter::new(&files, &SeqReadFmt::Auto, output.path(), &params).filter();
+    /// assert_eq!(counts, 3);
+    /// ```
+    pub fn filter(&self) -> usize {
+        check_unique_fastq_outputs(self.output_dir, self.inputs);
+        let spin = utils::set_spinner();
+        spin.set_message("Filtering reads...");
+        let (pairs, singles) = SeqReadFinder::find_pairs(self.inputs);
+        let mut records: Vec<ReadFilterRecord> = pairs
+            .par_iter()
+            .flat_map(|pair| {
+                let (read_1, read_2) = self.filter_pair(pair);
+                vec![read_1, read_2]
+            })
+            .chain(singles.par_iter().map(|file| self.filter_single(file)))
+            .collect();
+        records.sort_by(|a, b| a.input.cmp(&b.input));
+        spin.set_message("Summarizing reads before and after filtering...");
+        self.write_reports(&records);
+        spin.finish_with_message("Finished filtering reads!\n");
+        let kept_counts = records.iter().map(|rec| rec.kept_count).sum();
+        self.print_output_info(&records, kept_counts);
+        kept_counts
+    }
+
+    fn filter_single(&self, input: &Path) -> ReadFilterRecord {
+        let output = fastq_output_path(self.output_dir, input);
+        let mut counts = ReadFilterRecord::new(input, &output);
+        let offset = self.resolve_offset(input);
+        let mut writer = FastqWriter::from_path(&output).expect("Failed creating FASTQ file");
+        let mut reader = Reader::new(open_reads(input, self.input_fmt));
+        reader.records().for_each(|record| match record {
+            Ok(mut record) => {
+                counts.read_count += 1;
+                if self.filter_record(&mut record, offset, &mut counts) {
+                    writer
+                        .write_record(&record)
+                        .expect("Failed writing FASTQ record");
+                    counts.kept_count += 1;
+                }
+            }
+            Err(e) => log::error!("Error parsing fastq record: {}", e),
+        });
+        writer.finish().expect("Failed writing FASTQ file");
+        counts
+    }
+
+    fn filter_pair(&self, pair: &ReadPair) -> (ReadFilterRecord, ReadFilterRecord) {
+        let output_1 = fastq_output_path(self.output_dir, &pair.read_1);
+        let output_2 = fastq_output_path(self.output_dir, &pair.read_2);
+        let mut counts_1 = ReadFilterRecord::new(&pair.read_1, &output_1);
+        let mut counts_2 = ReadFilterRecord::new(&pair.read_2, &output_2);
+        let offset_1 = self.resolve_offset(&pair.read_1);
+        let offset_2 = self.resolve_offset(&pair.read_2);
+        let mut writer_1 = FastqWriter::from_path(&output_1).expect("Failed creating FASTQ file");
+        let mut writer_2 = FastqWriter::from_path(&output_2).expect("Failed creating FASTQ file");
+        let mut reader_1 = Reader::new(open_reads(&pair.read_1, self.input_fmt));
+        let mut reader_2 = Reader::new(open_reads(&pair.read_2, self.input_fmt));
+        let mut records_1 = reader_1.records();
+        let mut records_2 = reader_2.records();
+        loop {
+            match (records_1.next(), records_2.next()) {
+                (None, None) => break,
+                (Some(Ok(mut record_1)), Some(Ok(mut record_2))) => {
+                    counts_1.read_count += 1;
+                    counts_2.read_count += 1;
+                    let is_kept_1 = self.filter_record(&mut record_1, offset_1, &mut counts_1);
+                    let is_kept_2 = self.filter_record(&mut record_2, offset_2, &mut counts_2);
+                    match (is_kept_1, is_kept_2) {
+                        (true, true) => {
+                            writer_1
+                                .write_record(&record_1)
+                                .expect("Failed writing FASTQ record");
+                            writer_2
+                                .write_record(&record_2)
+                                .expect("Failed writing FASTQ record");
+                            counts_1.kept_count += 1;
+                            counts_2.kept_count += 1;
+                        }
+                        (true, false) => counts_1.mate_removed += 1,
+                        (false, true) => counts_2.mate_removed += 1,
+                        (false, false) => (),
+                    }
+                }
+                // Reads without mates are removed.
+                (Some(Ok(_)), None) => {
+                    counts_1.read_count += 1;
+                    counts_1.mate_removed += 1;
+                }
+                (None, Some(Ok(_))) => {
+                    counts_2.read_count += 1;
+                    counts_2.mate_removed += 1;
+                }
+                (Some(Err(e)), _) | (_, Some(Err(e))) => {
+                    log::error!("Error parsing fastq record: {}", e);
+                    break;
+                }
+            }
+        }
+        if counts_1.read_count != counts_2.read_count {
+            log::warn!(
+                "{} Sample {} has different read counts in R1 and R2. \
+                Reads without mates are removed.",
+                "WARNING!".red(),
+                pair.sample
+            );
+        }
+        writer_1.finish().expect("Failed writing FASTQ file");
+        writer_2.finish().expect("Failed writing FASTQ file");
+        (counts_1, counts_2)
+    }
+
+    // Trim a read and check whether it passes the filters.
+    fn filter_record(
+        &self,
+        record: &mut Record,
+        offset: u8,
+        counts: &mut ReadFilterRecord,
+    ) -> bool {
+        let read_len = record.sequence().len();
+        let mut end = read_len;
+        if let Some(adapter_start) = find_adapters(
+            record.sequence(),
+            &self.params.adapters,
+            self.params.min_adapter_overlap,
+        ) {
+            end = adapter_start;
+            counts.adapter_trimmed += 1;
+        }
+        if let Some(min_quality) = self.params.window_quality {
+            let window_end = sliding_window_end(
+                &record.quality_scores()[..end],
+                offset,
+                self.params.window_size,
+                min_quality,
+            );
+            if window_end < end {
+                end = window_end;
+                counts.quality_trimmed += 1;
+            }
+        }
+        if end < read_len {
+            record.sequence_mut().truncate(end);
+            record.quality_scores_mut().truncate(end);
+        }
+        if end == 0 || self.params.min_length.is_some_and(|min| end < min) {
+            counts.too_short += 1;
+            return false;
+        }
+        if let Some(max_n) = self.params.max_n
+            && n_proportion(record.sequence()) > max_n
+        {
+            counts.too_many_n += 1;
+            return false;
+        }
+        if let Some(min_quality) = self.params.min_mean_quality
+            && mean_quality(record.quality_scores(), offset) < min_quality
+        {
+            counts.low_quality += 1;
+            return false;
+        }
+        true
+    }
+
+    // ASCII offset of the quality scores. Auto encoding
+    // is detected from the first reads of the file.
+    fn resolve_offset(&self, input: &Path) -> u8 {
+        let encoding = match self.params.encoding {
+            QScoreEncoding::Auto => detect_qscore_encoding(input, self.input_fmt),
+            encoding => encoding,
+        };
+        encoding
+            .offset()
+            .expect("Quality score encoding is not detected")
+    }
+
+    fn write_reports(&self, records: &[ReadFilterRecord]) {
+        let output = self
+            .output_dir
+            .join(FILTER_SUMMARY_FNAME)
+            .with_extension("csv");
+        let mut writer = csv::Writer::from_path(output).expect("Failed to create CSV writer");
+        records.iter().for_each(|rec| {
+            writer.serialize(rec).expect("Failed to write CSV record");
+        });
+        writer.flush().expect("Failed to flush CSV writer");
+
+        let inputs: Vec<&Path> = records.iter().map(|rec| rec.input.as_path()).collect();
+        let outputs: Vec<&Path> = records.iter().map(|rec| rec.output.as_path()).collect();
+        self.write_summary(&inputs, self.input_fmt, BEFORE_PREFIX);
+        self.write_summary(&outputs, &SeqReadFmt::Gzip, AFTER_PREFIX);
+    }
+
+    fn write_summary(&self, files: &[&Path], input_fmt: &SeqReadFmt, prefix: &str) {
+        let summaries: Vec<FastqSummary> = files
+            .par_iter()
+            .map(|file| {
+                let mut summary = FastqSummary::new(file, &self.params.encoding);
+                summary.summarize(input_fmt);
+                summary
+            })
+            .collect();
+        ReadSummaryWriter::new(self.output_dir, Some(prefix))
+            .write(&summaries)
+            .expect("Failed writing to file");
+    }
+
+    fn print_output_info(&self, records: &[ReadFilterRecord], kept_counts: usize) {
+        let read_counts: usize = records.iter().map(|rec| rec.read_count).sum();
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&records.len()));
+        log::info!("{:18}: {}", "Total reads", utils::fmt_num(&read_counts));
+        log::info!("{:18}: {}", "Kept reads", utils::fmt_num(&kept_counts));
+        log::info!(
+            "{:18}: {}",
+            "Removed reads",
+            utils::fmt_num(&(read_counts - kept_counts))
+        );
+        log::info!("{:18}: {}", "Output format", "FASTQ (gzip)");
+    }
+}
+
+/// Position of the first adapter match in a read.
+/// Partial adapters are matched at the 3' end of the read
+/// if they overlap at least `min_overlap` bases.
+pub fn find_adapters(sequence: &[u8], adapters: &[Vec<u8>], min_overlap: usize) -> Option<usize> {
+    adapters
+        .iter()
+        .filter_map(|adapter| find_adapter(sequence, adapter, min_overlap))
+        .min()
+}
+
+fn find_adapter(sequence: &[u8], adapter: &[u8], min_overlap: usize) -> Option<usize> {
+    let min_overlap = min_overlap.clamp(1, adapter.len().max(1));
+    (0..sequence.len())
+        .take_while(|start| sequence.len() - start >= min_overlap)
+        .find(|&start| {
+            let overlap = adapter.len().min(sequence.len() - start);
+            sequence[start..start + overlap].eq_ignore_ascii_case(&adapter[..overlap])
+        })
+}
+
+/// End position of a read after sliding-window quality trimming.
+/// Reads shorter than the window are evaluated as a single window.
+pub fn sliding_window_end(
+    qualities: &[u8],
+    offset: u8,
+    window_size: usize,
+    min_quality: f64,
+) -> usize {
+    let window_size = window_size.clamp(1, qualities.len().max(1));
+    qualities
+        .windows(window_size)
+        .position(|window| mean_quality(window, offset) < min_quality)
+        .unwrap_or(qualities.len())
+}
+
+fn mean_quality(qualities: &[u8], offset: u8) -> f64 {
+    if qualities.is_empty() {
+        return 0.0;
+    }
+    let sum: usize = qualities
+        .iter()
+        .map(|q| q.saturating_sub(offset) as usize)
+        .sum();
+    sum as f64 / qualities.len() as f64
+}
+
+fn n_proportion(sequence: &[u8]) -> f64 {
+    if sequence.is_empty() {
+        return 0.0;
+    }
+    let n_count = sequence
+        .iter()
+        .filter(|&&base| base.eq_ignore_ascii_case(&b'N'))
+        .count();
+    n_count as f64 / sequence.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempdir::TempDir;
+
+    #[test]
+    fn test_find_adapters() {
+        let adapters = vec![b"AGATCGGAAG".to_vec(), b"CTGTCTCTTA".to_vec()];
+        assert_eq!(Some(4), find_adapters(b"ACGTAGATCGGAAGTTT", &adapters, 5));
+        // Partial adapter at the 3' end
+        assert_eq!(Some(6), find_adapters(b"ACGTACctgtc", &adapters, 5));
+        assert_eq!(None, find_adapters(b"ACGTACGTACCTGT", &adapters, 5));
+        assert_eq!(None, find_adapters(b"ACGT", &[], 5));
+    }
+
+    #[test]
+    fn test_sliding_window_end() {
+        // Q40, Q40, Q40, Q40, Q10, Q2, Q2, Q2
+        let qualities = b"IIII+###";
+        assert_eq!(3, sliding_window_end(qualities, 33, 4, 20.0));
+        assert_eq!(8, sliding_window_end(qualities, 33, 4, 2.0));
+        assert_eq!(0, sliding_window_end(b"##", 33, 4, 20.0));
+        assert_eq!(0, sliding_window_end(b"", 33, 4, 20.0));
+    }
+
+    #[test]
+    fn test_filter_paired() {
+        let files = vec![
+            PathBuf::from("tests/files/paired/sample_R1.fastq"),
+            PathBuf::from("tests/files/paired/sample_R2.fastq"),
+        ];
+        let output = TempDir::new("temp").unwrap();
+        let params = ReadFilterParameters {
+            adapters: vec![b"TACGT".to_vec()],
+            min_length: Some(4),
+            ..Default::default()
+        };
+        let handle = ReadFilter::new(&files, &SeqReadFmt::Auto, output.path(), &params);
+        // The adapter cuts both mates of the first pair to three bases.
+        // The third R1 read has no mate.
+        assert_eq!(2, handle.filter());
+        let output_1 = output.path().join("sample_R1.fastq.gz");
+        let mut reader = Reader::new(open_reads(&output_1, &SeqReadFmt::Gzip));
+        let records: Vec<Record> = reader.records().map(|r| r.unwrap()).collect();
+        assert_eq!(1, records.len());
+        assert_eq!(b"ACGTACGA", records[0].sequence());
+        assert!(output.path().join("read-filter-summary.csv").exists());
+        assert!(
+            output
+                .path()
+                .join("before_default-read-summary.csv")
+                .exists()
+        );
+        assert!(
+            output
+                .path()
+                .join("after_default-read-summary.csv")
+                .exists()
+        );
+    }
+}
@@ -1,2 +1,68 @@
 //! A module for handling raw read sequences.
+pub mod convert;
+pub mod filter;
+pub mod subsample;
 pub mod summarize;
+
+use std::path::{Path, PathBuf};
+
+use crate::helper::files::check_unique_outputs;
+
+/// Output path of a FASTQ file named after the input file
+/// with the `.fastq.gz` extension.
+fn fastq_output_path(output_dir: &Path, input: &Path) -> PathBuf {
+    let file_name = input
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("Failed parsing file name");
+    output_dir.join(format!("{}.fastq.gz", read_file_stem(file_name)))
+}
+
+/// Check the outputs named after each input file by [`fastq_output_path`].
+fn check_unique_fastq_outputs(output_dir: &Path, inputs: &[PathBuf]) {
+    check_unique_outputs(inputs.iter().map(|input| {
+        (
+            fastq_output_path(output_dir, input),
+            input.display().to_string(),
+        )
+    }));
+}
+
+// File name without the FASTQ and compression extensions.
+fn read_file_stem(file_name: &str) -> &str {
+    let lower = file_name.to_ascii_lowercase();
+    let mut end = file_name.len();
+    for ext in [".gz", ".gzip"] {
+        if lower[..end].ends_with(ext) {
+            end -= ext.len();
+        }
+    }
+    for ext in [".fastq", ".fq"] {
+        if lower[..end].ends_with(ext) {
+            end -= ext.len();
+        }
+    }
+    &file_name[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_file_stem() {
+        assert_eq!("sample_R1", read_file_stem("sample_R1.fastq.gz"));
+        assert_eq!("sample", read_file_stem("sample.FQ"));
+        assert_eq!("sample.txt", read_file_stem("sample.txt"));
+    }
+
+    #[test]
+    #[should_panic(expected = "are both written to")]
+    fn test_check_unique_fastq_outputs() {
+        let inputs = [
+            PathBuf::from("a/sample_R1.fastq"),
+            PathBuf::from("a/sample_R1.fastq.gz"),
+        ];
+        check_unique_fastq_outputs(Path::new("output"), &inputs);
+    }
+}
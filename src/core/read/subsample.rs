@@ -0,0 +1,360 @@
+//! Subsample raw-read sequences.
+//!
+//! Support FASTQ and compressed FASTQ in gunzip format.
+//! Reads can be sampled by:
+//! 1. Fraction. Each read is kept with the given probability.
+//! 2. Count. Exactly the given number of reads is kept
+//!    using reservoir sampling of the read indices.
+//! 3. Normalize. All samples are subsampled to the read count
+//!    of the smallest sample.
+//!
+//! R1 and R2 files of paired-end reads are sampled together,
+//! so both mates of a pair are either kept or removed.
+//! Paired samples are counted by their R1 reads.
+//! Sampled reads keep their input order and are written as gzipped FASTQ.
+//! The random number generator is seeded for each sample
+//! from the given seed, so the results are reproducible.
+use std::path::{Path, PathBuf};
+
+use colored::Colorize;
+use noodles::fastq::io::Reader;
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::index};
+use rayon::prelude::*;
+use serde::Serialize;
+
+use super::{check_unique_fastq_outputs, fastq_output_path};
+use crate::{
+    helper::{finder::SeqReadFinder, types::SeqReadFmt, utils},
+    stats::fastq::{FastqSummaryMin, open_reads},
+    writer::fastq::FastqWriter,
+};
+
+const SUBSAMPLE_SUMMARY_FNAME: &str = "read-subsample-summary";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SubsampleMode {
+    /// Proportion of reads to keep
+    Fraction(f64),
+    /// Number of reads to keep
+    Count(usize),
+    /// Keep the read count of the smallest sample
+    Normalize,
+}
+
+// Reads to keep in a sample.
+enum Selection {
+    Fraction(f64),
+    /// Sorted 0-based read indices
+    Indices(Vec<usize>),
+}
+
+/// Read counts of a subsampled file.
+#[derive(Debug, Serialize)]
+pub struct ReadSubsampleRecord {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub read_count: usize,
+    pub sampled_count: usize,
+}
+
+pub struct ReadSubsample<'a> {
+    inputs: &'a [PathBuf],
+    input_fmt: &'a SeqReadFmt,
+    output_dir: &'a Path,
+    mode: &'a SubsampleMode,
+    seed: u64,
+}
+
+impl<'a> ReadSubsample<'a> {
+    pub fn new(
+        inputs: &'a [PathBuf],
+        input_fmt: &'a SeqReadFmt,
+        output_dir: &'a Path,
+        mode: &'a SubsampleMode,
+        seed: u64,
+    ) -> Self {
+        Self {
+            inputs,
+            input_fmt,
+            output_dir,
+            mode,
+            seed,
+        }
+    }
+
+    /// Subsample reads and write one gzipped FASTQ per input file.
+    /// Returns the number of sampled reads.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::core::read::subsample::{ReadSubsample, SubsampleMode};
+    /// use segul::helper::types::SeqReadFmt;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/paired/sample_R1.fastq")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let mode = SubsampleMode::Count(2);
+    /// let counts = ReadSubsample::new(&files, &SeqReadFmt::Auto, output.path(), &mode, 42)
+    ///     .subsample();
+    /// assert_eq!(counts, 2);
+    /// ```
+    pub fn subsample(&self) -> usize {
+        check_unique_fastq_outputs(self.output_dir, self.inputs);
+        let spin = utils::set_spinner();
+        spin.set_message("Subsampling reads...");
+        let (pairs, singles) = SeqReadFinder::find_pairs(self.inputs);
+        let mut samples: Vec<Vec<&Path>> = pairs
+            .iter()
+            .map(|pair| vec![pair.read_1.as_path(), pair.read_2.as_path()])
+            .chain(singles.iter().map(|file| vec![file.as_path()]))
+            .collect();
+        samples.sort();
+        let target = match self.mode {
+            SubsampleMode::Normalize => {
+                spin.set_message("Counting reads...");
+                Some(self.count_reads(&samples))
+            }
+            _ => None,
+        };
+        spin.set_message("Subsampling reads...");
+        let mut records: Vec<ReadSubsampleRecord> = samples
+            .par_iter()
+            .enumerate()
+            .flat_map(|(i, mates)| {
+                let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(i as u64));
+                let selection = match (self.mode, &target) {
+                    (SubsampleMode::Fraction(fraction), _) => Selection::Fraction(*fraction),
+                    (SubsampleMode::Count(count), _) => {
+                        Selection::Indices(self.reservoir_sample(mates[0], *count, &mut rng))
+                    }
+                    (SubsampleMode::Normalize, Some((counts, target))) => {
+                        let selected = index::sample(&mut rng, counts[i], *target);
+                        let mut indices = selected.into_vec();
+                        indices.sort_unstable();
+                        Selection::Indices(indices)
+                    }
+                    (SubsampleMode::Normalize, None) => unreachable!("Missing read counts"),
+                };
+                self.sample_reads(mates, &selection, &mut rng)
+            })
+            .collect();
+        records.sort_by(|a, b| a.input.cmp(&b.input));
+        self.write_summary(&records);
+        spin.finish_with_message("Finished subsampling reads!\n");
+        let sampled_counts = records.iter().map(|rec| rec.sampled_count).sum();
+        self.print_output_info(&records, sampled_counts, target.map(|(_, t)| t));
+        sampled_counts
+    }
+
+    // Read counts of each sample and the smallest count.
+    fn count_reads(&self, samples: &[Vec<&Path>]) -> (Vec<usize>, usize) {
+        let counts: Vec<usize> = samples
+            .par_iter()
+            .map(|mates| {
+                let mut summary = FastqSummaryMin::new(mates[0]);
+                summary.summarize(self.input_fmt);
+                summary.read_count
+            })
+            .collect();
+        let target = counts.iter().min().copied().unwrap_or(0);
+        (counts, target)
+    }
+
+    // Sample read indices with reservoir sampling.
+    // Returns sorted indices.
+    fn reservoir_sample(&self, file: &Path, count: usize, rng: &mut StdRng) -> Vec<usize> {
+        let mut reader = Reader::new(open_reads(file, self.input_fmt));
+        let mut reservoir: Vec<usize> = Vec::with_capacity(count);
+        reader
+            .records()
+            .map_while(Result::ok)
+            .enumerate()
+            .for_each(|(i, _)| {
+                if i < count {
+                    reservoir.push(i);
+                } else {
+                    let j = rng.random_range(0..=i);
+                    if j < count {
+                        reservoir[j] = i;
+                    }
+                }
+            });
+        if reservoir.len() < count {
+            log::warn!(
+                "{} {} has fewer reads than {}. Keeping all reads.",
+                "WARNING!".red(),
+                file.display(),
+                utils::fmt_num(&count)
+            );
+        }
+        reservoir.sort_unstable();
+        reservoir
+    }
+
+    // Read the mates in lockstep and write the selected reads.
+    fn sample_reads(
+        &self,
+        mates: &[&Path],
+        selection: &Selection,
+        rng: &mut StdRng,
+    ) -> Vec<ReadSubsampleRecord> {
+        let outputs: Vec<PathBuf> = mates
+            .iter()
+            .map(|file| fastq_output_path(self.output_dir, file))
+            .collect();
+        let mut writers: Vec<FastqWriter> = outputs
+            .iter()
+            .map(|output| FastqWriter::from_path(output).expect("Failed creating FASTQ file"))
+            .collect();
+        let mut readers: Vec<_> = mates
+            .iter()
+            .map(|file| Reader::new(open_reads(file, self.input_fmt)))
+            .collect();
+        let mut records: Vec<_> = readers.iter_mut().map(|reader| reader.records()).collect();
+        let mut read_count = 0;
+        let mut sampled_count = 0;
+        let mut next_index = 0;
+        loop {
+            let reads: Vec<_> = records
+                .iter_mut()
+                .map(|records| records.next().and_then(Result::ok))
+                .collect();
+            if reads.iter().any(Option::is_none) {
+                if reads.iter().any(Option::is_some) {
+                    log::warn!(
+                        "{} Mates of {} have different read counts. \
+                        Reads without mates are removed.",
+                        "WARNING!".red(),
+                        mates[0].display()
+                    );
+                }
+                break;
+            }
+            let is_selected = match selection {
+                Selection::Fraction(fraction) => rng.random::<f64>() < *fraction,
+                Selection::Indices(indices) => {
+                    let is_selected = indices.get(next_index) == Some(&read_count);
+                    if is_selected {
+                        next_index += 1;
+                    }
+                    is_selected
+                }
+            };
+            if is_selected {
+                reads
+                    .iter()
+                    .zip(writers.iter_mut())
+                    .for_each(|(read, writer)| {
+                        writer
+                            .write_record(read.as_ref().expect("Missing read"))
+                            .expect("Failed writing FASTQ record");
+                    });
+                sampled_count += 1;
+            }
+            read_count += 1;
+        }
+        writers
+            .into_iter()
+            .for_each(|writer| writer.finish().expect("Failed writing FASTQ file"));
+        mates
+            .iter()
+            .zip(outputs)
+            .map(|(input, output)| ReadSubsampleRecord {
+                input: input.to_path_buf(),
+                output,
+                read_count,
+                sampled_count,
+            })
+            .collect()
+    }
+
+    fn write_summary(&self, records: &[ReadSubsampleRecord]) {
+        let output = self
+            .output_dir
+            .join(SUBSAMPLE_SUMMARY_FNAME)
+            .with_extension("csv");
+        let mut writer = csv::Writer::from_path(output).expect("Failed to create CSV writer");
+        records.iter().for_each(|rec| {
+            writer.serialize(rec).expect("Failed to write CSV record");
+        });
+        writer.flush().expect("Failed to flush CSV writer");
+    }
+
+    fn print_output_info(
+        &self,
+        records: &[ReadSubsampleRecord],
+        sampled_counts: usize,
+        target: Option<usize>,
+    ) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Output dir", self.output_dir.display());
+        log::info!("{:18}: {}", "File counts", utils::fmt_num(&records.len()));
+        if let Some(target) = target {
+            log::info!("{:18}: {}", "Normalized count", utils::fmt_num(&target));
+        }
+        log::info!(
+            "{:18}: {}",
+            "Sampled reads",
+            utils::fmt_num(&sampled_counts)
+        );
+        log::info!("{:18}: {}", "Output format", "FASTQ (gzip)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use noodles::fastq::Record;
+    use tempdir::TempDir;
+
+    fn read_names(path: &Path) -> Vec<String> {
+        let mut reader = Reader::new(open_reads(path, &SeqReadFmt::Gzip));
+        reader
+            .records()
+            .map(|r: std::io::Result<Record>| r.unwrap().name().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_subsample_paired() {
+        let files = vec![
+            PathBuf::from("tests/files/raw/read_1.fastq"),
+            PathBuf::from("tests/files/raw/read_2.fastq"),
+        ];
+        let output = TempDir::new("temp").unwrap();
+        let mode = SubsampleMode::Count(1);
+        let handle = ReadSubsample::new(&files, &SeqReadFmt::Auto, output.path(), &mode, 7);
+        // One read from each mate
+        assert_eq!(2, handle.subsample());
+        let read_1 = read_names(&output.path().join("read_1.fastq.gz"));
+        let read_2 = read_names(&output.path().join("read_2.fastq.gz"));
+        assert_eq!(1, read_1.len());
+        assert_eq!(read_1, read_2);
+    }
+
+    #[test]
+    fn test_subsample_normalize() {
+        let files = vec![
+            PathBuf::from("tests/files/paired/sample_R1.fastq"),
+            PathBuf::from("tests/files/raw/read_1.fastq"),
+        ];
+        let output = TempDir::new("temp").unwrap();
+        let mode = SubsampleMode::Normalize;
+        let handle = ReadSubsample::new(&files, &SeqReadFmt::Auto, output.path(), &mode, 7);
+        // Both samples are subsampled to two reads.
+        assert_eq!(4, handle.subsample());
+        let sampled = read_names(&output.path().join("sample_R1.fastq.gz"));
+        assert_eq!(2, sampled.len());
+    }
+
+    #[test]
+    fn test_subsample_fraction() {
+        let files = vec![PathBuf::from("tests/files/paired/sample_R1.fastq")];
+        let output = TempDir::new("temp").unwrap();
+        let mode = SubsampleMode::Fraction(1.0);
+        let handle = ReadSubsample::new(&files, &SeqReadFmt::Auto, output.path(), &mode, 7);
+        assert_eq!(3, handle.subsample());
+    }
+}
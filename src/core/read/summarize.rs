@@ -1,6 +1,9 @@
 //! Generate summary statistics for raw-read sequences
 //!
 //! Support FASTQ and compressed FASTQ in gunzip format.
+//! R1 and R2 files of paired-end reads are detected by their file names.
+//! The mates are checked for matching read counts and read names
+//! in the same pass as the summary, and summarized per sample.
 
 use std::{
     path::{Path, PathBuf},
@@ -10,14 +13,16 @@ use std::{
 use colored::Colorize;
 
 use indicatif::ProgressBar;
+use noodles::fastq::Record;
 use rayon::prelude::*;
 
 use crate::{
     helper::{
-        types::{SeqReadFmt, SummaryMode},
-        utils::set_spinner,
+        finder::{ReadPair, SeqReadFinder},
+        types::{QScoreEncoding, SeqReadFmt, SummaryMode},
+        utils::{self, set_spinner},
     },
-    stats::fastq::{FastqMappedRead, FastqSummary, FastqSummaryMin},
+    stats::fastq::{FastqMappedRead, FastqPairCheck, FastqSummary, FastqSummaryMin},
     writer::read::{ReadPosSummaryWriter, ReadSummaryWriter},
 };
 
@@ -29,7 +34,12 @@ use crate::{
 /// 1. Minimal: read count only
 /// 2. Default: essential statistics, such as read counts, base counts, gc, at, and n content, and qscore statistics
 /// 3. Complete: all the essential plus summary
-///     statistics per position in read for each file.
+///    statistics per position in read, the duplicate rate,
+///    overrepresented sequences, and the distributions
+///    of read length and per-read GC content for each file.
+///
+/// Paired-end reads are also summarized per sample.
+/// In Minimal mode, only the mate consistency is reported.
 pub struct GenomicReadSummary<'a> {
     /// Input path.
     pub inputs: &'a mut [PathBuf],
@@ -41,6 +51,10 @@ pub struct GenomicReadSummary<'a> {
     pub input_fmt: &'a SeqReadFmt,
     /// Summary statistic mode
     pub mode: &'a SummaryMode,
+    /// Quality score encoding.
+    /// In Auto, it will be detected for each file
+    /// from the first reads.
+    pub encoding: &'a QScoreEncoding,
     /// Output path. No extension required
     pub output: &'a Path,
     /// Output file prefix
@@ -53,6 +67,7 @@ impl<'a> GenomicReadSummary<'a> {
         inputs: &'a mut [PathBuf],
         input_fmt: &'a SeqReadFmt,
         mode: &'a SummaryMode,
+        encoding: &'a QScoreEncoding,
         output: &'a Path,
         prefix: Option<&'a str>,
     ) -> Self {
@@ -60,6 +75,7 @@ impl<'a> GenomicReadSummary<'a> {
             inputs,
             input_fmt,
             mode,
+            encoding,
             output,
             prefix,
         }
@@ -70,12 +86,13 @@ impl<'a> GenomicReadSummary<'a> {
     /// * `path` - A mutable slice of PathBuf that holds the fastq files.
     /// * `input_fmt` - The fastq input format.
     /// * `mode` - The summary mode.
+    /// * `encoding` - The quality score encoding.
     /// * `output` - The output path.
     /// # Example
     /// ```rust
     /// use std::path::{Path, PathBuf};
     /// use segul::core::read::summarize::GenomicReadSummary;
-    /// use segul::helper::types::{SeqReadFmt, SummaryMode};
+    /// use segul::helper::types::{QScoreEncoding, SeqReadFmt, SummaryMode};
     /// use tempdir::TempDir;
     ///
     /// let mut files = vec![
@@ -88,6 +105,7 @@ impl<'a> GenomicReadSummary<'a> {
     ///     &mut files,
     ///     &SeqReadFmt::Auto,
     ///     &SummaryMode::Default,
+    ///     &QScoreEncoding::Auto,
     ///     Path::new(output.path()),
     ///     None,
     /// );
@@ -96,72 +114,166 @@ impl<'a> GenomicReadSummary<'a> {
     pub fn summarize(&self) {
         let spin = set_spinner();
         spin.set_message("Calculating summary of fastq files");
-        match self.mode {
+        let (pairs, singles) = SeqReadFinder::find_pairs(self.inputs);
+        let writer = ReadSummaryWriter::new(self.output, self.prefix);
+        let pair_checks = match self.mode {
             SummaryMode::Minimal => {
-                let mut records = self.par_summarize_minimal();
+                let (mut records, pair_checks) = self.par_summarize_minimal(&pairs, &singles);
                 self.write_record_min(&spin, &mut records);
+                if !pair_checks.is_empty() {
+                    writer
+                        .write_paired_check_only(&pair_checks)
+                        .expect("Failed writing to file");
+                }
+                pair_checks
             }
             SummaryMode::Default => {
-                let mut records = self.par_summarize_default();
+                let (mut records, pair_checks) = self.par_summarize_default(&pairs, &singles);
                 self.write_record_default(&spin, &mut records);
+                self.write_paired(&writer, &pair_checks, &records);
+                pair_checks
             }
             SummaryMode::Complete => {
-                let all_records = self.par_summarize_complete();
+                let (all_records, pair_checks) = self.par_summarize_complete(&pairs, &singles);
                 let (mut records, read_records): (Vec<FastqSummary>, Vec<FastqMappedRead>) =
                     all_records.into_iter().unzip();
                 self.write_record_complete(&spin, &mut records, &read_records);
+                self.write_paired(&writer, &pair_checks, &records);
+                pair_checks
             }
-        }
+        };
         spin.finish_with_message("Finished processing fastq files\n");
-        self.print_output_info();
+        self.print_output_info(&pair_checks);
+    }
+
+    // Summarize both mates of each pair in the same pass as the mate check.
+    // Returns the summaries of all mates and the checks sorted by R1.
+    fn par_summarize_pairs<T, N, U>(
+        &self,
+        pairs: &[ReadPair],
+        new_summary: N,
+        update: U,
+    ) -> (Vec<T>, Vec<FastqPairCheck>)
+    where
+        T: Send,
+        N: Fn(&Path) -> T + Sync,
+        U: Fn(&mut T, &Record) + Sync,
+    {
+        let (mut checks, summaries): (Vec<FastqPairCheck>, Vec<[T; 2]>) = pairs
+            .par_iter()
+            .map(|pair| {
+                let mut summary_1 = new_summary(&pair.read_1);
+                let mut summary_2 = new_summary(&pair.read_2);
+                let mut check = FastqPairCheck::new(pair);
+                check.check_with(
+                    self.input_fmt,
+                    |record| update(&mut summary_1, record),
+                    |record| update(&mut summary_2, record),
+                );
+                (check, [summary_1, summary_2])
+            })
+            .unzip();
+        checks.sort_by(|a, b| a.read_1.cmp(&b.read_1));
+        (summaries.into_iter().flatten().collect(), checks)
+    }
+
+    fn write_paired(
+        &self,
+        writer: &ReadSummaryWriter,
+        pair_checks: &[FastqPairCheck],
+        records: &[FastqSummary],
+    ) {
+        if pair_checks.is_empty() {
+            return;
+        }
+        writer
+            .write_paired(pair_checks, records)
+            .expect("Failed writing to file");
     }
 
-    fn par_summarize_default(&self) -> Vec<FastqSummary> {
+    fn par_summarize_default(
+        &self,
+        pairs: &[ReadPair],
+        singles: &[PathBuf],
+    ) -> (Vec<FastqSummary>, Vec<FastqPairCheck>) {
+        let (mut records, pair_checks) = self.par_summarize_pairs(
+            pairs,
+            |path| {
+                let mut summary = FastqSummary::new(path, self.encoding);
+                summary.detect_encoding(self.input_fmt);
+                summary
+            },
+            |summary, record| summary.summarize_record(record),
+        );
         let (sender, receiver) = channel();
 
-        self.inputs.par_iter().for_each_with(sender, |s, p| {
+        singles.par_iter().for_each_with(sender, |s, p| {
             let record = self.summarize_default(p);
             s.send(record)
                 .expect("Failed parallel processing fastq files");
         });
 
-        receiver.iter().collect()
+        records.extend(receiver.iter());
+        (records, pair_checks)
     }
 
     fn summarize_default(&self, path: &Path) -> FastqSummary {
-        let mut summary = FastqSummary::new(path);
+        let mut summary = FastqSummary::new(path, self.encoding);
         summary.summarize(self.input_fmt);
         summary
     }
 
-    fn par_summarize_complete(&self) -> Vec<(FastqSummary, FastqMappedRead)> {
+    fn par_summarize_complete(
+        &self,
+        pairs: &[ReadPair],
+        singles: &[PathBuf],
+    ) -> (Vec<(FastqSummary, FastqMappedRead)>, Vec<FastqPairCheck>) {
+        let (mut records, pair_checks) = self.par_summarize_pairs(
+            pairs,
+            |path| {
+                let mut summary = FastqSummary::new(path, self.encoding);
+                summary.detect_encoding(self.input_fmt);
+                (summary, FastqMappedRead::new(path))
+            },
+            |(summary, mapped), record| summary.summarize_mapped_record(mapped, record),
+        );
         let (sender, receiver) = channel();
 
-        self.inputs.par_iter().for_each_with(sender, |s, p| {
+        singles.par_iter().for_each_with(sender, |s, p| {
             let record = self.summarize_complete(p);
             s.send(record)
                 .expect("Failed parallel processing fastq files");
         });
 
-        receiver.iter().collect()
+        records.extend(receiver.iter());
+        (records, pair_checks)
     }
 
     fn summarize_complete(&self, path: &Path) -> (FastqSummary, FastqMappedRead) {
-        let mut summary = FastqSummary::new(path);
+        let mut summary = FastqSummary::new(path, self.encoding);
         let mapped_records = summary.summarize_map(self.input_fmt);
         (summary, mapped_records)
     }
 
-    fn par_summarize_minimal(&self) -> Vec<FastqSummaryMin> {
+    fn par_summarize_minimal(
+        &self,
+        pairs: &[ReadPair],
+        singles: &[PathBuf],
+    ) -> (Vec<FastqSummaryMin>, Vec<FastqPairCheck>) {
+        let (mut records, pair_checks) =
+            self.par_summarize_pairs(pairs, FastqSummaryMin::new, |summary, _| {
+                summary.read_count += 1
+            });
         let (sender, receiver) = channel();
 
-        self.inputs.par_iter().for_each_with(sender, |s, p| {
+        singles.par_iter().for_each_with(sender, |s, p| {
             let summary = self.summarize_minimal(p, self.input_fmt);
             s.send(summary)
                 .expect("Failed parallel processing fastq files");
         });
 
-        receiver.iter().collect()
+        records.extend(receiver.iter());
+        (records, pair_checks)
     }
 
     fn summarize_minimal(&self, p: &Path, input_fmt: &SeqReadFmt) -> FastqSummaryMin {
@@ -197,6 +309,19 @@ impl<'a> GenomicReadSummary<'a> {
         let writer = ReadSummaryWriter::new(self.output, self.prefix);
         writer.write(records).expect("Failed writing to file");
 
+        writer
+            .write_duplication(read_records)
+            .expect("Failed writing to file");
+        writer
+            .write_overrepresented(read_records)
+            .expect("Failed writing to file");
+        writer
+            .write_length_distribution(read_records)
+            .expect("Failed writing to file");
+        writer
+            .write_gc_distribution(read_records)
+            .expect("Failed writing to file");
+
         spin.set_message("Writing records\n");
         let pos_writer = ReadPosSummaryWriter::new(self.output, self.prefix);
         pos_writer
@@ -204,9 +329,31 @@ impl<'a> GenomicReadSummary<'a> {
             .expect("Failed writing to file");
     }
 
-    fn print_output_info(&self) {
+    fn print_output_info(&self, pair_checks: &[FastqPairCheck]) {
         log::info!("{}", "Output".yellow());
         log::info!("{:18}: {}", "Dir", self.output.display());
+        if pair_checks.is_empty() {
+            return;
+        }
+        log::info!(
+            "{:18}: {}",
+            "Paired samples",
+            utils::fmt_num(&pair_checks.len())
+        );
+        pair_checks
+            .iter()
+            .filter(|pair| !pair.is_consistent())
+            .for_each(|pair| {
+                log::warn!(
+                    "{} Sample {} has inconsistent mates: \
+                    {} reads in R1, {} reads in R2, {} mismatched read names",
+                    "WARNING!".red(),
+                    pair.sample,
+                    utils::fmt_num(&pair.read_1_count),
+                    utils::fmt_num(&pair.read_2_count),
+                    utils::fmt_num(&pair.name_mismatches)
+                );
+            });
     }
 }
 
@@ -217,7 +364,8 @@ mod test {
     use tempdir::TempDir;
 
     use crate::core::read::summarize::GenomicReadSummary;
-    use crate::helper::types::{SeqReadFmt, SummaryMode};
+    use crate::helper::finder::SeqReadFinder;
+    use crate::helper::types::{QScoreEncoding, SeqReadFmt, SummaryMode};
     use crate::stats::fastq::{FastqMappedRead, FastqSummary};
 
     #[test]
@@ -231,6 +379,7 @@ mod test {
             &mut files,
             &SeqReadFmt::Auto,
             &SummaryMode::Default,
+            &QScoreEncoding::Auto,
             output.path(),
             None,
         );
@@ -238,6 +387,79 @@ mod test {
         assert!(output.path().exists());
     }
 
+    #[test]
+    fn test_summarize_complete() {
+        let mut files = vec![PathBuf::from("tests/files/raw/read_1.fastq")];
+        let output = TempDir::new("tempt").unwrap();
+        let handler = GenomicReadSummary::new(
+            &mut files,
+            &SeqReadFmt::Auto,
+            &SummaryMode::Complete,
+            &QScoreEncoding::Auto,
+            output.path(),
+            None,
+        );
+        handler.summarize();
+        let duplication =
+            std::fs::read_to_string(output.path().join("read-duplication.csv")).unwrap();
+        assert!(duplication.lines().nth(1).unwrap().ends_with(",2,2,2,0"));
+        assert!(output.path().join("overrepresented-sequences.csv").exists());
+        let lengths =
+            std::fs::read_to_string(output.path().join("read-length-distribution.csv")).unwrap();
+        assert!(lengths.lines().nth(1).unwrap().ends_with(",36,2"));
+        assert!(output.path().join("read-gc-distribution.csv").exists());
+    }
+
+    #[test]
+    fn test_summarize_paired() {
+        let mut files = vec![
+            PathBuf::from("tests/files/paired/sample_R1.fastq"),
+            PathBuf::from("tests/files/paired/sample_R2.fastq"),
+        ];
+        let output = TempDir::new("tempt").unwrap();
+        let handler = GenomicReadSummary::new(
+            &mut files,
+            &SeqReadFmt::Auto,
+            &SummaryMode::Default,
+            &QScoreEncoding::Auto,
+            output.path(),
+            None,
+        );
+        handler.summarize();
+        let summary =
+            std::fs::read_to_string(output.path().join("paired-read-summary.csv")).unwrap();
+        let row = summary.lines().nth(1).unwrap();
+        assert!(row.starts_with("sample,"));
+        assert!(row.contains(",3,2,false,1,2,5,40,"));
+    }
+
+    #[test]
+    fn test_summarize_paired_empty() {
+        let input = TempDir::new("input").unwrap();
+        let mut files = vec![
+            input.path().join("empty_R1.fastq"),
+            input.path().join("empty_R2.fastq"),
+        ];
+        files.iter().for_each(|f| {
+            std::fs::File::create(f).unwrap();
+        });
+        let output = TempDir::new("tempt").unwrap();
+        let handler = GenomicReadSummary::new(
+            &mut files,
+            &SeqReadFmt::Auto,
+            &SummaryMode::Default,
+            &QScoreEncoding::Auto,
+            output.path(),
+            None,
+        );
+        handler.summarize();
+        let summary =
+            std::fs::read_to_string(output.path().join("paired-read-summary.csv")).unwrap();
+        let row = summary.lines().nth(1).unwrap();
+        assert!(!row.contains("NaN"));
+        assert!(row.ends_with(",0,0,0,0,0,0,0,0"));
+    }
+
     #[test]
     fn test_read_count_only() {
         let mut files = vec![
@@ -249,16 +471,27 @@ mod test {
             &mut files,
             &SeqReadFmt::Auto,
             &SummaryMode::Minimal,
+            &QScoreEncoding::Auto,
             output.path(),
             None,
         );
-        let records = handler.par_summarize_complete();
-        let (_, pos): (Vec<FastqSummary>, Vec<FastqMappedRead>) = records.into_iter().unzip();
+        let (pairs, singles) = SeqReadFinder::find_pairs(handler.inputs);
+        let (records, checks) = handler.par_summarize_complete(&pairs, &singles);
+        assert_eq!(1, checks.len());
+        let (summaries, pos): (Vec<FastqSummary>, Vec<FastqMappedRead>) =
+            records.into_iter().unzip();
         pos.iter().for_each(|p| {
             assert_eq!(p.reads.len(), 36);
             assert_eq!(p.qscores.len(), 36);
         });
 
         assert_eq!(pos.len(), 2);
+        // Mates summarized with the pair check match single-file summaries.
+        summaries.iter().for_each(|summary| {
+            let single = handler.summarize_default(&summary.path);
+            assert_eq!(single.reads, summary.reads);
+            assert_eq!(single.qscores, summary.qscores);
+            assert_eq!(single.encoding, summary.encoding);
+        });
     }
 }
@@ -0,0 +1,473 @@
+//! Generate consensus sequences from alignments.
+//!
+//! The consensus can be built for a whole alignment or for groups of sequences
+//! within the alignment, for example, to merge multiple individuals per species
+//! before species-tree inference. Groups are defined by a regular expression
+//! or by an ID-to-group mapping file.
+//!
+//! Each site is resolved using either the majority rule or a frequency threshold:
+//! - Majority rule: use the most frequent character.
+//!   Ties are written as IUPAC ambiguity codes.
+//! - Threshold: add the most frequent characters until their combined frequency
+//!   reaches the threshold. Multiple characters are written as IUPAC ambiguity codes.
+//!
+//! Ambiguous input characters are split equally among the bases they represent.
+//! Missing data (`?` and `N`) are never counted. For amino acid sequences,
+//! any ambiguity is written as `X`.
+use std::path::{Path, PathBuf};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+use indexmap::IndexMap;
+use rayon::prelude::*;
+use regex::Regex;
+
+use crate::{
+    core::OutputPrint,
+    helper::{
+        alphabet, files,
+        sequence::SeqParser,
+        types::{DataType, Header, InputFmt, OutputFmt, SeqMatrix},
+        utils,
+    },
+    writer::sequences::SeqWriter,
+};
+
+const GAP: u8 = b'-';
+const MISSING: u8 = b'?';
+const AMBIGUOUS_AA: u8 = b'X';
+const EPSILON: f64 = 1e-9;
+
+/// Grouping options for generating consensus sequences.
+pub enum ConsensusGrouping {
+    /// Generate one consensus sequence per alignment.
+    /// The sequence ID is the alignment file name.
+    Alignment,
+    /// Group sequences using a regular expression.
+    /// The group name is the first capture group or
+    /// the whole match if the regex has no capture group.
+    Regex(String),
+    /// Group sequences using a list of ID and group name pairs.
+    Mapping(Vec<(String, String)>),
+}
+
+/// Options to handle gaps when generating consensus sequences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GapHandling {
+    /// Ignore gaps. A site is written as a gap
+    /// only if it contains no other characters.
+    Ignore,
+    /// Count gaps as a character state.
+    Include,
+}
+
+impl std::fmt::Display for GapHandling {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Ignore => write!(f, "ignore"),
+            Self::Include => write!(f, "include"),
+        }
+    }
+}
+
+impl std::str::FromStr for GapHandling {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(Self::Ignore),
+            "include" => Ok(Self::Include),
+            _ => Err(format!("{} is not a valid gap handling option", s)),
+        }
+    }
+}
+
+pub struct ConsensusParameters {
+    /// Minimum combined frequency of the characters in a site.
+    /// Use the majority rule if `None`.
+    pub threshold: Option<f64>,
+    /// Gap handling option.
+    pub gap: GapHandling,
+    /// Grouping option.
+    pub grouping: ConsensusGrouping,
+}
+
+impl OutputPrint for SequenceConsensus<'_> {}
+
+pub struct SequenceConsensus<'a> {
+    /// Input format.
+    input_fmt: &'a InputFmt,
+    /// Data type.
+    datatype: &'a DataType,
+    /// Output directory.
+    output_dir: &'a Path,
+    /// Output format.
+    output_fmt: &'a OutputFmt,
+    /// Consensus parameters.
+    params: &'a ConsensusParameters,
+}
+
+impl<'a> SequenceConsensus<'a> {
+    pub fn new(
+        input_fmt: &'a InputFmt,
+        datatype: &'a DataType,
+        output_dir: &'a Path,
+        output_fmt: &'a OutputFmt,
+        params: &'a ConsensusParameters,
+    ) -> Self {
+        Self {
+            input_fmt,
+            datatype,
+            output_dir,
+            output_fmt,
+            params,
+        }
+    }
+
+    /// Generate consensus sequences for each alignment.
+    /// # Example
+    /// ```
+    /// use std::path::{Path, PathBuf};
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::{DataType, InputFmt, OutputFmt};
+    /// use segul::core::sequence::consensus::{
+    ///     ConsensusGrouping, ConsensusParameters, GapHandling, SequenceConsensus
+    /// };
+    ///
+    /// let files = vec![PathBuf::from("tests/files/consensus/locus_1.fas")];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let params = ConsensusParameters {
+    ///     threshold: None,
+    ///     gap: GapHandling::Ignore,
+    ///     grouping: ConsensusGrouping::Regex(String::from("^([A-Za-z]+_[a-z]+)")),
+    /// };
+    /// let handle = SequenceConsensus::new(
+    ///     &InputFmt::Fasta, &DataType::Dna, output.path(), &OutputFmt::Fasta, &params
+    /// );
+    /// handle.generate(&files);
+    /// ```
+    pub fn generate(&self, files: &[PathBuf]) {
+        let spin = utils::set_spinner();
+        spin.set_message("Generating consensus sequences...");
+        let regex = match &self.params.grouping {
+            ConsensusGrouping::Regex(re) => Some(Regex::new(re).expect("Failed parsing regex")),
+            _ => None,
+        };
+        let mapping: HashMap<&str, &str> = match &self.params.grouping {
+            ConsensusGrouping::Mapping(names) => names
+                .iter()
+                .map(|(id, group)| (id.as_str(), group.as_str()))
+                .collect(),
+            _ => HashMap::new(),
+        };
+        files.par_iter().for_each(|file| {
+            let (matrix, _) = SeqParser::new(file, self.datatype).get_alignment(self.input_fmt);
+            let groups = self.group_sequences(file, &matrix, regex.as_ref(), &mapping);
+            let consensus = self.build_consensus(&groups);
+            self.write_output(&consensus, file);
+        });
+        spin.finish_with_message("Finished generating consensus sequences!\n");
+        self.print_output_info(files.len());
+    }
+
+    fn group_sequences<'m>(
+        &self,
+        file: &Path,
+        matrix: &'m SeqMatrix,
+        regex: Option<&Regex>,
+        mapping: &HashMap<&str, &str>,
+    ) -> IndexMap<String, Vec<&'m str>> {
+        let mut groups: IndexMap<String, Vec<&str>> = IndexMap::new();
+        matrix.iter().for_each(|(id, seq)| {
+            let group = match &self.params.grouping {
+                ConsensusGrouping::Alignment => file
+                    .file_stem()
+                    .expect("Failed parsing file name")
+                    .to_string_lossy()
+                    .to_string(),
+                ConsensusGrouping::Regex(_) => {
+                    let re = regex.expect("Missing regex");
+                    self.match_group(re, id)
+                }
+                ConsensusGrouping::Mapping(_) => mapping
+                    .get(id.as_str())
+                    .map(|group| group.to_string())
+                    .unwrap_or_else(|| id.to_string()),
+            };
+            groups.entry(group).or_default().push(seq.as_str());
+        });
+        groups
+    }
+
+    // Use the first capture group if available,
+    // otherwise use the whole match.
+    // IDs that do not match the regex are kept as is.
+    fn match_group(&self, re: &Regex, id: &str) -> String {
+        match re.captures(id) {
+            Some(caps) => caps
+                .get(1)
+                .or_else(|| caps.get(0))
+                .map(|m| m.as_str().to_string())
+                .unwrap_or_else(|| id.to_string()),
+            None => id.to_string(),
+        }
+    }
+
+    fn build_consensus(&self, groups: &IndexMap<String, Vec<&str>>) -> SeqMatrix {
+        groups
+            .iter()
+            .map(|(name, seqs)| (name.to_string(), self.consensus_sequence(seqs)))
+            .collect()
+    }
+
+    fn consensus_sequence(&self, seqs: &[&str]) -> String {
+        let nchar = seqs.iter().map(|s| s.len()).max().unwrap_or(0);
+        let mut column: Vec<u8> = Vec::with_capacity(seqs.len());
+        let consensus: Vec<u8> = (0..nchar)
+            .map(|idx| {
+                column.clear();
+                seqs.iter()
+                    .filter_map(|s| s.as_bytes().get(idx))
+                    .for_each(|ch| column.push(*ch));
+                self.consensus_site(&column)
+            })
+            .collect();
+        String::from_utf8(consensus).expect("Failed building consensus sequence")
+    }
+
+    fn consensus_site(&self, column: &[u8]) -> u8 {
+        let counts = match self.datatype {
+            DataType::Dna => self.count_dna(column),
+            _ => self.count_chars(column),
+        };
+        let states = self.select_states(&counts);
+        self.resolve_states(&states, column)
+    }
+
+    fn count_dna(&self, column: &[u8]) -> Vec<(u8, f64)> {
+        let mut weights = [0.0; 4];
+        let mut gaps = 0.0;
+        column.iter().for_each(|&ch| {
+            if ch == GAP {
+                gaps += 1.0;
+                return;
+            }
+            let mask = alphabet::dna_to_mask(ch);
+            // N is treated as missing data
+            if mask == 0 || mask == 0x0F {
+                return;
+            }
+            let weight = 1.0 / mask.count_ones() as f64;
+            (0..4)
+                .filter(|bit| mask & (1 << bit) != 0)
+                .for_each(|bit| weights[bit] += weight);
+        });
+        let mut counts: Vec<(u8, f64)> = b"ACGT"
+            .iter()
+            .zip(weights.iter())
+            .filter(|(_, w)| **w > 0.0)
+            .map(|(ch, w)| (*ch, *w))
+            .collect();
+        if self.params.gap == GapHandling::Include && gaps > 0.0 {
+            counts.push((GAP, gaps));
+        }
+        counts
+    }
+
+    fn count_chars(&self, column: &[u8]) -> Vec<(u8, f64)> {
+        let mut counts: IndexMap<u8, f64> = IndexMap::new();
+        column.iter().for_each(|&ch| {
+            let ch = ch.to_ascii_uppercase();
+            if ch == GAP && self.params.gap == GapHandling::Ignore {
+                return;
+            }
+            if ch != GAP && b"XBZJU?.~*".contains(&ch) {
+                return;
+            }
+            *counts.entry(ch).or_insert(0.0) += 1.0;
+        });
+        counts.into_iter().collect()
+    }
+
+    fn select_states(&self, counts: &[(u8, f64)]) -> Vec<u8> {
+        if counts.is_empty() {
+            return Vec::new();
+        }
+        let mut sorted = counts.to_vec();
+        sorted.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let total: f64 = sorted.iter().map(|(_, w)| w).sum();
+        let min_weight = match self.params.threshold {
+            Some(threshold) => {
+                let mut cumulative = 0.0;
+                let mut last = sorted[0].1;
+                for (_, weight) in sorted.iter() {
+                    cumulative += weight;
+                    last = *weight;
+                    if cumulative / total >= threshold - EPSILON {
+                        break;
+                    }
+                }
+                last
+            }
+            None => sorted[0].1,
+        };
+        // Include all states tied with the last selected state.
+        sorted
+            .iter()
+            .filter(|(_, w)| *w >= min_weight - EPSILON)
+            .map(|(ch, _)| *ch)
+            .collect()
+    }
+
+    fn resolve_states(&self, states: &[u8], column: &[u8]) -> u8 {
+        let residues: Vec<u8> = states.iter().filter(|&&ch| ch != GAP).copied().collect();
+        if residues.is_empty() {
+            return if states.contains(&GAP) || column.contains(&GAP) {
+                GAP
+            } else {
+                MISSING
+            };
+        }
+        match self.datatype {
+            DataType::Dna => {
+                let mask = residues
+                    .iter()
+                    .fold(0, |acc, &ch| acc | alphabet::dna_to_mask(ch));
+                alphabet::mask_to_iupac(mask)
+            }
+            _ => {
+                if residues.len() == 1 {
+                    residues[0]
+                } else {
+                    AMBIGUOUS_AA
+                }
+            }
+        }
+    }
+
+    fn write_output(&self, matrix: &SeqMatrix, file: &Path) {
+        let output_path = files::create_output_fname(self.output_dir, file, self.output_fmt);
+        let mut header = Header::new();
+        header.from_seq_matrix(matrix, true);
+        let mut writer = SeqWriter::new(&output_path, matrix, &header);
+        writer
+            .write_sequence(self.output_fmt)
+            .expect("Failed writing output sequence");
+    }
+
+    fn print_output_info(&self, file_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Directory", self.output_dir.display());
+        self.print_output_fmt(self.output_fmt);
+        log::info!("{:18}: {}", "Total files", utils::fmt_num(&file_counts));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    macro_rules! input {
+        ($handle: ident, $threshold: expr_2021, $gap: expr_2021, $grouping: expr_2021) => {
+            let params = ConsensusParameters {
+                threshold: $threshold,
+                gap: $gap,
+                grouping: $grouping,
+            };
+            let $handle = SequenceConsensus::new(
+                &InputFmt::Fasta,
+                &DataType::Dna,
+                Path::new("."),
+                &OutputFmt::Fasta,
+                &params,
+            );
+        };
+    }
+
+    #[test]
+    fn test_majority_rule_tie() {
+        input!(
+            handle,
+            None,
+            GapHandling::Ignore,
+            ConsensusGrouping::Alignment
+        );
+        assert_eq!(b'A', handle.consensus_site(b"AAG"));
+        assert_eq!(b'R', handle.consensus_site(b"AAGG"));
+        assert_eq!(b'A', handle.consensus_site(b"AR?N"));
+        assert_eq!(b'A', handle.consensus_site(b"A---"));
+        assert_eq!(b'-', handle.consensus_site(b"-?-"));
+        assert_eq!(b'?', handle.consensus_site(b"??N"));
+    }
+
+    #[test]
+    fn test_threshold_consensus() {
+        input!(
+            handle,
+            Some(0.75),
+            GapHandling::Ignore,
+            ConsensusGrouping::Alignment
+        );
+        assert_eq!(b'A', handle.consensus_site(b"AAAG"));
+        assert_eq!(b'R', handle.consensus_site(b"AAAGGT"));
+        // Ties with the last selected base are included
+        assert_eq!(b'D', handle.consensus_site(b"AAGT"));
+    }
+
+    #[test]
+    fn test_include_gaps() {
+        input!(
+            handle,
+            None,
+            GapHandling::Include,
+            ConsensusGrouping::Alignment
+        );
+        assert_eq!(b'-', handle.consensus_site(b"A---"));
+        assert_eq!(b'A', handle.consensus_site(b"AA--"));
+    }
+
+    #[test]
+    fn test_group_by_regex() {
+        let re = String::from("^([A-Za-z]+_[a-z]+)");
+        input!(
+            handle,
+            None,
+            GapHandling::Ignore,
+            ConsensusGrouping::Regex(re.clone())
+        );
+        let file = Path::new("tests/files/consensus/locus_1.fas");
+        let (matrix, _) = SeqParser::new(file, &DataType::Dna).parse(&InputFmt::Fasta);
+        let re = Regex::new(&re).unwrap();
+        let groups = handle.group_sequences(file, &matrix, Some(&re), &HashMap::new());
+        let consensus = handle.build_consensus(&groups);
+        assert_eq!(2, consensus.len());
+        assert_eq!(
+            Some(&String::from("ATGCRTACGT")),
+            consensus.get("Genus_alpha")
+        );
+        assert_eq!(
+            Some(&String::from("TTGCATACG-")),
+            consensus.get("Genus_beta")
+        );
+    }
+
+    #[test]
+    fn test_group_by_mapping() {
+        let names = vec![(String::from("Genus_alpha_1"), String::from("alpha"))];
+        input!(
+            handle,
+            None,
+            GapHandling::Ignore,
+            ConsensusGrouping::Mapping(names)
+        );
+        let file = Path::new("tests/files/consensus/locus_1.fas");
+        let (matrix, _) = SeqParser::new(file, &DataType::Dna).parse(&InputFmt::Fasta);
+        let mut mapping = HashMap::new();
+        mapping.insert("Genus_alpha_1", "alpha");
+        let groups = handle.group_sequences(file, &matrix, None, &mapping);
+        assert_eq!(4, groups.len());
+        assert!(groups.contains_key("alpha"));
+        assert!(groups.contains_key("Genus_alpha_2"));
+    }
+}
@@ -1,8 +1,11 @@
 //! Sequence handler module.
 pub mod addition;
+pub mod consensus;
 pub mod extract;
 pub mod filter;
 pub mod id;
+pub mod outlier;
 pub mod remove;
 pub mod rename;
+pub mod select;
 pub mod translate;
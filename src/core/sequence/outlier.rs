@@ -0,0 +1,429 @@
+//! Detect outlier sequences within alignments.
+//!
+//! For each locus, we compute the mean pairwise distance of every sequence
+//! to the other sequences in the alignment. A sequence is flagged as an outlier
+//! when its mean distance is extreme relative to the distribution of mean
+//! distances in the same locus. This is similar to the TreeShrink approach,
+//! but it works directly on the alignment without requiring gene trees.
+//! Misassembled or contaminated sequences usually stand out this way.
+//!
+//! Two rules are available:
+//! - Modified z-score: flag a sequence if its mean distance is more than
+//!   the given number of robust standard deviations above the locus median.
+//!   The spread is estimated from the median absolute deviation (MAD),
+//!   so a single extreme sequence does not inflate it. Unlike the classic
+//!   z-score, whose largest possible value in a locus of n sequences
+//!   is (n - 1) / sqrt(n), the modified z-score can flag outliers
+//!   in small loci.
+//! - Interquartile range (IQR): flag a sequence if its mean distance
+//!   is higher than Q3 + multiplier * IQR.
+//!
+//! Sequences without any site comparable to the other sequences,
+//! e.g. sequences that only contain gaps or missing data, have no mean
+//! distance. They are not tested, but listed in the summary
+//! as sequences with no comparable sites.
+//!
+//! Flagged sequences can be removed using the `SequenceRemoval` module.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::sequence::remove::{SeqRemovalParameters, SequenceRemoval},
+    helper::{
+        sequence::SeqParser,
+        types::{DataType, InputFmt, OutputFmt},
+        utils,
+    },
+    stats::distance,
+};
+
+/// Scale factors to make the MAD and the mean absolute deviation
+/// consistent with the standard deviation of a normal distribution.
+const MAD_SCALE: f64 = 1.4826;
+const MEAN_AD_SCALE: f64 = 1.253314;
+/// Minimum number of sequences with comparable sites
+/// required to compute the locus distance distribution.
+const MIN_TAXA: usize = 4;
+const SUMMARY_FNAME: &str = "outlier_summary";
+const FILTERED_DIR: &str = "filtered_alignments";
+
+/// Rules to flag outlier sequences.
+pub enum OutlierParameters {
+    /// Flag sequences with a modified z-score of mean distance
+    /// higher than the threshold.
+    ZScore(f64),
+    /// Flag sequences with a mean distance higher than
+    /// Q3 + multiplier * IQR.
+    Iqr(f64),
+}
+
+impl std::fmt::Display for OutlierParameters {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ZScore(threshold) => write!(f, "modified z-score > {}", threshold),
+            Self::Iqr(multiplier) => write!(f, "Q3 + {} * IQR", multiplier),
+        }
+    }
+}
+
+pub struct OutlierDetection<'a> {
+    /// List of input files.
+    files: &'a [PathBuf],
+    /// Input format.
+    input_fmt: &'a InputFmt,
+    /// Data type.
+    datatype: &'a DataType,
+    /// Output directory.
+    output_dir: &'a Path,
+    /// Output format for the filtered alignments.
+    output_fmt: &'a OutputFmt,
+    /// Outlier detection rule.
+    params: &'a OutlierParameters,
+}
+
+impl<'a> OutlierDetection<'a> {
+    pub fn new(
+        files: &'a [PathBuf],
+        input_fmt: &'a InputFmt,
+        datatype: &'a DataType,
+        output_dir: &'a Path,
+        output_fmt: &'a OutputFmt,
+        params: &'a OutlierParameters,
+    ) -> Self {
+        Self {
+            files,
+            input_fmt,
+            datatype,
+            output_dir,
+            output_fmt,
+            params,
+        }
+    }
+
+    /// Detect outlier sequences in each alignment
+    /// and write a per-locus summary.
+    /// If `remove` is true, write alignments without the outlier sequences.
+    /// Returns the total number of outlier sequences found.
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::{DataType, InputFmt, OutputFmt};
+    /// use segul::helper::finder::SeqFileFinder;
+    /// use segul::core::sequence::outlier::{OutlierDetection, OutlierParameters};
+    ///
+    /// let input_fmt = InputFmt::Fasta;
+    /// let datatype = DataType::Dna;
+    /// let files = SeqFileFinder::new(Path::new("tests/files/outlier")).find(&input_fmt);
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let output_fmt = OutputFmt::Fasta;
+    /// let params = OutlierParameters::Iqr(1.5);
+    /// let handle = OutlierDetection::new(
+    ///     &files, &input_fmt, &datatype, output.path(), &output_fmt, &params
+    /// );
+    /// let outliers = handle.detect(false);
+    /// assert_eq!(outliers, 1);
+    /// ```
+    pub fn detect(&self, remove: bool) -> usize {
+        let spin = utils::set_spinner();
+        spin.set_message("Detecting outlier sequences...");
+        let mut summary = self.par_detect();
+        summary.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        self.write_summary(&summary);
+        spin.finish_with_message("Finished detecting outlier sequences!\n");
+        let outlier_counts = summary.iter().map(|s| s.outlier_count).sum();
+        self.print_output_info(&summary, outlier_counts);
+        if remove {
+            self.remove_outliers(&summary);
+        }
+        outlier_counts
+    }
+
+    fn par_detect(&self) -> Vec<OutlierSummary> {
+        let (tx, rx) = mpsc::channel();
+        self.files.par_iter().for_each_with(tx, |tx, file| {
+            tx.send(self.detect_outliers(file))
+                .expect("Failed sending outlier summary");
+        });
+        rx.iter().collect()
+    }
+
+    fn detect_outliers(&self, file: &Path) -> OutlierSummary {
+        let (matrix, _) = SeqParser::new(file, self.datatype).get_alignment(self.input_fmt);
+        let distances = distance::mean_pairwise_distance(&matrix, self.datatype);
+        let values: Vec<(&String, f64)> = distances
+            .iter()
+            .filter_map(|(id, dist)| dist.map(|d| (id, d)))
+            .collect();
+        let mut summary = OutlierSummary::new(file, matrix.len());
+        let mut no_comparable: Vec<&str> = distances
+            .iter()
+            .filter(|(_, dist)| dist.is_none())
+            .map(|(id, _)| id.as_str())
+            .collect();
+        no_comparable.sort_unstable();
+        summary.no_comparable_count = no_comparable.len();
+        summary.no_comparable_sites = no_comparable.join(";");
+        if values.len() < MIN_TAXA {
+            return summary;
+        }
+        let dists: Vec<f64> = values.iter().map(|(_, d)| *d).collect();
+        summary.mean_distance = mean(&dists);
+        let outliers: Vec<String> = match self.params {
+            OutlierParameters::ZScore(threshold) => {
+                let mut sorted = dists.clone();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let median = quantile(&sorted, 0.5);
+                let scale = robust_scale(&dists, median);
+                if scale == 0.0 {
+                    return summary;
+                }
+                summary.cutoff = median + threshold * scale;
+                values
+                    .iter()
+                    .filter(|(_, d)| (d - median) / scale > *threshold)
+                    .map(|(id, _)| id.to_string())
+                    .collect()
+            }
+            OutlierParameters::Iqr(multiplier) => {
+                let mut sorted = dists.clone();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let q1 = quantile(&sorted, 0.25);
+                let q3 = quantile(&sorted, 0.75);
+                summary.cutoff = q3 + multiplier * (q3 - q1);
+                values
+                    .iter()
+                    .filter(|(_, d)| *d > summary.cutoff)
+                    .map(|(id, _)| id.to_string())
+                    .collect()
+            }
+        };
+        summary.outlier_count = outliers.len();
+        summary.outliers = outliers.join(";");
+        summary
+    }
+
+    fn remove_outliers(&self, summary: &[OutlierSummary]) {
+        let ids: HashMap<PathBuf, Vec<String>> = summary
+            .iter()
+            .filter(|s| s.outlier_count > 0)
+            .map(|s| {
+                let ids = s.outliers.split(';').map(|id| id.to_string()).collect();
+                (s.path.to_path_buf(), ids)
+            })
+            .collect();
+        let opts = SeqRemovalParameters::IdPerFile(ids);
+        let output_dir = self.output_dir.join(FILTERED_DIR);
+        SequenceRemoval::new(
+            self.input_fmt,
+            self.datatype,
+            &output_dir,
+            self.output_fmt,
+            &opts,
+        )
+        .remove(self.files);
+    }
+
+    fn write_summary(&self, summary: &[OutlierSummary]) {
+        let output_path = self.output_dir.join(SUMMARY_FNAME).with_extension("csv");
+        fs::create_dir_all(self.output_dir).expect("Failed to create output directory");
+        let mut writer = csv::Writer::from_path(output_path).expect("Failed to create CSV writer");
+        summary.iter().for_each(|s| {
+            writer.serialize(s).expect("Failed to write summary");
+        });
+    }
+
+    fn print_output_info(&self, summary: &[OutlierSummary], outlier_counts: usize) {
+        let loci = summary.iter().filter(|s| s.outlier_count > 0).count();
+        let no_comparable: usize = summary.iter().map(|s| s.no_comparable_count).sum();
+        if no_comparable > 0 {
+            log::warn!(
+                "{} {} sequences have no comparable sites and are not tested. \
+                See the no_comparable_sites column of the summary.",
+                "WARNING!".red(),
+                utils::fmt_num(&no_comparable)
+            );
+        }
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Directory", self.output_dir.display());
+        log::info!("{:18}: {}", "Rule", self.params);
+        log::info!(
+            "{:18}: {}",
+            "Outlier counts",
+            utils::fmt_num(&outlier_counts)
+        );
+        log::info!("{:18}: {}\n", "Affected loci", utils::fmt_num(&loci));
+    }
+}
+
+/// Per-locus outlier summary.
+#[derive(Debug, Serialize, Deserialize)]
+struct OutlierSummary {
+    /// Input path. Used to match the file for removal.
+    #[serde(skip)]
+    path: PathBuf,
+    /// File name
+    file_name: String,
+    /// Number of sequences in the alignment
+    taxon_count: usize,
+    /// Mean of the per-sequence mean pairwise distances
+    mean_distance: f64,
+    /// Distance above which a sequence is flagged
+    cutoff: f64,
+    /// Number of outlier sequences
+    outlier_count: usize,
+    /// Outlier sequence IDs separated by semicolon
+    outliers: String,
+    /// Number of sequences with no comparable sites
+    no_comparable_count: usize,
+    /// Sequence IDs with no comparable sites separated by semicolon
+    no_comparable_sites: String,
+}
+
+impl OutlierSummary {
+    fn new(path: &Path, taxon_count: usize) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            file_name: path
+                .file_name()
+                .expect("Failed to get file name")
+                .to_string_lossy()
+                .to_string(),
+            taxon_count,
+            mean_distance: 0.0,
+            cutoff: 0.0,
+            outlier_count: 0,
+            outliers: String::new(),
+            no_comparable_count: 0,
+            no_comparable_sites: String::new(),
+        }
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// Robust estimate of the standard deviation around the median.
+// Fall back to the mean absolute deviation if more than half
+// of the values are equal to the median and the MAD is zero.
+fn robust_scale(values: &[f64], median: f64) -> f64 {
+    let mut deviations: Vec<f64> = values.iter().map(|v| (v - median).abs()).collect();
+    deviations.sort_by(|a, b| a.total_cmp(b));
+    let mad = quantile(&deviations, 0.5);
+    if mad > 0.0 {
+        return mad * MAD_SCALE;
+    }
+    mean(&deviations) * MEAN_AD_SCALE
+}
+
+// Linear interpolation between the closest ranks.
+// The input must be sorted in ascending order.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = (sorted.len() - 1) as f64 * q;
+    let lower = pos.floor() as usize;
+    let upper = pos.ceil() as usize;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempdir::TempDir;
+
+    const INPUT: &str = "tests/files/outlier/locus_1.fas";
+
+    macro_rules! input {
+        ($handle: ident, $params: expr_2021, $output: ident) => {
+            let files = [PathBuf::from(INPUT)];
+            let params = $params;
+            let $output = TempDir::new("temp").unwrap();
+            let $handle = OutlierDetection::new(
+                &files,
+                &InputFmt::Fasta,
+                &DataType::Dna,
+                $output.path(),
+                &OutputFmt::Fasta,
+                &params,
+            );
+        };
+    }
+
+    #[test]
+    fn test_quantile() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(2.0, quantile(&values, 0.25));
+        assert_eq!(4.0, quantile(&values, 0.75));
+        assert_eq!(2.5, quantile(&[1.0, 2.0, 3.0, 4.0], 0.5));
+    }
+
+    #[test]
+    fn test_detect_iqr() {
+        input!(handle, OutlierParameters::Iqr(1.5), output);
+        let summary = handle.detect_outliers(Path::new(INPUT));
+        assert_eq!(1, summary.outlier_count);
+        assert_eq!("contaminant", summary.outliers);
+    }
+
+    #[test]
+    fn test_robust_scale() {
+        let values = [1.0, 2.0, 3.0, 4.0, 100.0];
+        assert!((robust_scale(&values, 3.0) - MAD_SCALE).abs() < 1e-9);
+        // MAD is zero, use the mean absolute deviation.
+        let values = [1.0, 1.0, 1.0, 5.0];
+        assert!((robust_scale(&values, 1.0) - MEAN_AD_SCALE).abs() < 1e-9);
+        assert_eq!(0.0, robust_scale(&[2.0, 2.0], 2.0));
+    }
+
+    #[test]
+    fn test_detect_zscore() {
+        input!(handle, OutlierParameters::ZScore(2.0), output);
+        let summary = handle.detect_outliers(Path::new(INPUT));
+        assert_eq!(1, summary.outlier_count);
+        assert_eq!("contaminant", summary.outliers);
+    }
+
+    #[test]
+    fn test_detect_zscore_default_threshold() {
+        // The locus has six sequences. The classic z-score cannot exceed
+        // (6 - 1) / sqrt(6) = 2.04 and would flag nothing at 3.5.
+        input!(handle, OutlierParameters::ZScore(3.5), output);
+        let summary = handle.detect_outliers(Path::new(INPUT));
+        assert_eq!(1, summary.outlier_count);
+        assert_eq!("contaminant", summary.outliers);
+    }
+
+    #[test]
+    fn test_no_comparable_sites() {
+        let input = TempDir::new("input").unwrap();
+        let file = input.path().join("locus.fas");
+        let locus = std::fs::read_to_string(INPUT).unwrap();
+        std::fs::write(&file, format!("{}>empty\n{}\n", locus, "-".repeat(40))).unwrap();
+        input!(handle, OutlierParameters::Iqr(1.5), output);
+        let summary = handle.detect_outliers(&file);
+        assert_eq!(7, summary.taxon_count);
+        assert_eq!("contaminant", summary.outliers);
+        assert_eq!(1, summary.no_comparable_count);
+        assert_eq!("empty", summary.no_comparable_sites);
+    }
+
+    #[test]
+    fn test_remove_outliers() {
+        input!(handle, OutlierParameters::Iqr(1.5), output);
+        handle.detect(true);
+        let output_file = output.path().join(FILTERED_DIR).join("locus_1.fas");
+        let (matrix, _) = SeqParser::new(&output_file, &DataType::Dna).parse(&InputFmt::Fasta);
+        assert_eq!(5, matrix.len());
+        assert!(!matrix.contains_key("contaminant"));
+        assert!(output.path().join("outlier_summary.csv").is_file());
+    }
+}
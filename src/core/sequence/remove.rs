@@ -1,5 +1,6 @@
 use std::path::{Path, PathBuf};
 
+use ahash::AHashMap as HashMap;
 use colored::Colorize;
 use rayon::prelude::*;
 use regex::Regex;
@@ -17,6 +18,9 @@ impl OutputPrint for SequenceRemoval<'_> {}
 pub enum SeqRemovalParameters {
     Id(Vec<String>),
     Regex(String),
+    /// Remove different IDs for each file.
+    /// Files not in the map are written unchanged.
+    IdPerFile(HashMap<PathBuf, Vec<String>>),
 }
 
 pub struct SequenceRemoval<'a> {
@@ -53,6 +57,7 @@ impl<'a> SequenceRemoval<'a> {
                 let ids = self.find_matching_ids(files, re);
                 self.par_remove(files, &ids);
             }
+            SeqRemovalParameters::IdPerFile(ids) => self.par_remove_per_file(files, ids),
         }
         spin.finish_with_message("Finished removing sequences!\n");
         self.print_output_info();
@@ -80,6 +85,16 @@ impl<'a> SequenceRemoval<'a> {
         })
     }
 
+    fn par_remove_per_file(&self, files: &[PathBuf], ids: &HashMap<PathBuf, Vec<String>>) {
+        files.par_iter().for_each(|file| {
+            let file_ids = ids.get(file).map(|ids| ids.as_slice()).unwrap_or_default();
+            let (matrix, header) = self.remove_sequence(file, file_ids);
+            if !matrix.is_empty() {
+                self.write_output(&matrix, &header, file);
+            }
+        })
+    }
+
     fn write_output(&self, matrix: &SeqMatrix, header: &Header, file: &Path) {
         let output_path = files::create_output_fname(self.output_dir, file, self.output_fmt);
         let mut writer = SeqWriter::new(&output_path, matrix, header);
@@ -154,4 +169,22 @@ mod test {
         let (_, header) = remove.remove_sequence(&file, &ids);
         assert_eq!(header.ntax, 1);
     }
+
+    #[test]
+    fn test_remove_per_file() {
+        let input_fmt = InputFmt::Fasta;
+        let datatype = DataType::Dna;
+        let file = PathBuf::from("tests/files/simple.fas");
+        let mut ids = HashMap::new();
+        ids.insert(file.clone(), vec![String::from("ABCD")]);
+        let opts = SeqRemovalParameters::IdPerFile(ids);
+        let output_dir = tempdir::TempDir::new("temp").unwrap();
+        let output_fmt = OutputFmt::Fasta;
+        let remove =
+            SequenceRemoval::new(&input_fmt, &datatype, output_dir.path(), &output_fmt, &opts);
+        remove.remove(&[file]);
+        let output = output_dir.path().join("simple.fas");
+        let (matrix, _) = SeqParser::new(&output, &datatype).parse(&input_fmt);
+        assert_eq!(matrix.len(), 1);
+    }
 }
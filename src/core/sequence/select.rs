@@ -0,0 +1,282 @@
+//! Select a representative sample per species.
+//!
+//! Given a list of sample IDs and their species names,
+//! keep the sequence with the least missing data for each species
+//! in each alignment and rename it to the species name.
+//! Missing data are gaps (`-`), missing characters (`?`),
+//! and for DNA sequences, undetermined bases (`N`).
+//! If several samples have the same amount of missing data,
+//! the first sample in the alignment is selected.
+//! Sequences without a species name are kept as is.
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+use ahash::AHashMap as HashMap;
+use colored::Colorize;
+use indexmap::IndexMap;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::OutputPrint,
+    helper::{
+        files,
+        sequence::SeqParser,
+        types::{DataType, Header, InputFmt, OutputFmt, SeqMatrix},
+        utils,
+    },
+    writer::sequences::SeqWriter,
+};
+
+const SUMMARY_FNAME: &str = "selection_summary";
+
+impl OutputPrint for SampleSelection<'_> {}
+
+pub struct SampleSelection<'a> {
+    /// Input format.
+    input_fmt: &'a InputFmt,
+    /// Data type.
+    datatype: &'a DataType,
+    /// Output directory.
+    output_dir: &'a Path,
+    /// Output format.
+    output_fmt: &'a OutputFmt,
+    /// Sample ID and species name pairs.
+    species: &'a [(String, String)],
+}
+
+impl<'a> SampleSelection<'a> {
+    pub fn new(
+        input_fmt: &'a InputFmt,
+        datatype: &'a DataType,
+        output_dir: &'a Path,
+        output_fmt: &'a OutputFmt,
+        species: &'a [(String, String)],
+    ) -> Self {
+        Self {
+            input_fmt,
+            datatype,
+            output_dir,
+            output_fmt,
+            species,
+        }
+    }
+
+    /// Select the most complete sample per species in each alignment.
+    /// Write the alignments and a summary of the selected samples.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use tempdir::TempDir;
+    /// use segul::helper::types::{DataType, InputFmt, OutputFmt};
+    /// use segul::core::sequence::select::SampleSelection;
+    ///
+    /// let files = vec![PathBuf::from("tests/files/consensus/locus_1.fas")];
+    /// let species = vec![
+    ///     (String::from("Genus_alpha_1"), String::from("Genus_alpha")),
+    ///     (String::from("Genus_alpha_2"), String::from("Genus_alpha")),
+    /// ];
+    /// // Replace the temp directory with your own directory.
+    /// let output = TempDir::new("temp").unwrap();
+    /// let handle = SampleSelection::new(
+    ///     &InputFmt::Fasta, &DataType::Dna, output.path(), &OutputFmt::Fasta, &species
+    /// );
+    /// handle.select(&files);
+    /// ```
+    pub fn select(&self, files: &[PathBuf]) {
+        let spin = utils::set_spinner();
+        spin.set_message("Selecting samples...");
+        let species: HashMap<&str, &str> = self
+            .species
+            .iter()
+            .map(|(id, name)| (id.as_str(), name.as_str()))
+            .collect();
+        let (tx, rx) = mpsc::channel();
+        files.par_iter().for_each_with(tx, |tx, file| {
+            let (matrix, header) = SeqParser::new(file, self.datatype).parse(self.input_fmt);
+            let (selected, summary) = self.select_samples(file, &matrix, &species);
+            self.write_output(&selected, &header, file);
+            tx.send(summary).expect("Failed sending selection summary");
+        });
+        let mut summary: Vec<SelectionSummary> = rx.iter().flatten().collect();
+        summary.sort_by(|a, b| {
+            a.file_name
+                .cmp(&b.file_name)
+                .then_with(|| a.species.cmp(&b.species))
+        });
+        self.write_summary(&summary);
+        spin.finish_with_message("Finished selecting samples!\n");
+        self.print_output_info(files.len());
+    }
+
+    fn select_samples(
+        &self,
+        file: &Path,
+        matrix: &SeqMatrix,
+        species: &HashMap<&str, &str>,
+    ) -> (SeqMatrix, Vec<SelectionSummary>) {
+        // Species name -> (selected ID, missing data, candidate counts)
+        let mut best: IndexMap<&str, (&str, usize, usize)> = IndexMap::new();
+        matrix.iter().for_each(|(id, seq)| {
+            if let Some(name) = species.get(id.as_str()) {
+                let missing = self.count_missing_data(seq);
+                match best.get_mut(name) {
+                    Some((best_id, best_missing, counts)) => {
+                        *counts += 1;
+                        if missing < *best_missing {
+                            *best_id = id;
+                            *best_missing = missing;
+                        }
+                    }
+                    None => {
+                        best.insert(name, (id, missing, 1));
+                    }
+                }
+            }
+        });
+
+        let mut selected = SeqMatrix::with_capacity(matrix.len());
+        matrix
+            .iter()
+            .for_each(|(id, seq)| match species.get(id.as_str()) {
+                Some(name) => {
+                    if let Some((best_id, _, _)) = best.get(name)
+                        && *best_id == id
+                    {
+                        self.insert_sequence(&mut selected, name, seq, file);
+                    }
+                }
+                None => self.insert_sequence(&mut selected, id, seq, file),
+            });
+
+        let file_name = file
+            .file_name()
+            .expect("Failed to get file name")
+            .to_string_lossy()
+            .to_string();
+        let summary = best
+            .iter()
+            .map(|(name, (id, missing, counts))| SelectionSummary {
+                file_name: file_name.clone(),
+                species: name.to_string(),
+                selected_sample: id.to_string(),
+                candidate_counts: *counts,
+                missing_data: *missing,
+                prop_missing_data: *missing as f64 / matrix[*id].len() as f64,
+            })
+            .collect();
+
+        (selected, summary)
+    }
+
+    fn insert_sequence(&self, matrix: &mut SeqMatrix, id: &str, seq: &str, file: &Path) {
+        if matrix.insert(id.to_string(), seq.to_string()).is_some() {
+            log::warn!(
+                "{} Sequence ID {} in {} conflicts with a species name. \
+                Keeping the last found sequence.",
+                "WARNING!".red(),
+                id,
+                file.display()
+            );
+        }
+    }
+
+    fn count_missing_data(&self, seq: &str) -> usize {
+        match self.datatype {
+            DataType::Dna => seq
+                .bytes()
+                .filter(|&c| c == b'-' || c == b'?' || c == b'N' || c == b'n')
+                .count(),
+            _ => seq.bytes().filter(|&c| c == b'-' || c == b'?').count(),
+        }
+    }
+
+    fn write_output(&self, matrix: &SeqMatrix, header: &Header, file: &Path) {
+        if matrix.is_empty() {
+            return;
+        }
+        let output_path = files::create_output_fname(self.output_dir, file, self.output_fmt);
+        let mut header = header.clone();
+        header.ntax = matrix.len();
+        let mut writer = SeqWriter::new(&output_path, matrix, &header);
+        writer
+            .write_sequence(self.output_fmt)
+            .expect("Failed writing output sequence");
+    }
+
+    fn write_summary(&self, summary: &[SelectionSummary]) {
+        let output_path = self.output_dir.join(SUMMARY_FNAME).with_extension("csv");
+        fs::create_dir_all(self.output_dir).expect("Failed to create output directory");
+        let mut writer = csv::Writer::from_path(output_path).expect("Failed to create CSV writer");
+        summary.iter().for_each(|s| {
+            writer.serialize(s).expect("Failed to write summary");
+        });
+    }
+
+    fn print_output_info(&self, file_counts: usize) {
+        log::info!("{}", "Output".yellow());
+        log::info!("{:18}: {}", "Directory", self.output_dir.display());
+        self.print_output_fmt(self.output_fmt);
+        log::info!("{:18}: {}", "Total files", utils::fmt_num(&file_counts));
+    }
+}
+
+/// Selected sample for each species in each locus.
+#[derive(Debug, Serialize, Deserialize)]
+struct SelectionSummary {
+    /// File name
+    file_name: String,
+    /// Species name
+    species: String,
+    /// Selected sample ID
+    selected_sample: String,
+    /// Number of samples of the species in the locus
+    candidate_counts: usize,
+    /// Missing data count of the selected sample
+    missing_data: usize,
+    /// Proportion of missing data of the selected sample
+    prop_missing_data: f64,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_select_least_missing() {
+        let file = Path::new("tests/files/select/locus_1.fas");
+        let species = vec![
+            (String::from("alpha_1"), String::from("Genus_alpha")),
+            (String::from("alpha_2"), String::from("Genus_alpha")),
+            (String::from("alpha_3"), String::from("Genus_alpha")),
+            (String::from("beta_1"), String::from("Genus_beta")),
+        ];
+        let handle = SampleSelection::new(
+            &InputFmt::Fasta,
+            &DataType::Dna,
+            Path::new("."),
+            &OutputFmt::Fasta,
+            &species,
+        );
+        let map: HashMap<&str, &str> = species
+            .iter()
+            .map(|(id, name)| (id.as_str(), name.as_str()))
+            .collect();
+        let (matrix, _) = SeqParser::new(file, &DataType::Dna).parse(&InputFmt::Fasta);
+        let (selected, summary) = handle.select_samples(file, &matrix, &map);
+        assert_eq!(3, selected.len());
+        assert_eq!(
+            Some(&String::from("ATGCATNCGT")),
+            selected.get("Genus_alpha")
+        );
+        assert!(selected.contains_key("Genus_beta"));
+        assert!(selected.contains_key("outgroup"));
+        assert_eq!(2, summary.len());
+        assert_eq!("alpha_2", summary[0].selected_sample);
+        assert_eq!(3, summary[0].candidate_counts);
+        assert_eq!(1, summary[0].missing_data);
+    }
+}
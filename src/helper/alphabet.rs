@@ -117,6 +117,94 @@ pub fn is_valid_aa(aa: &str) -> bool {
     aa.bytes().all(|char| AA.contains(&char))
 }
 
+/// IUPAC nucleotide codes indexed by a 4-bit mask of the bases they represent.
+/// The bits are A = 1, C = 2, G = 4, and T = 8.
+const IUPAC_DNA: &[u8; 16] = b"-ACMGRSVTWYHKDBN";
+
+/// Convert a DNA character to a 4-bit mask of the bases it represents.
+/// A = 1, C = 2, G = 4, and T (or U) = 8. Ambiguous IUPAC characters
+/// return the union of their bases. Gaps, missing data,
+/// and invalid characters return 0.
+/// # Example
+/// ```
+/// use segul::helper::alphabet;
+///
+/// assert_eq!(alphabet::dna_to_mask(b'a'), 1);
+/// assert_eq!(alphabet::dna_to_mask(b'R'), 5);
+/// assert_eq!(alphabet::dna_to_mask(b'-'), 0);
+/// ```
+pub fn dna_to_mask(dna: u8) -> u8 {
+    match dna.to_ascii_uppercase() {
+        b'A' => 1,
+        b'C' => 2,
+        b'M' => 3,
+        b'G' => 4,
+        b'R' => 5,
+        b'S' => 6,
+        b'V' => 7,
+        b'T' | b'U' => 8,
+        b'W' => 9,
+        b'Y' => 10,
+        b'H' => 11,
+        b'K' => 12,
+        b'D' => 13,
+        b'B' => 14,
+        b'N' => 15,
+        _ => 0,
+    }
+}
+
+/// Convert a 4-bit mask of bases to its IUPAC character.
+/// It is the reverse of `dna_to_mask()`. An empty mask returns a gap.
+/// # Example
+/// ```
+/// use segul::helper::alphabet;
+///
+/// assert_eq!(alphabet::mask_to_iupac(5), b'R');
+/// assert_eq!(alphabet::mask_to_iupac(alphabet::dna_to_mask(b'A') | alphabet::dna_to_mask(b'T')), b'W');
+/// ```
+pub fn mask_to_iupac(mask: u8) -> u8 {
+    IUPAC_DNA[(mask & 0x0F) as usize]
+}
+
+/// Complement a DNA character. IUPAC ambiguity codes are complemented
+/// to the code of the complementary bases. Case is preserved.
+/// Gaps, missing data, and other characters are returned unchanged.
+/// # Example
+/// ```
+/// use segul::helper::alphabet;
+///
+/// assert_eq!(alphabet::complement_dna(b'A'), b'T');
+/// assert_eq!(alphabet::complement_dna(b'r'), b'y');
+/// assert_eq!(alphabet::complement_dna(b'-'), b'-');
+/// ```
+pub fn complement_dna(dna: u8) -> u8 {
+    let mask = dna_to_mask(dna);
+    if mask == 0 {
+        return dna;
+    }
+    // Swap A <-> T and C <-> G by reversing the mask bits.
+    let complement = mask_to_iupac(
+        ((mask & 1) << 3) | ((mask & 2) << 1) | ((mask & 4) >> 1) | ((mask & 8) >> 3),
+    );
+    if dna.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
+}
+
+/// Reverse complement a DNA sequence.
+/// # Example
+/// ```
+/// use segul::helper::alphabet;
+///
+/// assert_eq!(alphabet::reverse_complement(b"AAC-g"), b"c-GTT".to_vec());
+/// ```
+pub fn reverse_complement(dna: &[u8]) -> Vec<u8> {
+    dna.iter().rev().map(|&c| complement_dna(c)).collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -141,4 +229,18 @@ mod test {
         let dna = String::from("agta?-z");
         check_valid_dna(sample, id, &dna);
     }
+
+    #[test]
+    fn test_iupac_round_trip() {
+        b"ACGTRYSWKMBDHVN".iter().for_each(|&ch| {
+            assert_eq!(ch, mask_to_iupac(dna_to_mask(ch)));
+        });
+    }
+
+    #[test]
+    fn test_complement_dna() {
+        let dna = b"ACGTRYSWKMBDHVN?-acgt";
+        let complement: Vec<u8> = dna.iter().map(|&c| complement_dna(c)).collect();
+        assert_eq!(b"TGCAYRSWMKVHDBN?-tgca".to_vec(), complement);
+    }
 }
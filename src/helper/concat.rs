@@ -2,12 +2,15 @@
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
+use colored::Colorize;
 use indexmap::{IndexMap, IndexSet};
 use indicatif::ProgressBar;
+use regex::Regex;
 
 use crate::helper::finder::IDs;
 use crate::helper::sequence::SeqParser;
 use crate::helper::types::{DataType, Header, InputFmt, Partition, SeqMatrix};
+use crate::parser::partition::PartitionParser;
 
 use super::types::{OutputFmt, PartitionFmt};
 
@@ -30,6 +33,125 @@ impl ConcatParams {
     }
 }
 
+/// A rule to normalize taxon names before concatenation.
+#[derive(Debug, Clone)]
+pub enum NameRule {
+    /// Use the first capture group of a regular expression
+    /// or the whole match if the regex has no capture group.
+    /// Names that do not match the regex are kept as is.
+    Regex(Regex),
+    /// Remove a suffix from the names.
+    StripSuffix(String),
+    /// Convert the names to lowercase.
+    CaseFold,
+}
+
+/// Options to resolve sequences in a locus
+/// that are normalized to the same taxon name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameConflictPolicy {
+    /// Keep the sequence with the most characters,
+    /// excluding gaps and missing data (`?`).
+    Longest,
+    /// Keep the sequence with the fewest gaps, missing data,
+    /// and undetermined characters (`N` for DNA, `X` for amino acids).
+    LeastMissing,
+    /// Stop the concatenation.
+    Error,
+}
+
+impl std::fmt::Display for NameConflictPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Longest => write!(f, "longest"),
+            Self::LeastMissing => write!(f, "least-missing"),
+            Self::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl std::str::FromStr for NameConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "longest" => Ok(Self::Longest),
+            "least-missing" => Ok(Self::LeastMissing),
+            "error" => Ok(Self::Error),
+            _ => Err(format!("{} is not a valid name conflict policy", s)),
+        }
+    }
+}
+
+/// Taxon name normalization for concatenation.
+/// Rules are applied in order.
+pub struct NameNormalization {
+    pub rules: Vec<NameRule>,
+    pub policy: NameConflictPolicy,
+}
+
+impl NameNormalization {
+    /// Normalize a taxon name.
+    /// # Example
+    /// ```
+    /// use regex::Regex;
+    /// use segul::helper::concat::{NameConflictPolicy, NameNormalization, NameRule};
+    ///
+    /// let normalization = NameNormalization {
+    ///     rules: vec![
+    ///         NameRule::StripSuffix(String::from("_R")),
+    ///         NameRule::Regex(Regex::new("^([A-Za-z]+_[A-Za-z]+)").unwrap()),
+    ///         NameRule::CaseFold,
+    ///     ],
+    ///     policy: NameConflictPolicy::Error,
+    /// };
+    /// assert_eq!(normalization.normalize("Genus_Species_123_R"), "genus_species");
+    /// ```
+    pub fn normalize(&self, name: &str) -> String {
+        self.rules
+            .iter()
+            .fold(name.to_string(), |name, rule| match rule {
+                NameRule::Regex(re) => match re.captures(&name) {
+                    Some(caps) => caps
+                        .get(1)
+                        .or_else(|| caps.get(0))
+                        .map(|m| m.as_str().to_string())
+                        .unwrap_or(name),
+                    None => name,
+                },
+                NameRule::StripSuffix(suffix) => match name.strip_suffix(suffix.as_str()) {
+                    Some(stripped) => stripped.to_string(),
+                    None => name,
+                },
+                NameRule::CaseFold => name.to_lowercase(),
+            })
+    }
+}
+
+/// An explicit list of taxa for concatenation.
+/// Only the listed taxa are written, in the list order.
+/// Listed taxa absent from a locus are filled with missing data.
+/// Taxa are matched after name normalization.
+pub struct TaxonList {
+    pub taxa: Vec<String>,
+    /// Stop if a listed taxon is absent from all alignments.
+    pub require_present: bool,
+}
+
+/// An existing concatenated alignment to append new loci to.
+/// The alignment format is inferred from the file extension.
+/// Taxa absent from the new loci are filled with missing data,
+/// and so are new taxa in the existing partitions.
+pub struct Supermatrix {
+    /// Concatenated alignment path.
+    pub alignment: PathBuf,
+    /// Partition path of the concatenated alignment.
+    /// Use the alignment path for partitions written in the alignment.
+    pub partition: PathBuf,
+    /// Partition format.
+    pub part_fmt: PartitionFmt,
+}
+
 /// Concatenate multiple alignments into a single alignment.
 /// # Arguments
 /// * `files` - A mutable slice of PathBuf that holds the alignment files.
@@ -61,12 +183,20 @@ pub struct Concat<'a> {
     pub header: Header,
     /// The partitions of the concatenated alignment.
     pub partition: Vec<Partition>,
+    /// The number of sequences dropped by resolving name conflicts.
+    pub name_conflicts: usize,
     /// The input data type.
     datatype: &'a DataType,
     /// The alignment input format.
     input_fmt: &'a InputFmt,
     /// The alignment files.
     files: &'a mut [PathBuf],
+    /// Taxon name normalization.
+    normalization: Option<&'a NameNormalization>,
+    /// Explicit taxon list.
+    taxon_list: Option<&'a TaxonList>,
+    /// Existing concatenated alignment.
+    supermatrix: Option<&'a Supermatrix>,
 }
 
 impl<'a> Concat<'a> {
@@ -78,15 +208,40 @@ impl<'a> Concat<'a> {
             alignment: IndexMap::new(),
             header: Header::new(),
             partition: Vec::new(),
+            name_conflicts: 0,
             files,
+            normalization: None,
+            taxon_list: None,
+            supermatrix: None,
         }
     }
 
+    /// Set the taxon name normalization.
+    /// Names are normalized before concatenation.
+    pub fn set_name_normalization(&mut self, normalization: &'a NameNormalization) {
+        self.normalization = Some(normalization);
+    }
+
+    /// Set an explicit taxon list.
+    /// The concatenated alignment only contains the listed taxa.
+    pub fn set_taxon_list(&mut self, taxon_list: &'a TaxonList) {
+        self.taxon_list = Some(taxon_list);
+    }
+
+    /// Set an existing concatenated alignment.
+    /// The alignments are appended to it as new loci.
+    pub fn set_supermatrix(&mut self, supermatrix: &'a Supermatrix) {
+        self.supermatrix = Some(supermatrix);
+    }
+
     /// Concatenate alignments, required a spinner for stdout.
     pub fn concat_alignment(&mut self, spin: &ProgressBar) {
         alphanumeric_sort::sort_path_slice(self.files);
         spin.set_message("Indexing alignments...");
-        let id = IDs::new(self.files, self.input_fmt, self.datatype).id_unique();
+        if let Some(supermatrix) = self.supermatrix {
+            self.load_supermatrix(supermatrix);
+        }
+        let id = self.get_unique_ids();
         spin.set_message("Concatenating alignments...");
         self.concat(&id);
         self.header.ntax = self.alignment.len();
@@ -96,21 +251,106 @@ impl<'a> Concat<'a> {
     /// Concatenate alignments, without a spinner.
     pub fn concat_alignment_no_spinner(&mut self) {
         alphanumeric_sort::sort_path_slice(self.files);
-        let id = IDs::new(self.files, self.input_fmt, self.datatype).id_unique();
+        if let Some(supermatrix) = self.supermatrix {
+            self.load_supermatrix(supermatrix);
+        }
+        let id = self.get_unique_ids();
         self.concat(&id);
         self.header.ntax = self.alignment.len();
         self.match_header_datatype();
     }
 
+    // Use the existing alignment and partitions as the start of the concatenation.
+    fn load_supermatrix(&mut self, supermatrix: &Supermatrix) {
+        let (mut matrix, header) =
+            SeqParser::new(&supermatrix.alignment, self.datatype).get_alignment(&InputFmt::Auto);
+        let partition =
+            PartitionParser::new(&supermatrix.partition, &supermatrix.part_fmt, false).parse();
+        let part_end = partition.last().map(|part| part.end).unwrap_or(0);
+        assert_eq!(
+            part_end, header.nchar,
+            "The partition ends at {} but the concatenated alignment has {} sites",
+            part_end, header.nchar
+        );
+        self.files.iter().for_each(|file| {
+            let gene_name = self.parse_aln_name(file);
+            assert!(
+                !partition.iter().any(|part| part.gene == gene_name),
+                "Locus {} is already in the concatenated alignment",
+                gene_name
+            );
+        });
+        if let Some(normalization) = self.normalization {
+            let locus = self.parse_aln_name(&supermatrix.alignment);
+            let (normalized, conflicts) = self.normalize_ids(matrix, &locus, normalization);
+            matrix = normalized;
+            self.name_conflicts += conflicts;
+        }
+        self.alignment = matrix;
+        self.header.nchar = header.nchar;
+        self.partition = partition;
+    }
+
+    fn get_unique_ids(&self) -> IndexSet<String> {
+        let id = IDs::new(self.files, self.input_fmt, self.datatype).id_unique();
+        let id: IndexSet<String> = match self.normalization {
+            Some(normalization) => id.iter().map(|id| normalization.normalize(id)).collect(),
+            None => id,
+        };
+        // Keep the taxon order of the existing alignment.
+        let id = self.alignment.keys().cloned().chain(id).collect();
+        match self.taxon_list {
+            Some(taxon_list) => self.match_taxon_list(&id, taxon_list),
+            None => id,
+        }
+    }
+
+    fn match_taxon_list(&self, id: &IndexSet<String>, taxon_list: &TaxonList) -> IndexSet<String> {
+        let absent: Vec<&str> = taxon_list
+            .taxa
+            .iter()
+            .filter(|taxon| !id.contains(*taxon))
+            .map(|taxon| taxon.as_str())
+            .collect();
+        if !absent.is_empty() {
+            assert!(
+                !taxon_list.require_present,
+                "Listed taxa are not found in any alignment: {}",
+                absent.join(", ")
+            );
+            log::warn!(
+                "{} Listed taxa are not found in any alignment \
+                and will be filled with missing data: {}",
+                "WARNING!".red(),
+                absent.join(", ")
+            );
+        }
+        taxon_list.taxa.iter().cloned().collect()
+    }
+
     fn concat(&mut self, id: &IndexSet<String>) {
         let mut alignment = IndexMap::with_capacity(id.len());
-        let mut nchar = 0;
-        let mut gene_start = 1;
-        let mut partition = Vec::new();
+        // Start from the existing alignment if available
+        let existing = std::mem::take(&mut self.alignment);
+        let mut nchar = self.header.nchar;
+        let mut gene_start = nchar + 1;
+        let mut partition = std::mem::take(&mut self.partition);
+        let mut name_conflicts = self.name_conflicts;
+        if nchar > 0 {
+            id.iter().for_each(|id| match existing.get(id) {
+                Some(seq) => self.insert_alignment(&mut alignment, id, seq),
+                None => self.insert_alignment(&mut alignment, id, &self.get_missings(nchar)),
+            });
+        }
         self.files.iter().for_each(|file| {
-            let (matrix, header) = self.get_alignment(file);
+            let (mut matrix, header) = self.get_alignment(file);
             nchar += header.nchar; // increment sequence length using the value from parser
             let gene_name = self.parse_aln_name(file);
+            if let Some(normalization) = self.normalization {
+                let (normalized, conflicts) = self.normalize_ids(matrix, &gene_name, normalization);
+                matrix = normalized;
+                name_conflicts += conflicts;
+            }
             let part = self.get_partition(&gene_name, gene_start, nchar);
             partition.push(part);
             gene_start = nchar + 1;
@@ -128,6 +368,75 @@ impl<'a> Concat<'a> {
         self.alignment = alignment;
         self.header.nchar = nchar;
         self.partition = partition;
+        self.name_conflicts = name_conflicts;
+    }
+
+    // Rename sequences to their normalized names.
+    // Returns the normalized matrix and the number of dropped sequences.
+    fn normalize_ids(
+        &self,
+        matrix: SeqMatrix,
+        locus: &str,
+        normalization: &NameNormalization,
+    ) -> (SeqMatrix, usize) {
+        let mut normalized: SeqMatrix = IndexMap::with_capacity(matrix.len());
+        let mut sources: IndexMap<String, String> = IndexMap::with_capacity(matrix.len());
+        let mut conflicts = 0;
+        matrix.into_iter().for_each(|(id, seq)| {
+            let name = normalization.normalize(&id);
+            let Some(current) = normalized.get_mut(&name) else {
+                sources.insert(name.clone(), id);
+                normalized.insert(name, seq);
+                return;
+            };
+            let source = sources.get_mut(&name).expect("Missing source ID");
+            assert!(
+                normalization.policy != NameConflictPolicy::Error,
+                "Sequences {} and {} in {} are both normalized to {}",
+                source,
+                id,
+                locus,
+                name
+            );
+            conflicts += 1;
+            let is_better = match normalization.policy {
+                NameConflictPolicy::Longest => {
+                    self.count_sequence_len(&seq) > self.count_sequence_len(current)
+                }
+                _ => self.count_missing_data(&seq) < self.count_missing_data(current),
+            };
+            let (kept, dropped) = if is_better {
+                (id.as_str(), source.as_str())
+            } else {
+                (source.as_str(), id.as_str())
+            };
+            log::warn!(
+                "{} Sequences {} and {} in {} are both normalized to {}. Keeping {}.",
+                "WARNING!".red(),
+                dropped,
+                kept,
+                locus,
+                name,
+                kept
+            );
+            if is_better {
+                *current = seq;
+                *source = id;
+            }
+        });
+        (normalized, conflicts)
+    }
+
+    fn count_sequence_len(&self, seq: &str) -> usize {
+        seq.bytes().filter(|ch| *ch != b'-' && *ch != b'?').count()
+    }
+
+    fn count_missing_data(&self, seq: &str) -> usize {
+        let undetermined: &[u8] = match self.datatype {
+            DataType::Aa => b"-?Xx",
+            _ => b"-?Nn",
+        };
+        seq.bytes().filter(|ch| undetermined.contains(ch)).count()
     }
 
     fn get_alignment(&self, file: &Path) -> (SeqMatrix, Header) {
@@ -244,6 +553,135 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_normalize_ids() {
+        let normalization = NameNormalization {
+            rules: vec![NameRule::StripSuffix(String::from("_R"))],
+            policy: NameConflictPolicy::LeastMissing,
+        };
+        let concat = Concat::new(&mut [], &InputFmt::Fasta, &DNA);
+        let mut matrix = IndexMap::new();
+        matrix.insert(String::from("Genus_species_1"), String::from("ATNNNN"));
+        matrix.insert(String::from("Genus_species_1_R"), String::from("ATGC--"));
+        matrix.insert(String::from("Genus_species_2"), String::from("ATGCAT"));
+        let (normalized, conflicts) = concat.normalize_ids(matrix.clone(), "locus", &normalization);
+        assert_eq!(1, conflicts);
+        assert_eq!(2, normalized.len());
+        assert_eq!("ATGC--", normalized.get("Genus_species_1").unwrap());
+        // N is not missing data for the longest policy.
+        let normalization = NameNormalization {
+            policy: NameConflictPolicy::Longest,
+            ..normalization
+        };
+        let (normalized, _) = concat.normalize_ids(matrix, "locus", &normalization);
+        assert_eq!("ATNNNN", normalized.get("Genus_species_1").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "are both normalized to")]
+    fn test_normalize_ids_error() {
+        let normalization = NameNormalization {
+            rules: vec![NameRule::CaseFold],
+            policy: NameConflictPolicy::Error,
+        };
+        let concat = Concat::new(&mut [], &InputFmt::Fasta, &DNA);
+        let mut matrix = IndexMap::new();
+        matrix.insert(String::from("ABC"), String::from("ATGC"));
+        matrix.insert(String::from("abc"), String::from("ATGC"));
+        concat.normalize_ids(matrix, "locus", &normalization);
+    }
+
+    #[test]
+    fn test_concat_normalized() {
+        let path = Path::new("tests/files/alignments/");
+        let mut files = SeqFileFinder::new(path).find(&InputFmt::Nexus);
+        let normalization = NameNormalization {
+            rules: vec![NameRule::Regex(Regex::new("^(AB)").unwrap())],
+            policy: NameConflictPolicy::LeastMissing,
+        };
+        let mut concat = Concat::new(&mut files, &InputFmt::Nexus, &DNA);
+        concat.set_name_normalization(&normalization);
+        concat.concat_alignment_no_spinner();
+        assert_eq!(1, concat.alignment.len());
+        assert_eq!(3, concat.name_conflicts);
+        assert_eq!(26, concat.alignment.get("AB").unwrap().len());
+    }
+
+    #[test]
+    fn test_concat_taxon_list() {
+        let path = Path::new("tests/files/alignments/");
+        let mut files = SeqFileFinder::new(path).find(&InputFmt::Nexus);
+        let taxon_list = TaxonList {
+            taxa: vec![
+                String::from("ABCE"),
+                String::from("ABCZ"),
+                String::from("ABCD"),
+            ],
+            require_present: false,
+        };
+        let mut concat = Concat::new(&mut files, &InputFmt::Nexus, &DNA);
+        concat.set_taxon_list(&taxon_list);
+        concat.concat_alignment_no_spinner();
+        let ids: Vec<&str> = concat.alignment.keys().map(|id| id.as_str()).collect();
+        assert_eq!(vec!["ABCE", "ABCZ", "ABCD"], ids);
+        assert_eq!(3, concat.header.ntax);
+        assert_eq!("?".repeat(26), *concat.alignment.get("ABCZ").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "ABCZ")]
+    fn test_concat_taxon_list_absent() {
+        let path = Path::new("tests/files/alignments/");
+        let mut files = SeqFileFinder::new(path).find(&InputFmt::Nexus);
+        let taxon_list = TaxonList {
+            taxa: vec![String::from("ABCD"), String::from("ABCZ")],
+            require_present: true,
+        };
+        let mut concat = Concat::new(&mut files, &InputFmt::Nexus, &DNA);
+        concat.set_taxon_list(&taxon_list);
+        concat.concat_alignment_no_spinner();
+    }
+
+    #[test]
+    fn test_concat_supermatrix() {
+        let mut files = vec![PathBuf::from("tests/files/alignments/gene_4.nexus")];
+        let supermatrix = Supermatrix {
+            alignment: PathBuf::from("tests/files/concat/supermatrix.fas"),
+            partition: PathBuf::from("tests/files/concat/supermatrix_partition.txt"),
+            part_fmt: PartitionFmt::Raxml,
+        };
+        let mut concat = Concat::new(&mut files, &InputFmt::Auto, &DNA);
+        concat.set_supermatrix(&supermatrix);
+        concat.concat_alignment_no_spinner();
+        assert_eq!(14, concat.header.nchar);
+        assert_eq!(3, concat.header.ntax);
+        let ids: Vec<&str> = concat.alignment.keys().map(|id| id.as_str()).collect();
+        assert_eq!(vec!["ABCD", "ABCF", "ABCE"], ids);
+        assert_eq!("gatataaagatata", concat.alignment.get("ABCD").unwrap());
+        assert_eq!("gatataat??????", concat.alignment.get("ABCF").unwrap());
+        assert_eq!("????????agtata", concat.alignment.get("ABCE").unwrap());
+        assert_eq!(2, concat.partition.len());
+        assert_eq!("gene_4", concat.partition[1].gene);
+        assert_eq!(
+            (9, 14),
+            (concat.partition[1].start, concat.partition[1].end)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "already in the concatenated alignment")]
+    fn test_concat_supermatrix_duplicate_locus() {
+        let mut files = vec![PathBuf::from("tests/files/alignments/gene_2.nex")];
+        let supermatrix = Supermatrix {
+            alignment: PathBuf::from("tests/files/concat/supermatrix.fas"),
+            partition: PathBuf::from("tests/files/concat/supermatrix_partition.txt"),
+            part_fmt: PartitionFmt::Raxml,
+        };
+        let mut concat = Concat::new(&mut files, &InputFmt::Auto, &DNA);
+        concat.set_supermatrix(&supermatrix);
+        concat.concat_alignment_no_spinner();
+    }
+
     #[test]
     fn test_header_datatype() {
         let path = Path::new("tests/files/alignments/");
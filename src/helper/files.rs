@@ -1,9 +1,13 @@
 //! Helper functions for creating output filenames
 use std::path::{Path, PathBuf};
-use std::{fs::File, io::BufReader};
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+};
 
 use crate::helper::types::OutputFmt;
 
+use ahash::AHashMap as HashMap;
 use flate2::read::MultiGzDecoder;
 
 /// Decode gzip compressed files
@@ -14,6 +18,65 @@ pub fn decode_gzip(path: &Path) -> BufReader<MultiGzDecoder<File>> {
     BufReader::new(decoder)
 }
 
+/// Compression of an input file.
+#[derive(Debug, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    /// Blocked gzip (BGZF), such as files compressed with `bgzip`.
+    Bgzf,
+}
+
+/// Detect file compression from the magic number.
+/// BGZF files are gzip files with the `BC` extra subfield.
+/// # Example
+/// ```
+/// use std::path::Path;
+/// use segul::helper::files::{self, Compression};
+///
+/// let path = Path::new("tests/files/maf/simple.maf");
+/// assert_eq!(files::detect_compression(path), Compression::None);
+/// ```
+pub fn detect_compression(path: &Path) -> Compression {
+    let file = File::open(path).expect("Failed opening a file");
+    let mut magic = Vec::with_capacity(14);
+    file.take(14)
+        .read_to_end(&mut magic)
+        .expect("Failed reading a file");
+    if !magic.starts_with(&[0x1f, 0x8b]) {
+        return Compression::None;
+    }
+    // FEXTRA flag and the BGZF subfield identifiers
+    if magic.len() == 14 && magic[3] & 0x04 != 0 && magic[12..14] == *b"BC" {
+        Compression::Bgzf
+    } else {
+        Compression::Gzip
+    }
+}
+
+/// Panic if several inputs are written to the same output file.
+/// It happens when files share a name in different directories
+/// or exist both compressed and uncompressed.
+/// Each item is an output path and the inputs written to it.
+pub(crate) fn check_unique_outputs<I>(outputs: I)
+where
+    I: IntoIterator<Item = (PathBuf, String)>,
+{
+    let mut seen: HashMap<PathBuf, String> = HashMap::new();
+    outputs.into_iter().for_each(|(output, input)| {
+        if let Some(other) = seen.get(&output) {
+            panic!(
+                "Inputs {} and {} are both written to {}. \
+                Rename the files or process them separately.",
+                other,
+                input,
+                output.display()
+            );
+        }
+        seen.insert(output, input);
+    });
+}
+
 /// Open a file
 /// Returns a BufReader of the file
 pub fn open_file(path: &Path) -> BufReader<File> {
@@ -47,6 +110,32 @@ pub fn create_output_fname(dir: &Path, file: &Path, output_fmt: &OutputFmt) -> P
     create_output_fname_from_path(&path, output_fmt)
 }
 
+/// Combine the output directory and the input filename
+/// without the gzip extension (`.gz` or `.bgz`).
+/// Use it for outputs written as plain text from compressed inputs.
+/// # Example
+/// ```
+/// use std::path::Path;
+/// use segul::helper::files;
+///
+/// let dir = Path::new("output");
+/// let file = Path::new("chr1.maf.gz");
+/// let output = files::create_uncompressed_output_fname(&dir, &file);
+/// assert_eq!(output, Path::new("output/chr1.maf"));
+/// ```
+pub fn create_uncompressed_output_fname(dir: &Path, file: &Path) -> PathBuf {
+    let path = dir.join(
+        file.file_name()
+            .expect("Failed parsing filename for output file"),
+    );
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("gz") || ext.eq_ignore_ascii_case("bgz") => {
+            path.with_extension("")
+        }
+        _ => path,
+    }
+}
+
 pub fn create_output_fname_for_text(dir: &Path, prefix: &Path) -> PathBuf {
     dir.join(prefix.with_extension("txt"))
 }
@@ -88,4 +177,21 @@ mod tests {
             Path::new("tests/test_create_output_fname.fas")
         );
     }
+
+    #[test]
+    fn test_create_uncompressed_output_fname() {
+        let dir = Path::new("output");
+        assert_eq!(
+            create_uncompressed_output_fname(dir, Path::new("maf/chr1.maf.bgz")),
+            Path::new("output/chr1.maf")
+        );
+        assert_eq!(
+            create_uncompressed_output_fname(dir, Path::new("chr1.maf.GZ")),
+            Path::new("output/chr1.maf")
+        );
+        assert_eq!(
+            create_uncompressed_output_fname(dir, Path::new("chr1.maf")),
+            Path::new("output/chr1.maf")
+        );
+    }
 }
@@ -1,5 +1,7 @@
 //! Find input files and parse IDs from input files.
 
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::channel;
 
@@ -121,6 +123,117 @@ impl<'a> SeqReadFinder<'a> {
         walk_dir!(self, re_matches_fastq_lazy)
     }
 
+    /// Group read files into R1 and R2 pairs by their file names.
+    /// Mates are named `{sample}{sep}[R]{1|2}[{sep}{digits}].{fastq|fq}[.gz]`,
+    /// where `sep` is `_`, `-`, or `.`,
+    /// e.g. `sample_R1_001.fastq.gz` and `sample_R2_001.fastq.gz`.
+    /// Mates must be in the same directory.
+    /// Returns the pairs sorted by sample name
+    /// and the files without a mate.
+    ///
+    /// # Example
+    /// ```
+    /// use std::path::Path;
+    /// use segul::helper::finder::SeqReadFinder;
+    ///
+    /// let dir = Path::new("tests/files/raw");
+    /// let files = SeqReadFinder::new(&dir).find_recursive();
+    /// let (pairs, singles) = SeqReadFinder::find_pairs(&files);
+    /// assert_eq!(pairs.len(), 2);
+    /// assert_eq!(pairs[0].sample, "read");
+    /// assert!(singles.is_empty());
+    /// ```
+    pub fn find_pairs(files: &[PathBuf]) -> (Vec<ReadPair>, Vec<PathBuf>) {
+        let mut mates: BTreeMap<(String, PathBuf, String), [Option<&PathBuf>; 2]> = BTreeMap::new();
+        let mut singles = Vec::new();
+        files.iter().for_each(|file| {
+            let file_name = file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .expect("Failed parsing file name");
+            match parse_mate(file_name) {
+                Some((sample, mate, suffix)) => {
+                    let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+                    let key = (sample.to_string(), dir, suffix.to_string());
+                    let entry = mates.entry(key).or_default();
+                    match entry[mate] {
+                        // Keep the first file if there are duplicate mates.
+                        Some(_) => singles.push(file.to_path_buf()),
+                        None => entry[mate] = Some(file),
+                    }
+                }
+                None => singles.push(file.to_path_buf()),
+            }
+        });
+        let mut pairs = Vec::new();
+        mates
+            .into_iter()
+            .for_each(|((sample, _, _), mate)| match mate {
+                [Some(read_1), Some(read_2)] => pairs.push(ReadPair {
+                    sample,
+                    read_1: read_1.to_path_buf(),
+                    read_2: read_2.to_path_buf(),
+                }),
+                [Some(read), None] | [None, Some(read)] => singles.push(read.to_path_buf()),
+                [None, None] => (),
+            });
+        (pairs, singles)
+    }
+
+    /// Group read files of the same sample sequenced on multiple lanes.
+    /// Lanes are detected from the Illumina lane number in file names,
+    /// e.g., `sample_S1_L001_R1_001.fastq.gz`. Lane files are sorted
+    /// by their lane number. Files without a lane number are returned
+    /// separately.
+    /// # Example
+    /// ```
+    /// use std::path::PathBuf;
+    /// use segul::helper::finder::SeqReadFinder;
+    ///
+    /// let files = vec![
+    ///     PathBuf::from("s1_L002_R1_001.fastq.gz"),
+    ///     PathBuf::from("s1_L001_R1_001.fastq.gz"),
+    ///     PathBuf::from("s2_R1.fastq.gz"),
+    /// ];
+    /// let (lanes, others) = SeqReadFinder::find_lanes(&files);
+    /// assert_eq!(lanes.len(), 1);
+    /// assert_eq!(lanes[0].name, "s1_R1_001");
+    /// assert_eq!(lanes[0].files[0], PathBuf::from("s1_L001_R1_001.fastq.gz"));
+    /// assert_eq!(others.len(), 1);
+    /// ```
+    pub fn find_lanes(files: &[PathBuf]) -> (Vec<ReadLanes>, Vec<PathBuf>) {
+        let mut lanes: BTreeMap<(PathBuf, String), BTreeMap<usize, &PathBuf>> = BTreeMap::new();
+        let mut others = Vec::new();
+        files.iter().for_each(|file| {
+            let file_name = file
+                .file_name()
+                .and_then(|name| name.to_str())
+                .expect("Failed parsing file name");
+            match parse_lane(file_name) {
+                Some((sample, lane, suffix)) => {
+                    let dir = file.parent().unwrap_or(Path::new("")).to_path_buf();
+                    let key = (dir, format!("{}{}", sample, suffix));
+                    match lanes.entry(key).or_default().entry(lane) {
+                        // Keep the first file if there are duplicate lanes.
+                        Entry::Occupied(_) => others.push(file.to_path_buf()),
+                        Entry::Vacant(entry) => {
+                            entry.insert(file);
+                        }
+                    }
+                }
+                None => others.push(file.to_path_buf()),
+            }
+        });
+        let lanes = lanes
+            .into_iter()
+            .map(|((_, name), files)| ReadLanes {
+                name: read_name_stem(&name).to_string(),
+                files: files.into_values().map(|f| f.to_path_buf()).collect(),
+            })
+            .collect();
+        (lanes, others)
+    }
+
     fn raw_pattern(&mut self, input_fmt: &'a SeqReadFmt) {
         self.pattern = match input_fmt {
             SeqReadFmt::Fastq => format!("{}/*.f*q", self.dir.display()),
@@ -130,6 +243,26 @@ impl<'a> SeqReadFinder<'a> {
     }
 }
 
+/// Paired-end read files of a sample.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadPair {
+    /// Sample name parsed from the file names
+    pub sample: String,
+    /// Forward read (R1) file
+    pub read_1: PathBuf,
+    /// Reverse read (R2) file
+    pub read_2: PathBuf,
+}
+
+/// Read files of a sample sequenced on multiple lanes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReadLanes {
+    /// File name without the lane number and extensions
+    pub name: String,
+    /// Read files sorted by lane number
+    pub files: Vec<PathBuf>,
+}
+
 pub struct ContigFileFinder<'a> {
     /// Input directory.
     dir: &'a Path,
@@ -186,7 +319,7 @@ impl<'a> ContigFileFinder<'a> {
     /// assert_eq!(files.len(), 2);
     /// ```
     pub fn find_recursive(&self) -> Vec<PathBuf> {
-        walk_dir!(self, re_matches_fasta_lazy)
+        walk_dir!(self, re_matches_contig_lazy)
     }
 
     fn contig_pattern(&mut self, input_fmt: &'a ContigFmt) {
@@ -216,6 +349,7 @@ impl<'a> MafFileFinder<'a> {
     }
 
     /// Find input files for multiple alignment format.
+    /// Include gzip and BGZF compressed files (`.maf.gz` or `.maf.bgz`).
     /// Return a vector of input files.
     /// # Example
     /// ```
@@ -227,7 +361,15 @@ impl<'a> MafFileFinder<'a> {
     /// assert_eq!(files.len(), 1);
     pub fn find(&mut self) -> Vec<PathBuf> {
         self.maf_pattern();
-        let files = self.glob_files(&self.pattern);
+        let files: Vec<PathBuf> = self
+            .glob_files(&self.pattern)
+            .into_iter()
+            .filter(|f| {
+                f.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(re_matches_maf_lazy)
+            })
+            .collect();
         self.check_results(&files);
 
         files
@@ -249,7 +391,7 @@ impl<'a> MafFileFinder<'a> {
     }
 
     fn maf_pattern(&mut self) {
-        self.pattern = format!("{}/*.maf", self.dir.display());
+        self.pattern = format!("{}/*.maf*", self.dir.display());
     }
 }
 
@@ -364,6 +506,64 @@ fn re_matches_fastq_lazy(fname: &str) -> bool {
     RE.is_match(fname)
 }
 
+// Parse the sample name, the mate index (0 for R1, 1 for R2),
+// and the file name suffix after the mate number.
+fn parse_mate(fname: &str) -> Option<(&str, usize, &str)> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"(?i)^(?P<sample>.+)[._-]R?(?P<mate>[12])(?P<suffix>(?:[._-]\d+)?\.(?:fastq|fq)(?:\.gz.*)?)$"
+        )
+        .unwrap();
+    }
+
+    let captures = RE.captures(fname)?;
+    let mate = match &captures["mate"] {
+        "1" => 0,
+        _ => 1,
+    };
+    Some((
+        captures.name("sample")?.as_str(),
+        mate,
+        captures.name("suffix")?.as_str(),
+    ))
+}
+
+// Parse the sample name, the lane number,
+// and the file name suffix after the lane number.
+fn parse_lane(fname: &str) -> Option<(&str, usize, &str)> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(
+            r"(?i)^(?P<sample>.+)_L(?P<lane>\d{3})(?P<suffix>_.*\.(?:fastq|fq)(?:\.gz.*)?)$"
+        )
+        .unwrap();
+    }
+
+    let captures = RE.captures(fname)?;
+    let lane = captures["lane"].parse().ok()?;
+    Some((
+        captures.name("sample")?.as_str(),
+        lane,
+        captures.name("suffix")?.as_str(),
+    ))
+}
+
+// File name without the FASTQ and compression extensions.
+fn read_name_stem(fname: &str) -> &str {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"(?i)\.(?:fastq|fq)(?:\.gz.*)?$").unwrap();
+    }
+
+    match RE.find(fname) {
+        Some(ext) => &fname[..ext.start()],
+        None => fname,
+    }
+}
+
+// FASTA files excluding their `.fai` indexes.
+fn re_matches_contig_lazy(fname: &str) -> bool {
+    re_matches_fasta_lazy(fname) && !fname.to_ascii_lowercase().ends_with(".fai")
+}
+
 fn re_matches_fasta_lazy(fname: &str) -> bool {
     lazy_static! {
         static ref RE: Regex = Regex::new(r"(?i)(.fa*)(?:.*)").unwrap();
@@ -374,7 +574,7 @@ fn re_matches_fasta_lazy(fname: &str) -> bool {
 
 fn re_matches_maf_lazy(fname: &str) -> bool {
     lazy_static! {
-        static ref RE: Regex = Regex::new(r"(?i)(.maf)").unwrap();
+        static ref RE: Regex = Regex::new(r"(?i)(\.maf)(\.b?gz)?$").unwrap();
     }
 
     RE.is_match(fname)
@@ -517,6 +717,15 @@ mod test {
         assert_eq!(4, files.len());
     }
 
+    #[test]
+    fn test_maf_pattern() {
+        assert!(re_matches_maf_lazy("chr1.maf"));
+        assert!(re_matches_maf_lazy("chr1.MAF.gz"));
+        assert!(re_matches_maf_lazy("chr1.maf.bgz"));
+        assert!(!re_matches_maf_lazy("chr1.maf.idx"));
+        assert!(!re_matches_maf_lazy("chr1_maf.fas"));
+    }
+
     #[test]
     fn test_files_recursive() {
         input!(finder);
@@ -573,6 +782,68 @@ mod test {
         assert_eq!(3, ids.len());
     }
 
+    #[test]
+    fn test_parse_mate() {
+        assert_eq!(Some(("read", 0, ".fastq")), parse_mate("read_1.fastq"));
+        assert_eq!(
+            Some(("sample_S1_L001", 1, "_001.fastq.gz")),
+            parse_mate("sample_S1_L001_R2_001.fastq.gz")
+        );
+        assert_eq!(Some(("sample_1", 0, ".fq")), parse_mate("sample_1_1.fq"));
+        assert_eq!(Some(("sample", 1, ".fq.gz")), parse_mate("sample.r2.fq.gz"));
+        assert_eq!(None, parse_mate("sample.fastq"));
+        assert_eq!(None, parse_mate("sample_R3.fastq"));
+    }
+
+    #[test]
+    fn test_find_pairs() {
+        let files = [
+            PathBuf::from("a/s1_R1.fq"),
+            PathBuf::from("a/s1_R2.fq"),
+            PathBuf::from("b/s1_R2.fq"),
+            PathBuf::from("a/s2_R1_001.fq.gz"),
+            PathBuf::from("a/s2_R2_002.fq.gz"),
+            PathBuf::from("a/s3.fq"),
+        ];
+        let (pairs, singles) = SeqReadFinder::find_pairs(&files);
+        assert_eq!(1, pairs.len());
+        assert_eq!("s1", pairs[0].sample);
+        assert_eq!(Path::new("a/s1_R2.fq"), pairs[0].read_2);
+        assert_eq!(4, singles.len());
+    }
+
+    #[test]
+    fn test_parse_lane() {
+        assert_eq!(
+            Some(("sample_S1", 2, "_R1_001.fastq.gz")),
+            parse_lane("sample_S1_L002_R1_001.fastq.gz")
+        );
+        assert_eq!(None, parse_lane("sample_R1.fastq.gz"));
+        assert_eq!(None, parse_lane("sample_L01_R1.fastq"));
+    }
+
+    #[test]
+    fn test_find_lanes() {
+        let files = [
+            PathBuf::from("a/s1_L002_R1.fq"),
+            PathBuf::from("a/s1_L001_R1.fq"),
+            PathBuf::from("a/s1_L001_R2.fq"),
+            PathBuf::from("b/s1_L001_R1.fq"),
+            PathBuf::from("a/s2.fq"),
+        ];
+        let (lanes, others) = SeqReadFinder::find_lanes(&files);
+        assert_eq!(3, lanes.len());
+        assert_eq!("s1_R1", lanes[0].name);
+        assert_eq!(
+            vec![
+                PathBuf::from("a/s1_L001_R1.fq"),
+                PathBuf::from("a/s1_L002_R1.fq")
+            ],
+            lanes[0].files
+        );
+        assert_eq!(1, others.len());
+    }
+
     #[test]
     fn match_fastq() {
         let fname = "test.fastq";
@@ -587,6 +858,8 @@ mod test {
         assert!(re_matches_fasta_lazy(fname));
         assert!(re_matches_fasta_lazy(fname2));
         assert!(re_matches_fasta_lazy(fname3));
+        assert!(re_matches_contig_lazy(fname2));
+        assert!(!re_matches_contig_lazy("test.fa.fai"));
     }
 
     #[test]
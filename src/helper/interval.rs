@@ -0,0 +1,87 @@
+//! A static interval index for overlap queries.
+//!
+//! Intervals are 0-based and half-open, similar to BED.
+//! The index is a sorted list of intervals with the running maximum
+//! of the end positions, so a query only visits intervals
+//! that start before the query end.
+
+/// Interval index for a single sequence (e.g. a chromosome).
+pub struct IntervalIndex<T> {
+    /// Intervals sorted by the start position.
+    intervals: Vec<(usize, usize, T)>,
+    /// Maximum end position of the intervals up to each index.
+    max_ends: Vec<usize>,
+}
+
+impl<T> IntervalIndex<T> {
+    /// Build an index from (start, end, value) tuples.
+    pub fn new(mut intervals: Vec<(usize, usize, T)>) -> Self {
+        intervals.sort_by_key(|(start, end, _)| (*start, *end));
+        let mut max_end = 0;
+        let max_ends = intervals
+            .iter()
+            .map(|(_, end, _)| {
+                max_end = max_end.max(*end);
+                max_end
+            })
+            .collect();
+        Self {
+            intervals,
+            max_ends,
+        }
+    }
+
+    /// Find the values of intervals overlapping [start, end).
+    /// The values are returned in the order of the interval start positions.
+    /// # Example
+    /// ```
+    /// use segul::helper::interval::IntervalIndex;
+    ///
+    /// let index = IntervalIndex::new(vec![(10, 20, "a"), (0, 5, "b"), (15, 30, "c")]);
+    /// assert_eq!(index.find_overlaps(18, 25), vec![&"a", &"c"]);
+    /// assert_eq!(index.find_overlaps(5, 10), Vec::<&&str>::new());
+    /// ```
+    pub fn find_overlaps(&self, start: usize, end: usize) -> Vec<&T> {
+        let upper = self.intervals.partition_point(|(s, _, _)| *s < end);
+        let mut overlaps: Vec<&T> = self.intervals[..upper]
+            .iter()
+            .zip(self.max_ends[..upper].iter())
+            .rev()
+            .take_while(|(_, max_end)| **max_end > start)
+            .filter(|((_, e, _), _)| *e > start)
+            .map(|((_, _, value), _)| value)
+            .collect();
+        overlaps.reverse();
+        overlaps
+    }
+
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_overlaps() {
+        let index = IntervalIndex::new(vec![(0, 100, 0), (10, 20, 1), (30, 40, 2), (50, 60, 3)]);
+        assert_eq!(vec![&0, &2], index.find_overlaps(35, 45));
+        assert_eq!(vec![&0, &1, &2, &3], index.find_overlaps(0, 100));
+        assert_eq!(vec![&0], index.find_overlaps(99, 120));
+        assert!(index.find_overlaps(100, 120).is_empty());
+    }
+
+    #[test]
+    fn test_half_open() {
+        let index = IntervalIndex::new(vec![(10, 20, 'a')]);
+        assert!(index.find_overlaps(20, 30).is_empty());
+        assert!(index.find_overlaps(0, 10).is_empty());
+        assert_eq!(vec![&'a'], index.find_overlaps(19, 20));
+    }
+}
@@ -3,6 +3,7 @@ pub mod alphabet;
 pub mod concat;
 pub mod files;
 pub mod finder;
+pub mod interval;
 pub mod logger;
 pub mod partition;
 pub mod sequence;
@@ -111,6 +111,60 @@ pub fn infer_raw_input_auto(input: &Path) -> SeqReadFmt {
     }
 }
 
+/// Quality score encoding of FASTQ reads
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum QScoreEncoding {
+    /// Detect the encoding from the first reads
+    Auto,
+    /// Sanger and Illumina 1.8+ encoding
+    Phred33,
+    /// Illumina 1.3 to 1.7 encoding
+    Phred64,
+}
+
+impl QScoreEncoding {
+    /// ASCII offset of the encoding.
+    /// Returns `None` for Auto, because it has no offset
+    /// until the encoding is detected.
+    /// # Example
+    /// ```
+    /// use segul::helper::types::QScoreEncoding;
+    ///
+    /// assert_eq!(QScoreEncoding::Phred33.offset(), Some(33));
+    /// assert_eq!(QScoreEncoding::Auto.offset(), None);
+    /// ```
+    pub fn offset(&self) -> Option<u8> {
+        match self {
+            Self::Phred33 => Some(33),
+            Self::Phred64 => Some(64),
+            Self::Auto => None,
+        }
+    }
+}
+
+impl std::fmt::Display for QScoreEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Phred33 => write!(f, "phred33"),
+            Self::Phred64 => write!(f, "phred64"),
+        }
+    }
+}
+
+impl std::str::FromStr for QScoreEncoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "phred33" => Ok(Self::Phred33),
+            "phred64" => Ok(Self::Phred64),
+            _ => Err(format!("{} is not a valid quality score encoding", s)),
+        }
+    }
+}
+
 /// Data type for contig sequences
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ContigFmt {
@@ -318,19 +372,14 @@ impl std::str::FromStr for PartitionFmt {
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
 pub enum DnaStrand {
     Forward,
     Reverse,
+    #[default]
     Missing,
 }
 
-impl Default for DnaStrand {
-    fn default() -> Self {
-        Self::Missing
-    }
-}
-
 impl std::fmt::Display for DnaStrand {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -587,6 +636,44 @@ impl std::str::FromStr for SummaryMode {
     }
 }
 
+/// Raw read format conversions
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ReadConvertMode {
+    /// Convert FASTQ to FASTA
+    Fasta,
+    /// Interleave paired-end files into a single file
+    Interleave,
+    /// Split interleaved files into R1 and R2 files
+    Deinterleave,
+    /// Merge files of the same sample sequenced on multiple lanes
+    MergeLanes,
+}
+
+impl std::fmt::Display for ReadConvertMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Fasta => write!(f, "fasta"),
+            Self::Interleave => write!(f, "interleave"),
+            Self::Deinterleave => write!(f, "deinterleave"),
+            Self::MergeLanes => write!(f, "merge-lanes"),
+        }
+    }
+}
+
+impl std::str::FromStr for ReadConvertMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fasta" => Ok(Self::Fasta),
+            "interleave" => Ok(Self::Interleave),
+            "deinterleave" => Ok(Self::Deinterleave),
+            "merge-lanes" => Ok(Self::MergeLanes),
+            _ => Err(format!("{} is not a valid read conversion mode", s)),
+        }
+    }
+}
+
 pub enum SummaryOutput {
     /// Print summary to stdout
     Stdout,
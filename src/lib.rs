@@ -6,7 +6,7 @@
 //! 2. Alignment conversion
 //! 3. Alignment filtering
 //! 4. Alignment partition conversion
-//! 5. Alignment splitting
+//! 5. Alignment splitting and slicing
 //! 6. Alignment summary statistics
 //! 7. Unalign alignments
 //! 7. Contiguous sequence summary statistics
@@ -18,6 +18,21 @@
 //! 13. Sequence removal
 //! 14. Sequence translation
 //! 15. Sequence unique ID parsing
+//! 16. Sequence outlier detection
+//! 17. Sequence consensus
+//! 18. Sequence representative sample selection
+//! 19. Alignment reference coordinate mapping
+//! 20. MAF block stitching
+//! 21. MAF summary, filtering, and species projection
+//! 22. MAF writing, species renaming, and alignment to MAF conversion
+//! 23. Compressed MAF input and MAF block indexing
+//! 24. Raw read quality filtering and trimming
+//! 25. Raw read subsampling and normalization
+//! 26. Raw read format conversion, interleaving, and lane merging
+//! 27. Contig filtering and renaming
+//! 28. Extended contig metrics and cumulative length tables
+//! 29. Contig indexing and region extraction
+//! 30. Bait design from alignments
 //!
 //! ## Example
 //!
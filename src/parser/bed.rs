@@ -147,6 +147,15 @@ impl<'a> BedParser<'a> {
                 line.retain(|&c| c != CAR_RETURN);
             }
 
+            // Remove the line ending so that the last column
+            // is not followed by an empty field
+            while line.last().is_some_and(|c| c.is_ascii_whitespace()) {
+                line.pop();
+            }
+            if line.is_empty() {
+                continue;
+            }
+
             let record = BedRecord::from_vec_bytes(&line)
                 .map_err(|e| format!("Failed to parse BED record: {}", e))?;
             bed.push(record);
@@ -175,6 +184,17 @@ mod tests {
         assert_eq!(records[0].strand, Some(DnaStrand::Forward));
     }
 
+    #[test]
+    fn test_bed_four_columns() {
+        let bed_file = PathBuf::from("tests/files/slice/regions.bed");
+        let mut parser = BedParser::new(&bed_file);
+        let records = parser.parse().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].chrom_start, 6);
+        assert_eq!(records[1].name, Some("gene2".to_string()));
+        assert_eq!(records[1].score, None);
+    }
+
     #[test]
     fn test_bed_with_header() {
         let bed_file = PathBuf::from("tests/files/bed/with_header.bed");
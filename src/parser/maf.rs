@@ -24,10 +24,15 @@
 use std::{
     error::Error,
     fmt::Display,
-    io::{prelude::*, BufReader},
+    fs::File,
+    io::{prelude::*, BufReader, SeekFrom},
+    path::Path,
     str::FromStr,
 };
 
+use flate2::read::MultiGzDecoder;
+use noodles::bgzf;
+
 use nom::{
     bytes::complete,
     character,
@@ -35,6 +40,7 @@ use nom::{
     sequence, IResult,
 };
 
+use crate::helper::files::{self, Compression};
 use crate::helper::types::DnaStrand;
 
 use super::{END_OF_LINE, EOF};
@@ -289,15 +295,17 @@ impl FromStr for MafVisibility {
 /// It contains:
 /// - `score`: the score of the alignment. At the the first line
 /// - `sequences`: the `s` lines in the alignment.
+/// - `qualities`: the `q` lines in the alignment. It is optional.
 /// - `information`: the `i` lines in the alignment. It is optional.
-/// - `quality`: the `q` lines in the alignment. It is optional.
+/// - `empty_lines`: the `e` lines in the alignment. It is optional.
 ///
+/// The `q` and `i` lines refer to the `s` line with the same source.
 pub struct MafAlignment {
     pub score: Option<f64>,
     pub sequences: Vec<MafSequence>,
-    pub quality: Option<Quality>,
-    pub information: Option<MafInformation>,
-    pub empty: Option<MafEmptyLine>,
+    pub qualities: Vec<Quality>,
+    pub information: Vec<MafInformation>,
+    pub empty_lines: Vec<MafEmptyLine>,
 }
 
 impl Default for MafAlignment {
@@ -311,9 +319,9 @@ impl MafAlignment {
         MafAlignment {
             score: None,
             sequences: Vec::new(),
-            quality: None,
-            information: None,
-            empty: None,
+            qualities: Vec::new(),
+            information: Vec::new(),
+            empty_lines: Vec::new(),
         }
     }
 
@@ -383,14 +391,14 @@ impl MafSequence {
 /// It contains:
 /// - `src`: the source of the information.
 /// - `leftStatus`: a character that specifies the relationship between the
-///     sequence in the current block and the sequence in the previous block.
+///   sequence in the current block and the sequence in the previous block.
 /// - `leftCount`: the number of bases in the aligning species between the start and
-///     the end of the previous block.
+///   the end of the previous block.
 /// - `rightStatus`: a character that specifies the relationship between the
-///     sequence in the current block and the sequence in the subsequent block.
+///   sequence in the current block and the sequence in the subsequent block.
 /// - `leftValues`: the values of the information before the block of sequences.
 /// - `rightCount`: the number of bases in the aligning species between the start of the
-///    current block and the start of the next block.
+///   current block and the start of the next block.
 pub struct MafInformation {
     pub source: String,
     pub left_status: char,
@@ -401,7 +409,9 @@ pub struct MafInformation {
 
 impl MafInformation {
     pub fn from_buf(line: &[u8]) -> Result<Self, Box<dyn Error>> {
-        let mut parts = line.split(|b| b.is_ascii_whitespace());
+        let mut parts = line
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|b| !b.is_empty());
 
         let _ = parts.next(); // Skip the first character
 
@@ -432,26 +442,40 @@ impl MafInformation {
 }
 
 /// This is the `e` line in MAF format.
+/// It describes a sequence that does not align to the block,
+/// but has bases on both sides of the block.
+/// It contains:
+/// - `src`: the source of the sequence.
+/// - `start`, `size`, `strand`, `srcSize`: the same as the `s` line.
+/// - `status`: a character that specifies the relationship between
+///   the blocks on both sides.
 pub struct MafEmptyLine {
     pub source: String,
+    pub start: u64,
     pub size: u64,
     pub strand: char,
     pub src_size: u64,
+    pub status: char,
 }
 
 impl MafEmptyLine {
     pub fn from_buf(line: &[u8]) -> Result<Self, Box<dyn Error>> {
-        let mut parts = line.split(|b| b.is_ascii_whitespace());
+        let mut parts = line
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|b| !b.is_empty());
 
         let _ = parts.next(); // Skip the first character
 
         let source = parts.next().unwrap_or_default();
+        let start = parts.next().unwrap_or_default();
         let size = parts.next().unwrap_or_default();
         let strand = parts.next().unwrap_or_default();
         let src_size = parts.next().unwrap_or_default();
+        let status = parts.next().unwrap_or_default();
 
         Ok(MafEmptyLine {
             source: String::from_utf8_lossy(source).to_string(),
+            start: String::from_utf8_lossy(start).parse().unwrap_or_default(),
             size: String::from_utf8_lossy(size).parse().unwrap_or_default(),
             strand: String::from_utf8_lossy(strand)
                 .chars()
@@ -460,6 +484,10 @@ impl MafEmptyLine {
             src_size: String::from_utf8_lossy(src_size)
                 .parse()
                 .unwrap_or_default(),
+            status: String::from_utf8_lossy(status)
+                .chars()
+                .next()
+                .unwrap_or_default(),
         })
     }
 }
@@ -474,7 +502,9 @@ pub struct Quality {
 
 impl Quality {
     pub fn from_buf(line: &[u8]) -> Result<Self, Box<dyn Error>> {
-        let mut parts = line.split(|b| b.is_ascii_whitespace());
+        let mut parts = line
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|b| !b.is_empty());
 
         let _ = parts.next(); // Skip the first character
 
@@ -488,6 +518,60 @@ impl Quality {
     }
 }
 
+/// MAF input from plain, gzip, or BGZF compressed files.
+/// The compression is detected from the file content.
+///
+/// Plain and BGZF inputs are seekable. The seek offset is the byte offset
+/// for plain files and the BGZF virtual position for BGZF files.
+/// Only `SeekFrom::Start` is supported.
+pub enum MafInput {
+    Plain(File),
+    Gzip(Box<MultiGzDecoder<File>>),
+    Bgzf(bgzf::io::Reader<File>),
+}
+
+impl MafInput {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let compression = files::detect_compression(path);
+        let file = File::open(path)?;
+        let input = match compression {
+            Compression::None => MafInput::Plain(file),
+            Compression::Gzip => MafInput::Gzip(Box::new(MultiGzDecoder::new(file))),
+            Compression::Bgzf => MafInput::Bgzf(bgzf::io::Reader::new(file)),
+        };
+        Ok(input)
+    }
+
+    pub fn is_seekable(&self) -> bool {
+        !matches!(self, MafInput::Gzip(_))
+    }
+}
+
+impl Read for MafInput {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            MafInput::Plain(file) => file.read(buf),
+            MafInput::Gzip(decoder) => decoder.read(buf),
+            MafInput::Bgzf(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for MafInput {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match (self, pos) {
+            (MafInput::Plain(file), pos) => file.seek(pos),
+            (MafInput::Bgzf(reader), SeekFrom::Start(offset)) => reader
+                .seek(bgzf::VirtualPosition::from(offset))
+                .map(u64::from),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "Seeking is only supported from the start of plain or BGZF files",
+            )),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MafReader<R> {
     pub reader: BufReader<R>,
@@ -591,15 +675,15 @@ impl<R: Read> MafReader<R> {
                 }
                 b'q' => {
                     let quality = Quality::from_buf(&self.buf).unwrap();
-                    alignment.quality = Some(quality);
+                    alignment.qualities.push(quality);
                 }
                 b'i' => {
                     let information = MafInformation::from_buf(&self.buf).unwrap();
-                    alignment.information = Some(information);
+                    alignment.information.push(information);
                 }
                 b'e' => {
                     let empty = MafEmptyLine::from_buf(&self.buf).unwrap();
-                    alignment.empty = Some(empty);
+                    alignment.empty_lines.push(empty);
                 }
                 END_OF_LINE | b' ' => break,
 
@@ -614,6 +698,24 @@ impl<R: Read> MafReader<R> {
     }
 }
 
+impl MafReader<MafInput> {
+    /// Open a plain, gzip, or BGZF compressed MAF file.
+    pub fn from_path(path: &Path) -> Self {
+        let input = MafInput::open(path).expect("Unable to open file");
+        MafReader::new(input)
+    }
+}
+
+impl<R: Read + Seek> MafReader<R> {
+    /// Move the reader to the start of a paragraph,
+    /// e.g. an alignment block offset in a MAF index.
+    pub fn seek_paragraph(&mut self, offset: u64) -> std::io::Result<()> {
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
 impl<R: Read> Iterator for MafReader<R> {
     type Item = MafParagraph;
 
@@ -702,7 +804,7 @@ mod tests {
         let tags = track.capture_tags(line, "mafDot=").unwrap();
         let maf_dot = track.parse_maf_dot(&tags).unwrap();
         let (_, res) = maf_dot;
-        assert_eq!(res, true);
+        assert!(res);
     }
 
     #[test]
@@ -757,6 +859,30 @@ mod tests {
         assert_eq!(alignments[0].score, Some(23262.0));
     }
 
+    #[test]
+    fn test_parse_block_lines() {
+        let block = "a score=5\n\
+            s hg18.chr1    100 10 + 1000 ACGT-ACGTAC\n\
+            s panTro1.chr6 200 10 + 2000 ACGTTAC-GTA\n\
+            q panTro1.chr6               9999999-999\n\
+            i panTro1.chr6 C 0 I 12\n\
+            e mm4.chr6 53310102 13316 + 151104725 I\n\n";
+        let mut reader = MafReader::new(block.as_bytes());
+        let alignment = match reader.next() {
+            Some(MafParagraph::Alignment(alignment)) => alignment,
+            _ => panic!("Expected an alignment block"),
+        };
+        assert_eq!(alignment.sequences.len(), 2);
+        assert_eq!(alignment.qualities[0].values.len(), 11);
+        assert_eq!(alignment.information[0].right_status, 'I');
+        assert_eq!(alignment.information[0].right_count, 12);
+        let empty = &alignment.empty_lines[0];
+        assert_eq!(empty.start, 53310102);
+        assert_eq!(empty.size, 13316);
+        assert_eq!(empty.src_size, 151104725);
+        assert_eq!(empty.status, 'I');
+    }
+
     #[test]
     fn test_capture_tags() {
         let line = "track name=chr1 description=\"Human chromosome\" visibility=pack";
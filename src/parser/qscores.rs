@@ -1,33 +1,93 @@
-//! Parse Illumina 1.8+ and Sanger quality scores
+//! Parse Phred+33 (Illumina 1.8+ and Sanger) and Phred+64 (Illumina 1.3 to 1.7) quality scores
+use crate::helper::types::QScoreEncoding;
+
+/// The highest printable ASCII character in quality strings.
+const MAX_ASCII: u8 = b'~';
+/// Characters below this value only appear in Phred+33 encoding.
+const PHRED64_MIN_ASCII: u8 = b';';
+/// Characters above this value are beyond Phred+33 Illumina scores (Q42).
+const PHRED33_MAX_ASCII: u8 = b'K';
+
 pub struct QScoreParser<'a> {
     /// Quality scores in ASCII format
     pub scores: &'a [u8],
+    /// ASCII offset of the quality score encoding
+    offset: u8,
     /// Index of the current quality score
     index: usize,
 }
 
 impl<'a> QScoreParser<'a> {
-    /// Create a new QScoreParser
-    pub fn new(scores: &'a [u8]) -> Self {
-        Self { scores, index: 0 }
+    /// Create a new QScoreParser.
+    /// Auto encoding is detected from the given scores.
+    pub fn new(scores: &'a [u8], encoding: &QScoreEncoding) -> Self {
+        let encoding = match encoding {
+            QScoreEncoding::Auto => {
+                let mut detector = QScoreEncodingDetector::new();
+                detector.update(scores);
+                detector.encoding()
+            }
+            encoding => *encoding,
+        };
+        Self {
+            scores,
+            offset: encoding
+                .offset()
+                .expect("Quality score encoding is not detected"),
+            index: 0,
+        }
     }
 }
 
 impl Iterator for QScoreParser<'_> {
     type Item = Option<u8>;
-    /// Read ASCII from vector bytes
-    /// and convert to Illumina 1.8+ and Sanger quality scores
+    /// Read ASCII from vector bytes and convert to quality scores.
+    /// Characters outside the encoding range return `None`.
     fn next(&mut self) -> Option<Self::Item> {
-        let q = self.scores.get(self.index);
-        match q {
-            Some(q) => {
-                if q > &74 {
-                    panic!("Invalid quality score: {}", q);
-                }
-                self.index += 1;
-                Some(Some(q - 33))
+        let q = self.scores.get(self.index)?;
+        self.index += 1;
+        if *q < self.offset || *q > MAX_ASCII {
+            return Some(None);
+        }
+        Some(Some(q - self.offset))
+    }
+}
+
+/// Detect quality score encoding from ASCII quality scores.
+///
+/// Scores below `;` are only found in Phred+33 encoding.
+/// Otherwise, scores above `K` are only found in Phred+64 encoding.
+/// Quality scores that fit in both encodings are assumed to be Phred+33.
+#[derive(Debug, Default)]
+pub struct QScoreEncodingDetector {
+    min: Option<u8>,
+    max: Option<u8>,
+}
+
+impl QScoreEncodingDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add quality scores of a read.
+    pub fn update(&mut self, scores: &[u8]) {
+        scores.iter().for_each(|&q| {
+            self.min = Some(self.min.map_or(q, |min| min.min(q)));
+            self.max = Some(self.max.map_or(q, |max| max.max(q)));
+        });
+    }
+
+    /// Returns true if the scores are only found in Phred+33 encoding.
+    pub fn is_phred33(&self) -> bool {
+        self.min.is_some_and(|min| min < PHRED64_MIN_ASCII)
+    }
+
+    pub fn encoding(&self) -> QScoreEncoding {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min >= PHRED64_MIN_ASCII && max > PHRED33_MAX_ASCII => {
+                QScoreEncoding::Phred64
             }
-            None => None, // End iterator when index is out of bound
+            _ => QScoreEncoding::Phred33,
         }
     }
 }
@@ -37,8 +97,8 @@ mod test {
     use super::*;
 
     macro_rules! qscore_parser {
-        ($scores:expr_2021, $sum: ident) => {
-            let records = QScoreParser::new($scores);
+        ($scores:expr_2021, $encoding: expr_2021, $sum: ident) => {
+            let records = QScoreParser::new($scores, $encoding);
             let $sum: u8 = records
                 .into_iter()
                 .map(|x| match x {
@@ -53,30 +113,63 @@ mod test {
     fn test_qscore_parser() {
         let scores = b"II";
         let scores_2 = b"00";
-        qscore_parser!(scores, sum);
-        qscore_parser!(scores_2, sum2);
+        qscore_parser!(scores, &QScoreEncoding::Phred33, sum);
+        qscore_parser!(scores_2, &QScoreEncoding::Phred33, sum2);
         assert_eq!(80, sum);
         assert_eq!(30, sum2);
     }
 
     #[test]
-    #[should_panic(expected = "Invalid quality score: 75")]
-    fn test_qscore_parser_panic() {
+    fn test_qscore_parser_phred64() {
+        let scores = b"hh";
+        let scores_2 = b"@B";
+        qscore_parser!(scores, &QScoreEncoding::Phred64, sum);
+        qscore_parser!(scores_2, &QScoreEncoding::Phred64, sum2);
+        assert_eq!(80, sum);
+        assert_eq!(2, sum2);
+    }
+
+    #[test]
+    fn test_qscore_parser_invalid() {
         let scores = b"II!)K";
-        let q = QScoreParser::new(scores);
-        q.into_iter().for_each(|x| match x {
-            Some(x) => println!("{}", x),
-            None => println!("None"),
-        });
+        let q: Vec<Option<u8>> = QScoreParser::new(scores, &QScoreEncoding::Phred64).collect();
+        assert_eq!(vec![Some(9), Some(9), None, None, Some(11)], q);
+        let q: Vec<Option<u8>> = QScoreParser::new(b"I\x7f", &QScoreEncoding::Phred33).collect();
+        assert_eq!(vec![Some(40), None], q);
+    }
+
+    #[test]
+    fn test_qscore_parser_auto() {
+        let q: Vec<Option<u8>> = QScoreParser::new(b"hB", &QScoreEncoding::Auto).collect();
+        assert_eq!(vec![Some(40), Some(2)], q);
+        let q: Vec<Option<u8>> = QScoreParser::new(b"I#", &QScoreEncoding::Auto).collect();
+        assert_eq!(vec![Some(40), Some(2)], q);
     }
 
     #[test]
     fn test_iter_empty() {
         let scores = b"";
-        let q = QScoreParser::new(scores);
-        q.into_iter().for_each(|x| match x {
-            Some(x) => println!("{}", x),
-            None => println!("None"),
-        });
+        let q = QScoreParser::new(scores, &QScoreEncoding::Phred33);
+        assert_eq!(0, q.count());
+    }
+
+    #[test]
+    fn test_detect_encoding() {
+        let mut detector = QScoreEncodingDetector::new();
+        detector.update(b"hhhhhhdd");
+        assert_eq!(QScoreEncoding::Phred64, detector.encoding());
+        detector.update(b"IIIIIGIC");
+        assert_eq!(QScoreEncoding::Phred64, detector.encoding());
+        detector.update(b"II#");
+        assert!(detector.is_phred33());
+        assert_eq!(QScoreEncoding::Phred33, detector.encoding());
+        // High quality Phred+33 scores fit in both encodings.
+        let mut detector = QScoreEncodingDetector::new();
+        detector.update(b"FFFF:F");
+        assert_eq!(QScoreEncoding::Phred33, detector.encoding());
+        assert_eq!(
+            QScoreEncoding::Phred33,
+            QScoreEncodingDetector::new().encoding()
+        );
     }
 }
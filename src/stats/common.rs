@@ -81,7 +81,7 @@ impl CommonStats {
         let n = sorted_vec.len();
         let midpoint = n / 2;
 
-        if n % 2 == 0 {
+        if n.is_multiple_of(2) {
             self.median = (sorted_vec[midpoint - 1] + sorted_vec[midpoint]) as f64 / 2.0;
         } else {
             self.median = sorted_vec[midpoint] as f64;
@@ -178,6 +178,10 @@ impl StreamStats {
 /// N50 is the length of the shortest contig at 50% of the total length
 /// N75 is the length of the shortest contig at 75% of the total length
 /// N90 is the length of the shortest contig at 90% of the total length
+/// L50, L75, and L90 are the number of contigs at the same thresholds.
+/// NG50 uses 50% of the expected genome size instead of the total length.
+/// auN is the area under the Nx curve, i.e., the sum of squared lengths
+/// divided by the total length.
 ///
 /// # Example
 /// ```
@@ -187,17 +191,29 @@ impl StreamStats {
 /// let contigs = vec![2, 3, 4, 5, 6, 7, 8, 9, 10];
 /// let total_len = contigs.iter().sum::<usize>();
 /// let mut nstats = NStats::new();
+/// nstats.set_genome_size(100);
 /// nstats.calculate(&contigs, total_len);
 ///
 /// assert_eq!(8, nstats.n50);
 /// assert_eq!(6, nstats.n75);
 /// assert_eq!(4, nstats.n90);
+/// assert_eq!(3, nstats.l50);
+/// assert_eq!(Some(3), nstats.ng50);
 /// ```
 pub struct NStats {
     pub n50: usize,
     pub n75: usize,
     pub n90: usize,
+    pub l50: usize,
+    pub l75: usize,
+    pub l90: usize,
+    /// NG50. None if the genome size is not set
+    /// or the total length is less than half of the genome size.
+    pub ng50: Option<usize>,
+    /// Area under the Nx curve
+    pub aun: f64,
     sum: usize,
+    genome_size: Option<usize>,
 }
 
 impl Default for NStats {
@@ -212,10 +228,21 @@ impl NStats {
             n50: 0,
             n75: 0,
             n90: 0,
+            l50: 0,
+            l75: 0,
+            l90: 0,
+            ng50: None,
+            aun: 0.0,
             sum: 0,
+            genome_size: None,
         }
     }
 
+    /// Set the expected genome size to calculate NG50.
+    pub fn set_genome_size(&mut self, genome_size: usize) {
+        self.genome_size = Some(genome_size);
+    }
+
     pub fn calculate(&mut self, contigs: &[usize], total_len: usize) {
         self.sum = total_len;
         let sorted_contig = self.sort_vec_desc(contigs);
@@ -223,24 +250,47 @@ impl NStats {
         self.n50(&sorted_contig, &csum);
         self.n75(&sorted_contig, &csum);
         self.n90(&sorted_contig, &csum);
+        self.ng50(&sorted_contig, &csum);
+        self.aun(&sorted_contig);
     }
 
     fn n50(&mut self, sorted_contigs: &[usize], csum: &[usize]) {
         let n50_len = self.n_len(0.5);
         let idx = self.n_idx(n50_len, csum);
         self.n50 = sorted_contigs[idx];
+        self.l50 = idx + 1;
     }
 
     fn n75(&mut self, sorted_contigs: &[usize], csum: &[usize]) {
         let n75_len = self.n_len(0.75);
         let idx = self.n_idx(n75_len, csum);
         self.n75 = sorted_contigs[idx];
+        self.l75 = idx + 1;
     }
 
     fn n90(&mut self, sorted_contigs: &[usize], csum: &[usize]) {
         let n90_len = self.n_len(0.9);
         let idx = self.n_idx(n90_len, csum);
         self.n90 = sorted_contigs[idx];
+        self.l90 = idx + 1;
+    }
+
+    fn ng50(&mut self, sorted_contigs: &[usize], csum: &[usize]) {
+        if let Some(genome_size) = self.genome_size {
+            let ng50_len = (genome_size as f64 * 0.5) as usize;
+            self.ng50 = csum
+                .iter()
+                .position(|i| *i >= ng50_len)
+                .map(|idx| sorted_contigs[idx]);
+        }
+    }
+
+    fn aun(&mut self, sorted_contigs: &[usize]) {
+        if self.sum == 0 {
+            return;
+        }
+        let squared_sum: f64 = sorted_contigs.iter().map(|c| (*c as f64).powi(2)).sum();
+        self.aun = squared_sum / self.sum as f64;
     }
 
     fn sort_vec_desc(&self, vec: &[usize]) -> Vec<usize> {
@@ -331,5 +381,24 @@ mod test {
         assert_eq!(8, seq.n50);
         assert_eq!(6, seq.n75);
         assert_eq!(4, seq.n90);
+        assert_eq!(3, seq.l50);
+        assert_eq!(5, seq.l75);
+        assert_eq!(7, seq.l90);
+        assert_eq!(None, seq.ng50);
+    }
+
+    #[test]
+    fn ng50_aun_test() {
+        let contigs = vec![2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let sum = contigs.iter().sum::<usize>();
+        let mut seq = NStats::new();
+        seq.set_genome_size(60);
+        seq.calculate(&contigs, sum);
+        assert_eq!(Some(7), seq.ng50);
+        assert_approx_eq!(384.0 / 54.0, seq.aun, 6f64);
+        // Assembly shorter than half of the genome
+        seq.set_genome_size(200);
+        seq.calculate(&contigs, sum);
+        assert_eq!(None, seq.ng50);
     }
 }
@@ -1,16 +1,98 @@
 //! Statistics for contigs
-use std::{io::BufRead, path::Path};
+use std::{cmp::Reverse, io::BufRead, path::Path};
 
 use crate::{
     helper::{
         files,
-        types::{infer_contig_fmt_auto, ContigFmt},
+        types::{ContigFmt, infer_contig_fmt_auto},
     },
     stats::common::{CommonStats, NStats},
 };
 
 use crate::parser::fasta::FastaReader;
 
+/// Default length thresholds to count contigs
+pub const DEFAULT_LENGTH_THRESHOLDS: [usize; 3] = [750, 1000, 1500];
+
+/// Contig summary parameters
+#[derive(Debug, Clone)]
+pub struct ContigSummaryParameters {
+    /// Count contigs longer than each threshold
+    pub thresholds: Vec<usize>,
+    /// Expected genome size to calculate NG50
+    pub genome_size: Option<usize>,
+    /// Keep statistics of each contig
+    pub per_contig: bool,
+}
+
+impl Default for ContigSummaryParameters {
+    fn default() -> Self {
+        Self {
+            thresholds: DEFAULT_LENGTH_THRESHOLDS.to_vec(),
+            genome_size: None,
+            per_contig: false,
+        }
+    }
+}
+
+/// Statistics of a single contig
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContigStats {
+    /// Contig name without the description
+    pub name: String,
+    pub length: usize,
+    /// Proportion of G and C in the contig
+    pub gc_content: f64,
+    pub n_count: usize,
+    /// Length of the longest run of N
+    pub longest_n_run: usize,
+    /// Proportion of lowercase (soft-masked) bases
+    pub soft_masked: f64,
+}
+
+impl ContigStats {
+    pub fn new(id: &str, seq: &str) -> Self {
+        let mut gc_count = 0;
+        let mut n_count = 0;
+        let mut n_run = 0;
+        let mut longest_n_run = 0;
+        let mut lowercase = 0;
+        seq.bytes().for_each(|base| {
+            if base.is_ascii_lowercase() {
+                lowercase += 1;
+            }
+            match base {
+                b'N' | b'n' => {
+                    n_count += 1;
+                    n_run += 1;
+                    longest_n_run = longest_n_run.max(n_run);
+                }
+                b'G' | b'g' | b'C' | b'c' => {
+                    gc_count += 1;
+                    n_run = 0;
+                }
+                _ => n_run = 0,
+            }
+        });
+        let length = seq.len();
+        let proportion = |count: usize| {
+            if length == 0 {
+                0.0
+            } else {
+                count as f64 / length as f64
+            }
+        };
+        Self {
+            name: id.split_whitespace().next().unwrap_or_default().to_string(),
+            length,
+            gc_content: proportion(gc_count),
+            n_count,
+            longest_n_run,
+            soft_masked: proportion(lowercase),
+        }
+    }
+}
+
 /// Summary statistics for contigs
 pub struct ContigSummary {
     /// Path to the file
@@ -44,12 +126,18 @@ pub struct ContigSummary {
     /// Common stat for contigs
     /// Mean, median, min, max, and stdev
     pub stats: CommonStats,
-    /// Number of contigs > 750 bp
-    pub contig750: usize,
-    /// Number of contigs > 1000 bp
-    pub contig1000: usize,
-    /// Number of contigs > 1500 bp
-    pub contig1500: usize,
+    /// Number of contigs longer than each length threshold
+    /// (threshold, count)
+    pub length_bins: Vec<(usize, usize)>,
+    /// Contig lengths sorted in descending order
+    pub sorted_lengths: Vec<usize>,
+    /// Statistics of each contig in the input order.
+    /// Only collected if `per_contig` is set.
+    pub per_contig: Vec<ContigStats>,
+    /// Length thresholds to count contigs
+    thresholds: Vec<usize>,
+    /// Keep statistics of each contig
+    keep_per_contig: bool,
 }
 
 impl Default for ContigSummary {
@@ -60,6 +148,14 @@ impl Default for ContigSummary {
 
 impl ContigSummary {
     pub fn new() -> Self {
+        Self::with_parameters(&ContigSummaryParameters::default())
+    }
+
+    pub fn with_parameters(params: &ContigSummaryParameters) -> Self {
+        let mut nstats = NStats::new();
+        if let Some(genome_size) = params.genome_size {
+            nstats.set_genome_size(genome_size);
+        }
         Self {
             file_path: String::new(),
             contig_name: String::new(),
@@ -74,11 +170,13 @@ impl ContigSummary {
             unknown: 0,
             gc_content: 0.0,
             at_content: 0.0,
-            nstats: NStats::new(),
+            nstats,
             stats: CommonStats::new(),
-            contig750: 0,
-            contig1000: 0,
-            contig1500: 0,
+            length_bins: Vec::new(),
+            sorted_lengths: Vec::new(),
+            per_contig: Vec::new(),
+            thresholds: params.thresholds.clone(),
+            keep_per_contig: params.per_contig,
         }
     }
 
@@ -95,20 +193,8 @@ impl ContigSummary {
     }
 
     fn parse_file(&mut self, path: &Path, file_fmt: &ContigFmt) -> Vec<usize> {
-        match file_fmt {
-            ContigFmt::Fasta => {
-                let mut buff = files::open_file(path);
-                self.count(&mut buff)
-            }
-            ContigFmt::Gzip => {
-                let mut buff = files::decode_gzip(path);
-                self.count(&mut buff)
-            }
-            ContigFmt::Auto => {
-                let file_fmt = infer_contig_fmt_auto(path);
-                self.parse_file(path, &file_fmt)
-            }
-        }
+        let mut buff = open_contigs(path, file_fmt);
+        self.count(&mut buff)
     }
 
     fn count<R: BufRead>(&mut self, buff: &mut R) -> Vec<usize> {
@@ -116,6 +202,9 @@ impl ContigSummary {
         let mut contigs = Vec::new();
         reader.into_iter().for_each(|r| {
             contigs.push(r.seq.len());
+            if self.keep_per_contig {
+                self.per_contig.push(ContigStats::new(&r.id, &r.seq));
+            }
             r.seq.bytes().for_each(|s| match s {
                 b'G' | b'g' => self.g_count += 1,
                 b'C' | b'c' => self.c_count += 1,
@@ -136,9 +225,8 @@ impl ContigSummary {
         self.count_nucleotide();
         self.calculate_gc_content();
         self.calculate_at_content();
-        self.count_contig750(contigs);
-        self.count_contig1000(contigs);
-        self.count_contig1500(contigs);
+        self.count_length_bins(contigs);
+        self.sort_lengths(contigs);
     }
 
     fn count_contigs(&mut self, contigs: &[usize]) {
@@ -169,16 +257,31 @@ impl ContigSummary {
         self.at_content = (self.a_count + self.t_count) as f64 / self.base_count as f64;
     }
 
-    fn count_contig750(&mut self, contigs: &[usize]) {
-        self.contig750 = contigs.iter().filter(|&c| *c > 750).count();
+    fn count_length_bins(&mut self, contigs: &[usize]) {
+        self.length_bins = self
+            .thresholds
+            .iter()
+            .map(|&t| (t, contigs.iter().filter(|&c| *c > t).count()))
+            .collect();
     }
 
-    fn count_contig1000(&mut self, contigs: &[usize]) {
-        self.contig1000 = contigs.iter().filter(|&c| *c > 1000).count();
+    fn sort_lengths(&mut self, contigs: &[usize]) {
+        self.sorted_lengths = contigs.to_vec();
+        self.sorted_lengths.sort_by_key(|c| Reverse(*c));
     }
+}
 
-    fn count_contig1500(&mut self, contigs: &[usize]) {
-        self.contig1500 = contigs.iter().filter(|&c| *c > 1500).count();
+/// Open a plain or gzip compressed contig file.
+/// In Auto, the format is inferred from the file extension.
+pub fn open_contigs(path: &Path, file_fmt: &ContigFmt) -> Box<dyn BufRead> {
+    let input_fmt = match file_fmt {
+        ContigFmt::Auto => infer_contig_fmt_auto(path),
+        _ => *file_fmt,
+    };
+    match input_fmt {
+        ContigFmt::Fasta => Box::new(files::open_file(path)),
+        ContigFmt::Gzip => Box::new(files::decode_gzip(path)),
+        ContigFmt::Auto => unreachable!("Unsupported input format"),
     }
 }
 
@@ -208,8 +311,36 @@ mod test {
         assert_eq!(parser.stats.max, 258);
         assert_eq!(parser.stats.mean, 242.5);
         assert_eq!(parser.stats.median, 242.5);
-        assert_eq!(parser.contig1000, 0);
-        assert_eq!(parser.contig1500, 0);
-        assert_eq!(parser.contig750, 0);
+        assert_eq!(parser.length_bins, vec![(750, 0), (1000, 0), (1500, 0)]);
+        assert_eq!(parser.sorted_lengths, vec![258, 227]);
+    }
+
+    #[test]
+    fn test_custom_parameters() {
+        let params = ContigSummaryParameters {
+            thresholds: vec![200, 250],
+            genome_size: Some(1000),
+            ..Default::default()
+        };
+        let mut parser = ContigSummary::with_parameters(&params);
+        parser.summarize(
+            Path::new("tests/files/contigs/contigs1.fa"),
+            &ContigFmt::Auto,
+        );
+        assert_eq!(parser.length_bins, vec![(200, 2), (250, 1)]);
+        assert_eq!(parser.nstats.l50, 1);
+        assert_eq!(parser.nstats.ng50, None);
+        assert!(parser.per_contig.is_empty());
+    }
+
+    #[test]
+    fn test_contig_stats() {
+        let stats = ContigStats::new("contig_1 len=10", "ACGNNnTacg");
+        assert_eq!(stats.name, "contig_1");
+        assert_eq!(stats.length, 10);
+        assert_eq!(stats.gc_content, 0.4);
+        assert_eq!(stats.n_count, 3);
+        assert_eq!(stats.longest_n_run, 3);
+        assert_eq!(stats.soft_masked, 0.4);
     }
 }
@@ -0,0 +1,134 @@
+//! Pairwise distances between aligned sequences.
+//!
+//! Distances are uncorrected p-distances. A site is only compared
+//! when both sequences have an unambiguous character at the site.
+//! Gaps, missing data, and ambiguous characters are ignored.
+use indexmap::IndexMap;
+
+use crate::helper::types::{DataType, SeqMatrix};
+
+/// Compute the uncorrected p-distance between two aligned sequences.
+/// Returns `None` if the sequences do not share any comparable sites.
+///
+/// # Example
+/// ```
+/// use segul::helper::types::DataType;
+/// use segul::stats::distance;
+///
+/// let dist = distance::p_distance("ATGC", "ATGA", &DataType::Dna);
+/// assert_eq!(dist, Some(0.25));
+/// ```
+pub fn p_distance(seq_a: &str, seq_b: &str, datatype: &DataType) -> Option<f64> {
+    let mut compared = 0;
+    let mut differences = 0;
+    seq_a
+        .bytes()
+        .zip(seq_b.bytes())
+        .filter(|(a, b)| is_comparable(a, datatype) && is_comparable(b, datatype))
+        .for_each(|(a, b)| {
+            compared += 1;
+            if !a.eq_ignore_ascii_case(&b) {
+                differences += 1;
+            }
+        });
+
+    if compared == 0 {
+        None
+    } else {
+        Some(differences as f64 / compared as f64)
+    }
+}
+
+/// Compute the mean pairwise distance of each sequence
+/// to all other sequences in an alignment.
+/// The value is `None` if the sequence does not share
+/// any comparable sites with the other sequences.
+///
+/// # Example
+/// ```
+/// use indexmap::IndexMap;
+/// use segul::helper::types::DataType;
+/// use segul::stats::distance;
+///
+/// let mut matrix = IndexMap::new();
+/// matrix.insert(String::from("ABC"), String::from("ATGC"));
+/// matrix.insert(String::from("ABD"), String::from("ATGC"));
+/// matrix.insert(String::from("ABE"), String::from("ATGA"));
+/// let distances = distance::mean_pairwise_distance(&matrix, &DataType::Dna);
+/// assert_eq!(distances.get("ABE"), Some(&Some(0.25)));
+/// ```
+pub fn mean_pairwise_distance(
+    matrix: &SeqMatrix,
+    datatype: &DataType,
+) -> IndexMap<String, Option<f64>> {
+    let sequences: Vec<&str> = matrix.values().map(|s| s.as_str()).collect();
+    let ntax = sequences.len();
+    let mut sums = vec![0.0; ntax];
+    let mut counts = vec![0usize; ntax];
+    for i in 0..ntax {
+        for j in (i + 1)..ntax {
+            if let Some(dist) = p_distance(sequences[i], sequences[j], datatype) {
+                sums[i] += dist;
+                sums[j] += dist;
+                counts[i] += 1;
+                counts[j] += 1;
+            }
+        }
+    }
+
+    matrix
+        .keys()
+        .enumerate()
+        .map(|(idx, id)| {
+            let mean = if counts[idx] > 0 {
+                Some(sums[idx] / counts[idx] as f64)
+            } else {
+                None
+            };
+            (id.to_string(), mean)
+        })
+        .collect()
+}
+
+fn is_comparable(ch: &u8, datatype: &DataType) -> bool {
+    match datatype {
+        DataType::Aa => !b"XBZJU?-.~*".contains(&ch.to_ascii_uppercase()),
+        _ => b"ACGT".contains(&ch.to_ascii_uppercase()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_p_distance_ignore_missing() {
+        let dist = p_distance("AT-C?N", "ATGAAA", &DataType::Dna);
+        assert_eq!(Some(1.0 / 3.0), dist);
+    }
+
+    #[test]
+    fn test_p_distance_no_overlap() {
+        let dist = p_distance("AT----", "--GCAA", &DataType::Dna);
+        assert_eq!(None, dist);
+    }
+
+    #[test]
+    fn test_p_distance_case_insensitive() {
+        let dist = p_distance("atgc", "ATGC", &DataType::Dna);
+        assert_eq!(Some(0.0), dist);
+    }
+
+    #[test]
+    fn test_mean_pairwise_distance() {
+        let mut matrix = IndexMap::new();
+        matrix.insert(String::from("A"), String::from("AAAA"));
+        matrix.insert(String::from("B"), String::from("AAAA"));
+        matrix.insert(String::from("C"), String::from("TTTT"));
+        matrix.insert(String::from("D"), String::from("----"));
+        let distances = mean_pairwise_distance(&matrix, &DataType::Dna);
+        assert_eq!(Some(&Some(0.5)), distances.get("A"));
+        assert_eq!(Some(&Some(1.0)), distances.get("C"));
+        assert_eq!(Some(&None), distances.get("D"));
+    }
+}
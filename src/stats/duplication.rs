@@ -0,0 +1,139 @@
+//! Read duplication and overrepresented sequences
+//!
+//! Duplication is estimated by exact-sequence hashing of the first reads
+//! in a file, similar to FastQC. Sequences found in the first reads
+//! are counted across the whole file to find overrepresented sequences.
+use ahash::AHashMap as HashMap;
+
+/// Number of reads used to estimate the duplicate rate
+pub const DEFAULT_SAMPLED_READS: usize = 100_000;
+/// Number of overrepresented sequences to report
+pub const DEFAULT_TOP_SEQUENCES: usize = 20;
+/// Minimum proportion of reads to consider a sequence overrepresented
+const MIN_OVERREPRESENTED: f64 = 0.001;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverrepresentedSeq {
+    pub sequence: String,
+    pub count: usize,
+    /// Proportion of all reads in the file
+    pub proportion: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReadDuplication {
+    /// Number of reads used to estimate the duplicate rate
+    pub sampled_reads: usize,
+    /// Total number of reads in the file
+    pub read_count: usize,
+    /// Number of overrepresented sequences to report
+    top_sequences: usize,
+    /// Counts of sequences found in the sampled reads
+    counts: HashMap<Vec<u8>, usize>,
+    /// Number of distinct sequences in the sampled reads
+    unique_reads: usize,
+}
+
+impl Default for ReadDuplication {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLED_READS, DEFAULT_TOP_SEQUENCES)
+    }
+}
+
+impl ReadDuplication {
+    pub fn new(sampled_reads: usize, top_sequences: usize) -> Self {
+        Self {
+            sampled_reads,
+            read_count: 0,
+            top_sequences,
+            counts: HashMap::new(),
+            unique_reads: 0,
+        }
+    }
+
+    /// Add a read sequence.
+    /// New sequences are only tracked in the sampled reads.
+    pub fn update(&mut self, sequence: &[u8]) {
+        self.read_count += 1;
+        if self.read_count <= self.sampled_reads {
+            let count = self
+                .counts
+                .entry(sequence.to_ascii_uppercase())
+                .or_insert(0);
+            if *count == 0 {
+                self.unique_reads += 1;
+            }
+            *count += 1;
+        } else if let Some(count) = self.counts.get_mut(&sequence.to_ascii_uppercase()) {
+            *count += 1;
+        }
+    }
+
+    /// Number of distinct sequences in the sampled reads.
+    pub fn unique_reads(&self) -> usize {
+        self.unique_reads
+    }
+
+    /// Proportion of sampled reads that duplicate another read.
+    pub fn duplicate_rate(&self) -> f64 {
+        let sampled = self.read_count.min(self.sampled_reads);
+        if sampled == 0 {
+            return 0.0;
+        }
+        1.0 - self.unique_reads as f64 / sampled as f64
+    }
+
+    /// Most frequent sequences found in at least 0.1% of all reads,
+    /// sorted by count in descending order.
+    pub fn overrepresented(&self) -> Vec<OverrepresentedSeq> {
+        if self.read_count == 0 {
+            return Vec::new();
+        }
+        let mut sequences: Vec<(&Vec<u8>, &usize)> = self
+            .counts
+            .iter()
+            .filter(|(_, count)| **count > 1)
+            .filter(|(_, count)| **count as f64 / self.read_count as f64 >= MIN_OVERREPRESENTED)
+            .collect();
+        sequences.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        sequences
+            .into_iter()
+            .take(self.top_sequences)
+            .map(|(sequence, count)| OverrepresentedSeq {
+                sequence: String::from_utf8_lossy(sequence).to_string(),
+                count: *count,
+                proportion: *count as f64 / self.read_count as f64,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_rate() {
+        let mut dup = ReadDuplication::new(4, 2);
+        [b"ACGT", b"acgt", b"TTTT", b"ACGT", b"GGGG", b"GGGG"]
+            .iter()
+            .for_each(|seq| dup.update(*seq));
+        assert_eq!(6, dup.read_count);
+        assert_eq!(2, dup.unique_reads());
+        // Two of the four sampled reads are duplicates.
+        assert_eq!(0.5, dup.duplicate_rate());
+        let top = dup.overrepresented();
+        assert_eq!(1, top.len());
+        // GGGG is not in the sampled reads.
+        assert_eq!("ACGT", top[0].sequence);
+        assert_eq!(3, top[0].count);
+        assert_eq!(0.5, top[0].proportion);
+    }
+
+    #[test]
+    fn test_empty() {
+        let dup = ReadDuplication::default();
+        assert_eq!(0.0, dup.duplicate_rate());
+        assert!(dup.overrepresented().is_empty());
+    }
+}
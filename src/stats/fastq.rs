@@ -1,4 +1,4 @@
-//! Fastq parser only for Illumina 1.8+ and Sanger quality scores
+//! Fastq parser for Phred+33 (Illumina 1.8+ and Sanger) and Phred+64 quality scores
 
 use std::{
     collections::BTreeMap,
@@ -6,18 +6,22 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use noodles::fastq::io::Reader;
+use noodles::fastq::{Record, io::Reader};
 
 use crate::{
     helper::{
         files,
-        types::{SeqReadFmt, infer_raw_input_auto},
+        finder::ReadPair,
+        types::{QScoreEncoding, SeqReadFmt, infer_raw_input_auto},
     },
-    parser::qscores::QScoreParser,
-    stats::read::ReadRecord,
+    parser::qscores::{QScoreEncodingDetector, QScoreParser},
+    stats::read::{ReadDistribution, ReadRecord},
 };
 
-use super::{qscores::ReadQScore, read::ReadSummary};
+use super::{duplication::ReadDuplication, qscores::ReadQScore, read::ReadSummary};
+
+/// Number of reads scanned to detect the quality score encoding
+const ENCODING_DETECTION_READS: usize = 10_000;
 
 macro_rules! summarize_reads {
     ($self: ident, $record: ident, $sequence: ident) => {
@@ -102,12 +106,15 @@ pub struct FastqSummary {
     pub reads: ReadRecord,
     pub read_summary: ReadSummary,
     pub qscores: ReadQScore,
+    /// Quality score encoding.
+    /// Auto is replaced by the detected encoding.
+    pub encoding: QScoreEncoding,
 }
 
 impl SeqRead for FastqSummary {}
 
 impl FastqSummary {
-    pub fn new(path: &Path) -> Self {
+    pub fn new(path: &Path, encoding: &QScoreEncoding) -> Self {
         Self {
             path: path.to_path_buf(),
             file_name: path
@@ -119,26 +126,57 @@ impl FastqSummary {
             reads: ReadRecord::new(),
             read_summary: ReadSummary::new(),
             qscores: ReadQScore::new(),
+            encoding: *encoding,
         }
     }
 
     pub fn summarize(&mut self, file_fmt: &SeqReadFmt) {
         let input_fmt = self.parse_input_fmt(&self.path, file_fmt);
+        self.resolve_encoding(input_fmt);
         compute_stats!(self, input_fmt, compute_default);
     }
 
     pub fn summarize_map(&mut self, file_fmt: &SeqReadFmt) -> FastqMappedRead {
         let input_fmt = self.parse_input_fmt(&self.path, file_fmt);
+        self.resolve_encoding(input_fmt);
         compute_stats!(self, input_fmt, compute_mapped)
     }
 
+    /// Detect the quality score encoding if it is Auto.
+    /// Call it before summarizing records one by one.
+    pub fn detect_encoding(&mut self, file_fmt: &SeqReadFmt) {
+        let input_fmt = self.parse_input_fmt(&self.path, file_fmt);
+        self.resolve_encoding(input_fmt);
+    }
+
+    /// Add a read record to the summary.
+    pub fn summarize_record(&mut self, record: &Record) {
+        summarize_reads!(self, record, sequence);
+        summarize_qscores!(self, record, qrecord);
+    }
+
+    /// Add a read record to the summary and the per-position records.
+    pub fn summarize_mapped_record(&mut self, map_records: &mut FastqMappedRead, record: &Record) {
+        summarize_reads!(self, record, sequence);
+        map_records.duplication.update(sequence);
+        map_records.distribution.update(sequence);
+        // Map reads to their index
+        self.map_reads(map_records, sequence);
+        // Map quality scores to their index
+        summarize_qscores!(self, record, qrecord);
+        self.map_qscores(map_records, qrecord);
+    }
+
+    fn resolve_encoding(&mut self, input_fmt: SeqReadFmt) {
+        if self.encoding == QScoreEncoding::Auto {
+            self.encoding = detect_qscore_encoding(&self.path, &input_fmt);
+        }
+    }
+
     fn compute_default<R: BufRead>(&mut self, buff: &mut R) {
         let mut reader = Reader::new(buff);
         reader.records().for_each(|r| match r {
-            Ok(record) => {
-                summarize_reads!(self, record, sequence);
-                summarize_qscores!(self, record, qrecord);
-            }
+            Ok(record) => self.summarize_record(&record),
             Err(e) => {
                 log::error!("Error parsing fastq record: {}", e);
             }
@@ -150,14 +188,7 @@ impl FastqSummary {
         let mut map_records = FastqMappedRead::new(&self.path);
 
         reader.records().for_each(|r| match r {
-            Ok(record) => {
-                summarize_reads!(self, record, sequence);
-                // Map reads to their index
-                self.map_reads(&mut map_records, sequence);
-                // Map quality scores to their index
-                summarize_qscores!(self, record, qrecord);
-                self.map_qscores(&mut map_records, qrecord);
-            }
+            Ok(record) => self.summarize_mapped_record(&mut map_records, &record),
             Err(e) => {
                 log::error!("Error parsing fastq record: {}", e);
             }
@@ -197,7 +228,7 @@ impl FastqSummary {
 
     fn parse_qscores(&self, qscore: &[u8]) -> Vec<u8> {
         let mut qscores = Vec::with_capacity(qscore.len());
-        let parser = QScoreParser::new(qscore);
+        let parser = QScoreParser::new(qscore, &self.encoding);
         parser.into_iter().for_each(|q| {
             if let Some(q) = q {
                 qscores.push(q);
@@ -208,11 +239,154 @@ impl FastqSummary {
     }
 }
 
+/// Consistency check between the mates of paired-end reads.
+///
+/// Mates are compared record by record. Read names are compared
+/// without the `/1` and `/2` mate suffixes.
+pub struct FastqPairCheck {
+    pub sample: String,
+    pub read_1: PathBuf,
+    pub read_2: PathBuf,
+    pub read_1_count: usize,
+    pub read_2_count: usize,
+    /// Number of mate records with different read names
+    pub name_mismatches: usize,
+    /// 1-based index of the first mate record with different read names
+    pub first_mismatch: Option<usize>,
+}
+
+impl FastqPairCheck {
+    pub fn new(pair: &ReadPair) -> Self {
+        Self {
+            sample: pair.sample.clone(),
+            read_1: pair.read_1.clone(),
+            read_2: pair.read_2.clone(),
+            read_1_count: 0,
+            read_2_count: 0,
+            name_mismatches: 0,
+            first_mismatch: None,
+        }
+    }
+
+    pub fn check(&mut self, file_fmt: &SeqReadFmt) {
+        self.check_with(file_fmt, |_| (), |_| ());
+    }
+
+    /// Check the mates and pass each record to `on_read_1` or `on_read_2`.
+    /// Use it to summarize the mates in the same pass as the check.
+    pub fn check_with<F1, F2>(
+        &mut self,
+        file_fmt: &SeqReadFmt,
+        mut on_read_1: F1,
+        mut on_read_2: F2,
+    ) where
+        F1: FnMut(&Record),
+        F2: FnMut(&Record),
+    {
+        let read_1 = open_reads(&self.read_1, file_fmt);
+        let read_2 = open_reads(&self.read_2, file_fmt);
+        let mut records_1 = Reader::new(read_1);
+        let mut records_2 = Reader::new(read_2);
+        let mut records_1 = records_1.records();
+        let mut records_2 = records_2.records();
+        loop {
+            let record_1 = self.next_record(records_1.next(), &self.read_1);
+            let record_2 = self.next_record(records_2.next(), &self.read_2);
+            if let Some(record) = &record_1 {
+                on_read_1(record);
+            }
+            if let Some(record) = &record_2 {
+                on_read_2(record);
+            }
+            match (record_1, record_2) {
+                (None, None) => break,
+                (Some(record_1), Some(record_2)) => {
+                    self.read_1_count += 1;
+                    self.read_2_count += 1;
+                    if mate_name(record_1.name()) != mate_name(record_2.name()) {
+                        self.name_mismatches += 1;
+                        self.first_mismatch.get_or_insert(self.read_1_count);
+                    }
+                }
+                (Some(_), None) => self.read_1_count += 1,
+                (None, Some(_)) => self.read_2_count += 1,
+            }
+        }
+    }
+
+    /// Returns true if both mates have the same number of reads.
+    pub fn is_count_match(&self) -> bool {
+        self.read_1_count == self.read_2_count
+    }
+
+    /// Returns true if both mates have the same number of reads
+    /// and the same read names.
+    pub fn is_consistent(&self) -> bool {
+        self.is_count_match() && self.name_mismatches == 0
+    }
+
+    // Stop reading a mate at the first invalid record.
+    // A truncated mate file ends with an invalid record.
+    fn next_record(&self, record: Option<std::io::Result<Record>>, path: &Path) -> Option<Record> {
+        match record? {
+            Ok(record) => Some(record),
+            Err(e) => {
+                log::error!("Error parsing fastq record in {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+}
+
+/// Open a plain or gzip compressed FASTQ file.
+/// In Auto, the format is inferred from the file extension.
+pub fn open_reads(path: &Path, file_fmt: &SeqReadFmt) -> Box<dyn BufRead> {
+    let input_fmt = match file_fmt {
+        SeqReadFmt::Auto => infer_raw_input_auto(path),
+        _ => *file_fmt,
+    };
+    match input_fmt {
+        SeqReadFmt::Fastq => Box::new(files::open_file(path)),
+        SeqReadFmt::Gzip => Box::new(files::decode_gzip(path)),
+        SeqReadFmt::Auto => unreachable!("Unsupported input format"),
+    }
+}
+
+/// Detect the quality score encoding of a FASTQ file
+/// by scanning the quality scores of the first reads.
+/// Stop early once the scores are only found in Phred+33.
+pub fn detect_qscore_encoding(path: &Path, file_fmt: &SeqReadFmt) -> QScoreEncoding {
+    let mut reader = Reader::new(open_reads(path, file_fmt));
+    let mut detector = QScoreEncodingDetector::new();
+    for record in reader.records().take(ENCODING_DETECTION_READS) {
+        match record {
+            Ok(record) => detector.update(record.quality_scores()),
+            Err(e) => log::error!("Error parsing fastq record: {}", e),
+        }
+        if detector.is_phred33() {
+            break;
+        }
+    }
+    detector.encoding()
+}
+
+// Read name without the mate suffix.
+fn mate_name(name: &[u8]) -> &[u8] {
+    match name {
+        [name @ .., b'/', b'1' | b'2'] => name,
+        _ => name,
+    }
+}
+
 /// Data structure for storing mapped read records
 pub struct FastqMappedRead {
     pub file_path: PathBuf,
     pub reads: BTreeMap<i32, ReadRecord>,
     pub qscores: BTreeMap<i32, ReadQScore>,
+    /// Duplication and overrepresented sequences
+    pub duplication: ReadDuplication,
+    /// Read length and per-read GC content distributions
+    pub distribution: ReadDistribution,
 }
 
 impl FastqMappedRead {
@@ -222,6 +396,8 @@ impl FastqMappedRead {
             file_path: file_path.to_path_buf(),
             reads: BTreeMap::new(),
             qscores: BTreeMap::new(),
+            duplication: ReadDuplication::default(),
+            distribution: ReadDistribution::new(),
         }
     }
 }
@@ -241,4 +417,53 @@ mod test {
         assert_eq!(summary.path, PathBuf::from(path));
         assert_eq!(summary.read_count, 2);
     }
+
+    #[test]
+    fn test_pair_check() {
+        let pair = ReadPair {
+            sample: String::from("read"),
+            read_1: PathBuf::from("tests/files/raw/read_1.fastq"),
+            read_2: PathBuf::from("tests/files/raw/read_2.fastq"),
+        };
+        let mut check = FastqPairCheck::new(&pair);
+        check.check(&SeqReadFmt::Auto);
+        assert_eq!((2, 2), (check.read_1_count, check.read_2_count));
+        assert!(check.is_consistent());
+    }
+
+    #[test]
+    fn test_pair_check_truncated() {
+        let pair = ReadPair {
+            sample: String::from("sample"),
+            read_1: PathBuf::from("tests/files/paired/sample_R1.fastq"),
+            read_2: PathBuf::from("tests/files/paired/sample_R2.fastq"),
+        };
+        let mut check = FastqPairCheck::new(&pair);
+        check.check(&SeqReadFmt::Fastq);
+        assert_eq!((3, 2), (check.read_1_count, check.read_2_count));
+        assert!(!check.is_count_match());
+        assert_eq!(1, check.name_mismatches);
+        assert_eq!(Some(2), check.first_mismatch);
+    }
+
+    #[test]
+    fn test_mate_name() {
+        assert_eq!(b"read", mate_name(b"read/1"));
+        assert_eq!(b"read", mate_name(b"read/2"));
+        assert_eq!(b"read/3", mate_name(b"read/3"));
+    }
+
+    #[test]
+    fn test_phred64_summary() {
+        let phred33 = Path::new("tests/files/raw/read_1.fastq");
+        let phred64 = Path::new("tests/files/phred64/read_1.fastq");
+        let mut summary = FastqSummary::new(phred33, &QScoreEncoding::Auto);
+        summary.summarize(&SeqReadFmt::Fastq);
+        assert_eq!(QScoreEncoding::Phred33, summary.encoding);
+        let mut summary_64 = FastqSummary::new(phred64, &QScoreEncoding::Auto);
+        let mapped = summary_64.summarize_map(&SeqReadFmt::Fastq);
+        assert_eq!(QScoreEncoding::Phred64, summary_64.encoding);
+        assert_eq!(summary.qscores, summary_64.qscores);
+        assert_eq!(40, mapped.qscores[&1].stats.max.unwrap_or(0));
+    }
 }
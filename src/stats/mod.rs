@@ -7,6 +7,8 @@
 //! 3. Sequence Alignment in FASTA, NEXUS, and relaxed-PHYLIP formats
 pub mod common;
 pub mod contigs;
+pub mod distance;
+pub mod duplication;
 pub mod fastq;
 pub mod qscores;
 pub mod read;
@@ -1,4 +1,6 @@
 //! Data types for all FASTQ records
+use std::collections::BTreeMap;
+
 use super::common::StreamStats;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -109,6 +111,37 @@ impl ReadRecord {
     }
 }
 
+/// Distributions of read length and per-read GC content
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReadDistribution {
+    /// Read counts per read length
+    pub lengths: BTreeMap<usize, usize>,
+    /// Read counts per GC content in percent (0-100).
+    /// GC content is computed from A, C, G, and T bases only.
+    /// Reads without these bases are not counted.
+    pub gc_percents: BTreeMap<usize, usize>,
+}
+
+impl ReadDistribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a read to the distributions
+    pub fn update(&mut self, read: &[u8]) {
+        *self.lengths.entry(read.len()).or_insert(0) += 1;
+        let (gc, acgt) = read.iter().fold((0, 0), |(gc, acgt), base| match base {
+            b'G' | b'g' | b'C' | b'c' => (gc + 1, acgt + 1),
+            b'A' | b'a' | b'T' | b't' => (gc, acgt + 1),
+            _ => (gc, acgt),
+        });
+        if acgt > 0 {
+            let percent = (gc as f64 * 100.0 / acgt as f64).round() as usize;
+            *self.gc_percents.entry(percent).or_insert(0) += 1;
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -148,4 +181,19 @@ mod test {
         assert_eq!(summary.at_content, 0.4);
         assert_eq!(summary.n_content, 0.1);
     }
+
+    #[test]
+    fn test_read_distribution() {
+        let mut dist = ReadDistribution::new();
+        [b"ATGC".as_slice(), b"GGGC", b"ATGCN", b"NN", b"AT"]
+            .iter()
+            .for_each(|r| dist.update(r));
+        assert_eq!(Some(&2), dist.lengths.get(&4));
+        assert_eq!(Some(&2), dist.lengths.get(&2));
+        assert_eq!(Some(&2), dist.gc_percents.get(&50));
+        assert_eq!(Some(&1), dist.gc_percents.get(&100));
+        assert_eq!(Some(&1), dist.gc_percents.get(&0));
+        // Reads with only Ns are not counted.
+        assert_eq!(4, dist.gc_percents.values().sum::<usize>());
+    }
 }
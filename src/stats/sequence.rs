@@ -419,6 +419,22 @@ impl Sites {
         missing_data_per_site
     }
 
+    /// Get conserved sites.
+    /// Return the sorted site location of sites with a single character state.
+    /// Ambiguous and missing data characters are ignored.
+    /// Character states are case-insensitive, so soft-masked bases match.
+    /// Sites without any non-ambiguous characters are not conserved.
+    pub fn get_conserved_sites(&self, matrix: &SeqMatrix, datatype: &DataType) -> Vec<usize> {
+        let site_matrix = self.index_site_without_ambiguity(matrix, datatype);
+        let mut conserved: Vec<usize> = site_matrix
+            .iter()
+            .filter(|(_, site)| site.iter().all(|ch| ch.eq_ignore_ascii_case(&site[0])))
+            .map(|(idx, _)| *idx)
+            .collect();
+        conserved.sort_unstable();
+        conserved
+    }
+
     /// Get the sites as is
     /// Return the site location including missing data characters.
     pub fn index_site_with_missing_data(&self, matrix: &SeqMatrix) -> SiteMap {
@@ -768,6 +784,20 @@ mod test {
         assert_eq!(1, site.pars_inf);
     }
 
+    #[test]
+    fn conserved_sites_test() {
+        let id = ["ABC", "ABE", "ABF"];
+        let seq = ["ATNA--", "ATCAT-", "A?GNT-"];
+        let mat = get_matrix(&id, &seq);
+        let sites = Sites::default().get_conserved_sites(&mat, &DNA);
+        assert_eq!(vec![0, 1, 3, 4], sites);
+
+        let seq = ["aTNa--", "AtCAt-", "a?GNT-"];
+        let mat = get_matrix(&id, &seq);
+        let sites = Sites::default().get_conserved_sites(&mat, &DNA);
+        assert_eq!(vec![0, 1, 3, 4], sites);
+    }
+
     #[test]
     fn count_variable_sites_test() {
         let id = ["ABC", "ABE", "ABF", "ABD"];
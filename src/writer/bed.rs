@@ -0,0 +1,51 @@
+//! BED writer module
+use std::io::prelude::*;
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::parser::bed::BedRecord;
+use crate::writer::FileWriter;
+
+impl FileWriter for BedWriter<'_> {}
+
+/// Write BED records to a file.
+/// Only the first six columns are written.
+/// Optional columns are written up to the last column with a value.
+pub struct BedWriter<'a> {
+    output: &'a Path,
+    records: &'a [BedRecord],
+}
+
+impl<'a> BedWriter<'a> {
+    pub fn new(output: &'a Path, records: &'a [BedRecord]) -> Self {
+        Self { output, records }
+    }
+
+    pub fn write(&self) -> Result<()> {
+        let mut writer = self.create_output_file(self.output)?;
+        for rec in self.records {
+            write!(
+                writer,
+                "{}\t{}\t{}",
+                rec.chrom, rec.chrom_start, rec.chrom_end
+            )?;
+            if rec.name.is_none() && rec.score.is_none() && rec.strand.is_none() {
+                writeln!(writer)?;
+                continue;
+            }
+            write!(writer, "\t{}", rec.name.as_deref().unwrap_or("."))?;
+            if rec.score.is_none() && rec.strand.is_none() {
+                writeln!(writer)?;
+                continue;
+            }
+            write!(writer, "\t{}", rec.score.unwrap_or(0))?;
+            match &rec.strand {
+                Some(strand) => writeln!(writer, "\t{}", strand)?,
+                None => writeln!(writer)?,
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -1,14 +1,17 @@
-//! Contig summary writer
+//! Contig summary and sequence writer
 use std::{
     io::{Result, Write},
     path::{Path, PathBuf},
 };
 
+use crate::parser::fasta::Records;
 use crate::stats::contigs::ContigSummary;
 
 use super::FileWriter;
 
 const OUTPUT_SUFFIX: &str = "contig-summary";
+const CUMULATIVE_SUFFIX: &str = "contig-cumulative-length";
+const PER_CONTIG_SUFFIX: &str = "per-contig-summary";
 const DEFAULT_EXTENSION: &str = "csv";
 
 pub struct ContigSummaryWriter<'a> {
@@ -28,46 +31,95 @@ impl<'a> ContigSummaryWriter<'a> {
         }
     }
 
+    /// Write the summary and the cumulative length table.
     pub fn write(&self) -> Result<()> {
-        let output_path = self.create_final_output_path();
+        let output_path = self.create_final_output_path(OUTPUT_SUFFIX);
         let mut writer = self
             .create_output_file(&output_path)
             .expect("Failed writing to file");
         self.write_records(&mut writer)?;
         writer.flush()?;
+        let output_path = self.create_final_output_path(CUMULATIVE_SUFFIX);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        self.write_cumulative_lengths(&mut writer)?;
+        writer.flush()?;
         Ok(())
     }
 
-    fn create_final_output_path(&self) -> PathBuf {
+    /// Write statistics of each contig.
+    /// Requires summaries with `per_contig` statistics.
+    pub fn write_per_contig(&self) -> Result<()> {
+        let output_path = self.create_final_output_path(PER_CONTIG_SUFFIX);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        writeln!(
+            writer,
+            "file_path,contig_name,contig,length,GC_content,N_count,longest_N_run,soft_masked"
+        )?;
+        for summary in self.summary {
+            for contig in &summary.per_contig {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{},{}",
+                    summary.file_path,
+                    summary.contig_name,
+                    contig.name,
+                    contig.length,
+                    contig.gc_content,
+                    contig.n_count,
+                    contig.longest_n_run,
+                    contig.soft_masked
+                )?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn create_final_output_path(&self, suffix: &str) -> PathBuf {
         match self.prefix {
             Some(prefix) => {
-                let file_name = format!("{}_{}", prefix, OUTPUT_SUFFIX);
+                let file_name = format!("{}_{}", prefix, suffix);
                 self.output
                     .join(file_name)
                     .with_extension(DEFAULT_EXTENSION)
             }
-            None => self
-                .output
-                .join(OUTPUT_SUFFIX)
-                .with_extension(DEFAULT_EXTENSION),
+            None => self.output.join(suffix).with_extension(DEFAULT_EXTENSION),
         }
     }
 
     fn write_records<W: Write>(&self, writer: &mut W) -> Result<()> {
+        // All summaries use the same length thresholds.
+        let bins: Vec<String> = self
+            .summary
+            .first()
+            .map(|summary| {
+                summary
+                    .length_bins
+                    .iter()
+                    .map(|(threshold, _)| format!("contig_{}", threshold))
+                    .collect()
+            })
+            .unwrap_or_default();
         writeln!(
             writer,
             "file_path,contig_name,\
             contig_count,base_count,nucleotide,\
             GC_content,AT_content,\
             sum,min,max,mean,median,\
-            N50,N75,N90,\
-            contig_750,contig_1000,contig_1500,\
-            G_Count,C_Count,A_Count,T_Count"
+            N50,N75,N90,L50,L75,L90,NG50,auN,{}\
+            G_Count,C_Count,A_Count,T_Count",
+            bins.iter()
+                .map(|bin| format!("{},", bin))
+                .collect::<String>()
         )?;
         for summary in self.summary {
-            writeln!(
+            write!(
                 writer,
-                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},",
                 summary.file_path,
                 summary.contig_name,
                 summary.contig_count,
@@ -83,16 +135,83 @@ impl<'a> ContigSummaryWriter<'a> {
                 summary.nstats.n50,
                 summary.nstats.n75,
                 summary.nstats.n90,
-                summary.contig750,
-                summary.contig1000,
-                summary.contig1500,
-                summary.g_count,
-                summary.c_count,
-                summary.a_count,
-                summary.t_count,
+                summary.nstats.l50,
+                summary.nstats.l75,
+                summary.nstats.l90,
+                summary
+                    .nstats
+                    .ng50
+                    .map(|ng50| ng50.to_string())
+                    .unwrap_or_default(),
+                summary.nstats.aun,
+            )?;
+            for (_, count) in &summary.length_bins {
+                write!(writer, "{},", count)?;
+            }
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                summary.g_count, summary.c_count, summary.a_count, summary.t_count,
             )?;
         }
 
         Ok(())
     }
+
+    // Contigs sorted by length with their cumulative lengths
+    // to plot the cumulative length curve.
+    fn write_cumulative_lengths<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writeln!(
+            writer,
+            "file_path,contig_name,rank,length,cumulative_length,cumulative_proportion"
+        )?;
+        for summary in self.summary {
+            let mut cumulative_length = 0;
+            for (i, length) in summary.sorted_lengths.iter().enumerate() {
+                cumulative_length += length;
+                let proportion = if summary.stats.sum == 0 {
+                    0.0
+                } else {
+                    cumulative_length as f64 / summary.stats.sum as f64
+                };
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{}",
+                    summary.file_path,
+                    summary.contig_name,
+                    i + 1,
+                    length,
+                    cumulative_length,
+                    proportion
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Write contig sequences to a FASTA file.
+pub struct ContigWriter<'a> {
+    output: &'a Path,
+}
+
+impl FileWriter for ContigWriter<'_> {}
+
+impl<'a> ContigWriter<'a> {
+    pub fn new(output: &'a Path) -> Self {
+        Self { output }
+    }
+
+    pub fn write(&self, contigs: &[Records]) -> Result<()> {
+        let mut writer = self
+            .create_output_file(self.output)
+            .expect("Failed writing a fasta formatted file");
+        for contig in contigs {
+            writeln!(writer, ">{}", contig.id)?;
+            writeln!(writer, "{}", contig.seq)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
 }
@@ -0,0 +1,89 @@
+//! FASTQ writer module
+//!
+//! Write FASTQ records to gzip compressed files.
+//! Read sequences can also be written as gzip compressed FASTA.
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, prelude::*};
+use std::path::Path;
+
+use anyhow::Result;
+use flate2::{Compression, write::GzEncoder};
+use noodles::fasta;
+use noodles::fastq::{Record, io::Writer};
+
+use super::create_parent_directory;
+
+/// Write FASTQ records to a new gzip compressed file.
+pub struct FastqWriter {
+    writer: Writer<GzEncoder<BufWriter<File>>>,
+}
+
+impl FastqWriter {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        create_parent_directory(path)?;
+        let file = OpenOptions::new().write(true).create_new(true).open(path);
+        match file {
+            Ok(file) => {
+                let encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+                Ok(Self {
+                    writer: Writer::new(encoder),
+                })
+            }
+            Err(error) => panic!("Failed writing to {}: {}", path.display(), error),
+        }
+    }
+
+    pub fn write_record(&mut self, record: &Record) -> Result<()> {
+        self.writer.write_record(record)?;
+        Ok(())
+    }
+
+    /// Finish the gzip stream and flush the file.
+    pub fn finish(self) -> Result<()> {
+        let mut writer = self.writer.into_inner().finish()?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Write FASTQ records as FASTA to a new gzip compressed file.
+/// Quality scores are discarded.
+pub struct ReadFastaWriter {
+    writer: fasta::io::Writer<GzEncoder<BufWriter<File>>>,
+}
+
+impl ReadFastaWriter {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        create_parent_directory(path)?;
+        let file = OpenOptions::new().write(true).create_new(true).open(path);
+        match file {
+            Ok(file) => {
+                let encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+                Ok(Self {
+                    writer: fasta::io::Writer::new(encoder),
+                })
+            }
+            Err(error) => panic!("Failed writing to {}: {}", path.display(), error),
+        }
+    }
+
+    pub fn write_record(&mut self, record: &Record) -> Result<()> {
+        let description = if record.description().is_empty() {
+            None
+        } else {
+            Some(record.description().to_owned())
+        };
+        let definition = fasta::record::Definition::new(record.name().to_vec(), description);
+        let sequence = fasta::record::Sequence::from(record.sequence().to_vec());
+        self.writer
+            .write_record(&fasta::Record::new(definition, sequence))?;
+        Ok(())
+    }
+
+    /// Finish the gzip stream and flush the file.
+    pub fn finish(self) -> Result<()> {
+        let mut writer = self.writer.into_inner().finish()?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+//! MAF writer module
+//!
+//! Write MAF paragraphs parsed by `parser::maf` back to MAF format.
+//! In alignment blocks, the `q` and `i` lines are written after
+//! the `s` line with the same source. The `e` lines are written
+//! after all the `s` lines.
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, prelude::*};
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::parser::maf::{
+    MafAlignment, MafEmptyLine, MafHeader, MafInformation, MafParagraph, MafSequence, Quality,
+    TrackLine,
+};
+
+use super::create_parent_directory;
+
+/// Write MAF paragraphs to a writer.
+/// Each alignment block is followed by a blank line.
+pub struct MafWriter<W: Write> {
+    writer: W,
+}
+
+impl MafWriter<BufWriter<File>> {
+    /// Create a MAF writer to a new file.
+    pub fn from_path(path: &Path) -> Result<Self> {
+        create_parent_directory(path)?;
+        let file = OpenOptions::new().write(true).create_new(true).open(path);
+        match file {
+            Ok(file) => Ok(Self::new(BufWriter::new(file))),
+            Err(error) => panic!("Failed writing to {}: {}", path.display(), error),
+        }
+    }
+}
+
+impl<W: Write> MafWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write a paragraph as it is parsed by `MafReader`.
+    /// Empty and unknown paragraphs are skipped.
+    pub fn write_paragraph(&mut self, paragraph: &MafParagraph) -> Result<()> {
+        match paragraph {
+            MafParagraph::Header(header) => self.write_header(header),
+            MafParagraph::Track(track) => self.write_track(track),
+            MafParagraph::Comments(comments) => self.write_comments(comments),
+            MafParagraph::Alignment(aln) => self.write_alignment(aln),
+            MafParagraph::Empty | MafParagraph::Unknown => Ok(()),
+        }
+    }
+
+    /// Write the `##maf` header line.
+    pub fn write_header(&mut self, header: &MafHeader) -> Result<()> {
+        write!(self.writer, "##maf version={}", header.version)?;
+        if let Some(scoring) = &header.scoring {
+            write!(self.writer, " scoring={}", scoring)?;
+        }
+        if let Some(program) = &header.program {
+            // The parser keeps the program tag as it is.
+            if program.starts_with("program=") {
+                write!(self.writer, " {}", program)?;
+            } else {
+                write!(self.writer, " program={}", program)?;
+            }
+        }
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    /// Write the browser track line.
+    pub fn write_track(&mut self, track: &TrackLine) -> Result<()> {
+        write!(self.writer, "track name={}", track.name)?;
+        if let Some(description) = &track.description {
+            write!(self.writer, " description=\"{}\"", description)?;
+        }
+        if let Some(frames) = &track.frames {
+            write!(self.writer, " frames={}", frames)?;
+        }
+        if track.maf_dot {
+            write!(self.writer, " mafDot=on")?;
+        }
+        if let Some(visibility) = &track.visibility {
+            write!(self.writer, " visibility={}", visibility)?;
+        }
+        if let Some(species_order) = &track.species_order {
+            write!(self.writer, " speciesOrder=\"{}\"", species_order)?;
+        }
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    /// Write comment lines. A leading `#` is added if missing.
+    pub fn write_comments(&mut self, comments: &str) -> Result<()> {
+        comments
+            .lines()
+            .try_for_each(|line| match line.starts_with('#') {
+                true => writeln!(self.writer, "{}", line),
+                false => writeln!(self.writer, "# {}", line),
+            })?;
+        Ok(())
+    }
+
+    /// Write an alignment block followed by a blank line.
+    pub fn write_alignment(&mut self, aln: &MafAlignment) -> Result<()> {
+        match aln.score {
+            Some(score) => writeln!(self.writer, "a score={}", score)?,
+            None => writeln!(self.writer, "a")?,
+        }
+        for seq in &aln.sequences {
+            self.write_sequence(seq)?;
+            aln.qualities
+                .iter()
+                .filter(|q| q.source == seq.source)
+                .try_for_each(|q| self.write_quality(q))?;
+            aln.information
+                .iter()
+                .filter(|i| i.source == seq.source)
+                .try_for_each(|i| self.write_information(i))?;
+        }
+        aln.empty_lines
+            .iter()
+            .try_for_each(|e| self.write_empty_line(e))?;
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_sequence(&mut self, seq: &MafSequence) -> Result<()> {
+        writeln!(
+            self.writer,
+            "s {} {} {} {} {} {}",
+            seq.source,
+            seq.start,
+            seq.size,
+            seq.strand,
+            seq.src_size,
+            String::from_utf8_lossy(&seq.text)
+        )?;
+        Ok(())
+    }
+
+    fn write_quality(&mut self, quality: &Quality) -> Result<()> {
+        let values: String = quality.values.iter().collect();
+        writeln!(self.writer, "q {} {}", quality.source, values)?;
+        Ok(())
+    }
+
+    fn write_information(&mut self, info: &MafInformation) -> Result<()> {
+        writeln!(
+            self.writer,
+            "i {} {} {} {} {}",
+            info.source, info.left_status, info.left_count, info.right_status, info.right_count
+        )?;
+        Ok(())
+    }
+
+    fn write_empty_line(&mut self, empty: &MafEmptyLine) -> Result<()> {
+        writeln!(
+            self.writer,
+            "e {} {} {} {} {} {}",
+            empty.source, empty.start, empty.size, empty.strand, empty.src_size, empty.status
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::maf::MafReader;
+
+    #[test]
+    fn test_write_alignment() {
+        let input = "##maf version=1 scoring=test\n\
+            a score=10\n\
+            s ref.chr1 10 4 + 30 AC-GT\n\
+            s sp1.scf1 0 5 - 100 ACTGT\n\n";
+        let mut writer = MafWriter::new(Vec::new());
+        MafReader::new(input.as_bytes())
+            .for_each(|paragraph| writer.write_paragraph(&paragraph).unwrap());
+        assert_eq!(input, String::from_utf8(writer.writer).unwrap());
+    }
+
+    #[test]
+    fn test_write_block_lines() {
+        let input = "track name=euArc visibility=pack\n\
+            ##maf version=1 scoring=tba.v8\n\
+            # tba.v8 (((human chimp) baboon) (mouse rat))\n\
+            a score=23262\n\
+            s hg18.chr7 27578828 38 + 158545518 AAA-GGGAATGTTAACCAAATGA---ATTGTCTCTTACGGTG\n\
+            s panTro1.chr6 28741140 38 + 161576975 AAA-GGGAATGTTAACCAAATGA---ATTGTCTCTTACGGTG\n\
+            q panTro1.chr6 99999999999999999999999999999999999999999999\n\
+            i panTro1.chr6 C 0 C 0\n\
+            e mm4.chr6 53310102 13316 + 151104725 I\n\n";
+        let mut writer = MafWriter::new(Vec::new());
+        MafReader::new(input.as_bytes())
+            .for_each(|paragraph| writer.write_paragraph(&paragraph).unwrap());
+        assert_eq!(input, String::from_utf8(writer.writer).unwrap());
+    }
+
+    #[test]
+    fn test_round_trip_file() {
+        let input = std::fs::read_to_string("tests/files/maf/simple.maf").unwrap();
+        let mut writer = MafWriter::new(Vec::new());
+        MafReader::new(input.as_bytes())
+            .for_each(|paragraph| writer.write_paragraph(&paragraph).unwrap());
+        let output = String::from_utf8(writer.writer).unwrap();
+        // Written blocks are parsed back to the same blocks.
+        let parse = |maf: &str| -> Vec<(Option<f64>, Vec<String>)> {
+            MafReader::new(maf.as_bytes())
+                .filter_map(|paragraph| match paragraph {
+                    MafParagraph::Alignment(aln) => Some((
+                        aln.score,
+                        aln.sequences
+                            .iter()
+                            .map(|s| String::from_utf8_lossy(&s.text).to_string())
+                            .collect(),
+                    )),
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(parse(&input), parse(&output));
+        assert_eq!(3, parse(&output).len());
+    }
+}
@@ -6,10 +6,18 @@
 //! 2. `PartitionWriter`: write partition data to a file.
 //! 3. `SummaryWriter`: write summary data to a file.
 //! 4. `ContigSummaryWriter`: write contig summary data to a file.
+//!    `ContigWriter` writes contig sequences to a FASTA file.
 //! 5. `ArchiveWriter`: write archive data to a file.
 //! 6. `ReadSummaryWriter`: write read summary data to a file.
+//! 7. `BedWriter`: write BED records to a file.
+//! 8. `MafWriter`: write MAF alignment blocks to a file.
+//! 9. `FastqWriter`: write FASTQ records to a gzip compressed file.
+//! 10. `ReadFastaWriter`: write FASTQ records as FASTA to a gzip compressed file.
 pub mod archive;
+pub mod bed;
 pub mod contigs;
+pub mod fastq;
+pub mod maf;
 pub mod partition;
 pub mod read;
 pub mod sequences;
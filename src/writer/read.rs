@@ -6,13 +6,18 @@ use zip::write::FileOptions;
 
 use super::FileWriter;
 
-use crate::stats::fastq::{FastqMappedRead, FastqSummary, FastqSummaryMin};
+use crate::stats::fastq::{FastqMappedRead, FastqPairCheck, FastqSummary, FastqSummaryMin};
 use crate::stats::qscores::ReadQScore;
 use crate::stats::read::ReadRecord;
 
 const DEFAULT_READ_SUFFIX: &str = "default-read-summary";
 const MINIMAL_READ_SUFFIX: &str = "minimal-read-summary";
 const PER_READ_SUFFIX: &str = "read-pos-summary";
+const PAIRED_READ_SUFFIX: &str = "paired-read-summary";
+const DUPLICATION_SUFFIX: &str = "read-duplication";
+const OVERREPRESENTED_SUFFIX: &str = "overrepresented-sequences";
+const LENGTH_DIST_SUFFIX: &str = "read-length-distribution";
+const GC_DIST_SUFFIX: &str = "read-gc-distribution";
 
 const DEFAULT_EXTENSION: &str = "csv";
 const ZIP_EXTENSION: &str = "zip";
@@ -26,6 +31,23 @@ N_content,\
 G,C,A,T,N,\
 low_QScore,mean_QScore,min_QScore,max_QScore";
 
+const PAIRED_READ_HEADER: &str = "sample,read_1_path,read_2_path,\
+read_1_count,read_2_count,read_count_match,\
+name_mismatches,first_name_mismatch";
+
+const PAIRED_STATS_HEADER: &str = "read_count,base_count,mean_read_length,\
+GC_content,AT_content,N_content,\
+low_QScore,mean_QScore";
+
+const DUPLICATION_HEADER: &str = "file_path,file_name,read_count,\
+sampled_reads,unique_reads,duplicate_rate";
+
+const OVERREPRESENTED_HEADER: &str = "file_path,file_name,sequence,count,proportion";
+
+const LENGTH_DIST_HEADER: &str = "file_path,file_name,read_length,read_count";
+
+const GC_DIST_HEADER: &str = "file_path,file_name,GC_percent,read_count";
+
 const PER_READ_HEADER: &str = "index,G,C,A,T\
 ,proportion_G,proportion_C,proportion_A,proportion_T\
 ,mean_QScore,min_QScore,max_QScore";
@@ -72,6 +94,186 @@ impl<'a> ReadSummaryWriter<'a> {
         Ok(())
     }
 
+    /// Write the mate consistency of paired-end reads.
+    /// One row per sample.
+    pub fn write_paired_check_only(&self, pairs: &[FastqPairCheck]) -> Result<()> {
+        let output_path = self.create_final_output_path(PAIRED_READ_SUFFIX);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        writeln!(writer, "{}", PAIRED_READ_HEADER)?;
+        for pair in pairs {
+            writeln!(writer, "{}", self.format_pair_check(pair))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the mate consistency and the summary statistics
+    /// of both mates combined. One row per sample.
+    pub fn write_paired(&self, pairs: &[FastqPairCheck], records: &[FastqSummary]) -> Result<()> {
+        let output_path = self.create_final_output_path(PAIRED_READ_SUFFIX);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        writeln!(writer, "{},{}", PAIRED_READ_HEADER, PAIRED_STATS_HEADER)?;
+        let find_record = |path: &Path| {
+            records
+                .iter()
+                .find(|rec| rec.path == path)
+                .expect("Failed finding read summary of a mate")
+        };
+        for pair in pairs {
+            let read_1 = find_record(&pair.read_1);
+            let read_2 = find_record(&pair.read_2);
+            let read_count = read_1.reads.stats.count + read_2.reads.stats.count;
+            let base_count = read_1.reads.len + read_2.reads.len;
+            let gc_count = read_1.read_summary.gc_count + read_2.read_summary.gc_count;
+            let at_count = read_1.read_summary.at_count + read_2.read_summary.at_count;
+            let n_count = read_1.reads.n_count + read_2.reads.n_count;
+            let q_count = read_1.qscores.len + read_2.qscores.len;
+            let q_sum = read_1.qscores.q_sum + read_2.qscores.q_sum;
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{}",
+                self.format_pair_check(pair),
+                read_count,
+                base_count,
+                self.ratio(base_count, read_count),
+                self.ratio(gc_count, base_count),
+                self.ratio(at_count, base_count),
+                self.ratio(n_count, base_count),
+                read_1.qscores.low_q + read_2.qscores.low_q,
+                self.ratio(q_sum, q_count),
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the duplicate rate estimated from the first reads of each file.
+    pub fn write_duplication(&self, reads: &[FastqMappedRead]) -> Result<()> {
+        let output_path = self.create_final_output_path(DUPLICATION_SUFFIX);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        writeln!(writer, "{}", DUPLICATION_HEADER)?;
+        for read in reads {
+            let dup = &read.duplication;
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                read.file_path.display(),
+                self.file_name(&read.file_path),
+                dup.read_count,
+                dup.sampled_reads.min(dup.read_count),
+                dup.unique_reads(),
+                dup.duplicate_rate()
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the top overrepresented sequences of each file.
+    pub fn write_overrepresented(&self, reads: &[FastqMappedRead]) -> Result<()> {
+        let output_path = self.create_final_output_path(OVERREPRESENTED_SUFFIX);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        writeln!(writer, "{}", OVERREPRESENTED_HEADER)?;
+        for read in reads {
+            for seq in read.duplication.overrepresented() {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{}",
+                    read.file_path.display(),
+                    self.file_name(&read.file_path),
+                    seq.sequence,
+                    seq.count,
+                    seq.proportion
+                )?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Write the read length distribution of each file.
+    pub fn write_length_distribution(&self, reads: &[FastqMappedRead]) -> Result<()> {
+        self.write_distribution(reads, LENGTH_DIST_SUFFIX, LENGTH_DIST_HEADER, |read| {
+            &read.distribution.lengths
+        })
+    }
+
+    /// Write the per-read GC content distribution of each file.
+    pub fn write_gc_distribution(&self, reads: &[FastqMappedRead]) -> Result<()> {
+        self.write_distribution(reads, GC_DIST_SUFFIX, GC_DIST_HEADER, |read| {
+            &read.distribution.gc_percents
+        })
+    }
+
+    fn write_distribution<F>(
+        &self,
+        reads: &[FastqMappedRead],
+        suffix: &str,
+        header: &str,
+        distribution: F,
+    ) -> Result<()>
+    where
+        F: Fn(&FastqMappedRead) -> &BTreeMap<usize, usize>,
+    {
+        let output_path = self.create_final_output_path(suffix);
+        let mut writer = self
+            .create_output_file(&output_path)
+            .expect("Failed writing to file");
+        writeln!(writer, "{}", header)?;
+        for read in reads {
+            let file_name = self.file_name(&read.file_path);
+            for (value, count) in distribution(read) {
+                writeln!(
+                    writer,
+                    "{},{},{},{}",
+                    read.file_path.display(),
+                    file_name,
+                    value,
+                    count
+                )?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    // Empty mates have no reads to average over.
+    fn ratio(&self, numerator: usize, denominator: usize) -> f64 {
+        if denominator == 0 {
+            return 0.0;
+        }
+        numerator as f64 / denominator as f64
+    }
+
+    fn file_name(&self, path: &Path) -> String {
+        path.file_name()
+            .expect("Failed getting file name")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn format_pair_check(&self, pair: &FastqPairCheck) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            pair.sample,
+            pair.read_1.display(),
+            pair.read_2.display(),
+            pair.read_1_count,
+            pair.read_2_count,
+            pair.is_count_match(),
+            pair.name_mismatches,
+            pair.first_mismatch.map_or(String::new(), |i| i.to_string()),
+        )
+    }
+
     fn create_final_output_path(&self, suffix: &str) -> PathBuf {
         match self.prefix {
             Some(prefix) => {
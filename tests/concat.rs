@@ -27,3 +27,65 @@ fn test_concat_nexus_part() {
 
     tmp_dir.close().unwrap();
 }
+
+#[test]
+fn test_concat_append() {
+    initiate_cmd!(cmd, "align", "concat", "tests/files/alignments/", tmp_dir);
+    let dir = env::current_dir().unwrap().join("tests/files/concat");
+    // Rename the existing locus to avoid a conflict with the input loci.
+    let partition = tmp_dir.path().join("supermatrix_partition.part");
+    std::fs::write(&partition, "DNA, old_locus = 1-8\n").unwrap();
+    cmd.arg("--append-to")
+        .arg(dir.join("supermatrix.fas"))
+        .arg("--append-partition")
+        .arg(&partition)
+        .arg("--append-partition-format")
+        .arg("raxml")
+        .arg("--partition-format")
+        .arg("raxml")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Align-Concat");
+    let partition = std::fs::read_dir(&output)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "txt"))
+        .unwrap();
+    let partition = std::fs::read_to_string(partition).unwrap();
+    assert!(partition.starts_with("DNA, old_locus = 1-8\n"));
+    test_results!(1, tmp_dir, "Align-Concat", Nexus);
+}
+
+#[test]
+fn test_concat_append_without_partition() {
+    initiate_cmd!(cmd, "align", "concat", "tests/files/alignments/", tmp_dir);
+    let dir = env::current_dir().unwrap().join("tests/files/concat");
+    cmd.arg("--append-to")
+        .arg(dir.join("supermatrix.fas"))
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("--append-partition"));
+    tmp_dir.close().unwrap();
+}
+
+#[test]
+fn test_concat_append_inside_output() {
+    initiate_cmd!(cmd, "align", "concat", "tests/files/alignments/", tmp_dir);
+    let output = tmp_dir.path().join("Align-Concat");
+    std::fs::create_dir(&output).unwrap();
+    let supermatrix = output.join("supermatrix.fas");
+    let partition = output.join("supermatrix_partition.txt");
+    std::fs::copy("tests/files/concat/supermatrix.fas", &supermatrix).unwrap();
+    std::fs::copy("tests/files/concat/supermatrix_partition.txt", &partition).unwrap();
+    cmd.arg("--append-to")
+        .arg(&supermatrix)
+        .arg("--append-partition")
+        .arg(&partition)
+        .arg("--force")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("inside the output directory"));
+    assert!(supermatrix.exists());
+    assert!(partition.exists());
+    tmp_dir.close().unwrap();
+}
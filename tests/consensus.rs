@@ -0,0 +1,25 @@
+mod utils;
+
+use std::env;
+
+use predicates::Predicate;
+
+use segul::helper::finder::SeqFileFinder;
+use segul::helper::types::InputFmt;
+
+#[test]
+fn test_consensus_regex() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/consensus/");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("sequence")
+        .arg("consensus")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--re=^([A-Za-z]+_[a-z]+)")
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Sequence-Consensus", Nexus);
+}
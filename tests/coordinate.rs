@@ -0,0 +1,48 @@
+mod utils;
+
+use std::env;
+
+#[test]
+fn test_coordinate_map() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/slice/");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("align")
+        .arg("coordinate")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--reference")
+        .arg("ref")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Align-Coordinate/chr1_map.csv");
+    let map = std::fs::read_to_string(output).unwrap();
+    assert_eq!(13, map.lines().count());
+    assert!(map.contains("5,\n"));
+}
+
+#[test]
+fn test_coordinate_sites() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/slice/");
+    let sites = tmp_dir.path().join("sites.txt");
+    std::fs::write(&sites, "1\n2\n5\n7\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("align")
+        .arg("coordinate")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--reference")
+        .arg("ref")
+        .arg("--sites")
+        .arg(sites)
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Align-Coordinate/sites.bed");
+    let bed = std::fs::read_to_string(output).unwrap();
+    assert_eq!("chr1\t0\t2\nchr1\t4\t5\n", bed);
+}
@@ -0,0 +1,353 @@
+mod utils;
+
+use std::env;
+
+use predicates::Predicate;
+
+use segul::helper::finder::SeqFileFinder;
+use segul::helper::types::InputFmt;
+
+#[test]
+fn test_maf_stitch() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = env::current_dir()
+        .unwrap()
+        .join("tests/files/stitch/blocks.maf");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("stitch")
+        .arg("-i")
+        .arg(input)
+        .arg("--ref-species")
+        .arg("ref")
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Maf-Stitch", Fasta);
+}
+
+#[test]
+fn test_maf_stitch_bed() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/stitch");
+    let bed = tmp_dir.path().join("regions.bed");
+    std::fs::write(&bed, "chr1\t2\t12\tr1\nchr1\t20\t25\tr2\nchr2\t0\t3\tr3\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("stitch")
+        .arg("-i")
+        .arg(dir.join("blocks.maf"))
+        .arg("--ref-species")
+        .arg("ref")
+        .arg("--bed")
+        .arg(bed)
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Maf-Stitch", Fasta);
+}
+
+#[test]
+fn test_maf_convert_bed_overlap() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/stitch");
+    let bed = tmp_dir.path().join("regions.bed");
+    std::fs::write(&bed, "chr1\t2\t12\tr1\nchr1\t13\t16\tr2\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("maf")
+        .arg("-i")
+        .arg(dir.join("blocks.maf"))
+        .arg("--reference")
+        .arg(bed)
+        .arg("--from-bed")
+        .arg("--species-names")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Genomic-Convert");
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &output.join("r1-chr1-2-12.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    assert_eq!("AA??????AC-", matrix["ref"]);
+    assert_eq!("TA??????ACT", matrix["sp1"]);
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &output.join("r2-chr1-13-16.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    assert_eq!("TTG", matrix["ref"]);
+    test_results!(2, tmp_dir, "Genomic-Convert", Fasta);
+}
+
+#[test]
+fn test_maf_convert_bed_source_name() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/stitch");
+    let bed = tmp_dir.path().join("regions.bed");
+    std::fs::write(&bed, "ref.chr1\t2\t12\tr1\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("maf")
+        .arg("-i")
+        .arg(dir.join("blocks.maf"))
+        .arg("--reference")
+        .arg(bed)
+        .arg("--from-bed")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Genomic-Convert");
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &output.join("r1-ref_chr1-2-12.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    // Sequences are named by the full MAF source name by default.
+    assert_eq!("AA??????AC-", matrix["ref.chr1"]);
+    assert_eq!("TA?????????", matrix["sp1.scf2"]);
+    assert_eq!("????????ACT", matrix["sp1.scf1"]);
+    assert!(!matrix.contains_key("sp1"));
+}
+
+#[test]
+fn test_maf_convert_bed_stale_index() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let maf = tmp_dir.path().join("blocks.maf");
+    std::fs::copy("tests/files/stitch/blocks.maf", &maf).unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("index")
+        .arg("-i")
+        .arg(&maf)
+        .assert()
+        .success();
+    // Shift all block offsets after indexing.
+    let content = std::fs::read_to_string(&maf).unwrap();
+    std::fs::write(&maf, format!("# edited\n{}", content)).unwrap();
+
+    let bed = tmp_dir.path().join("regions.bed");
+    std::fs::write(&bed, "chr1\t13\t16\tr2\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("maf")
+        .arg("-i")
+        .arg(&maf)
+        .arg("--reference")
+        .arg(bed)
+        .arg("--from-bed")
+        .assert()
+        .success();
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &tmp_dir
+            .path()
+            .join("Genomic-Convert")
+            .join("r2-chr1-13-16.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    assert_eq!("TTG", matrix["ref.chr1"]);
+}
+
+#[test]
+fn test_maf_summary() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = env::current_dir()
+        .unwrap()
+        .join("tests/files/stitch/blocks.maf");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("summary")
+        .arg("-i")
+        .arg(input)
+        .arg("--ref-species")
+        .arg("ref")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Maf-Summary");
+    let summary = std::fs::read_to_string(output.join("maf_summary.csv")).unwrap();
+    assert_eq!(2, summary.lines().count());
+    let coverage = std::fs::read_to_string(output.join("maf_species_coverage.csv")).unwrap();
+    // Header, ref, sp1, and sp2
+    assert_eq!(4, coverage.lines().count());
+}
+
+#[test]
+fn test_maf_filter_projection() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = env::current_dir()
+        .unwrap()
+        .join("tests/files/stitch/blocks.maf");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("filter")
+        .arg("-i")
+        .arg(input)
+        .arg("--min-score")
+        .arg("8")
+        .arg("--project")
+        .arg("ref")
+        .arg("sp1")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Maf-Filter/blocks.maf");
+    let maf = std::fs::read_to_string(output).unwrap();
+    assert!(maf.starts_with("##maf version=1 scoring=test\n"));
+    assert_eq!(3, maf.lines().filter(|l| l.starts_with('a')).count());
+    assert!(!maf.contains("sp2"));
+}
+
+#[test]
+fn test_maf_rename() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = env::current_dir()
+        .unwrap()
+        .join("tests/files/stitch/blocks.maf");
+    let names = tmp_dir.path().join("names.csv");
+    std::fs::write(&names, "origin,destination\nsp1,species_1\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("rename")
+        .arg("-i")
+        .arg(input)
+        .arg("--replace-id")
+        .arg(names)
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Maf-Rename/blocks.maf");
+    let maf = std::fs::read_to_string(output).unwrap();
+    assert!(maf.contains("s species_1.scf1 0 5 + 100 ACTGT\n"));
+    assert!(!maf.contains("sp1."));
+}
+
+#[test]
+fn test_maf_from_align() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = tmp_dir.path().join("r1-chr1-2-12.fas");
+    std::fs::write(
+        &input,
+        ">ref\nAA??????AC-\n>sp1\nTA??????ACT\n>sp2\n-A?????????\n",
+    )
+    .unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("from-align")
+        .arg("-i")
+        .arg(input)
+        .arg("--reference")
+        .arg("ref")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Maf-From-Align/r1-chr1-2-12.maf");
+    let maf = std::fs::read_to_string(output).unwrap();
+    assert!(maf.contains("s ref.chr1 2 2 + 12 AA\n"));
+    assert!(maf.contains("s ref.chr1 10 2 + 12 AC-\n"));
+    assert!(maf.contains("s sp1 2 3 + 5 ACT\n"));
+    assert_eq!(2, maf.lines().filter(|l| l.starts_with('a')).count());
+}
+
+fn write_bgzf(input: &std::path::Path, output: &std::path::Path) {
+    use std::io::Write;
+    let file = std::fs::File::create(output).unwrap();
+    let mut writer = noodles::bgzf::io::Writer::new(file);
+    writer.write_all(&std::fs::read(input).unwrap()).unwrap();
+    writer.finish().unwrap();
+}
+
+#[test]
+fn test_maf_stitch_gzip() {
+    use std::io::Write;
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = env::current_dir()
+        .unwrap()
+        .join("tests/files/stitch/blocks.maf");
+    let gzip = tmp_dir.path().join("blocks.maf.gz");
+    let mut encoder = flate2::write::GzEncoder::new(
+        std::fs::File::create(&gzip).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder.write_all(&std::fs::read(input).unwrap()).unwrap();
+    encoder.finish().unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("stitch")
+        .arg("-i")
+        .arg(gzip)
+        .arg("--ref-species")
+        .arg("ref")
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Maf-Stitch", Fasta);
+}
+
+#[test]
+fn test_maf_convert_bed_indexed() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let input = env::current_dir()
+        .unwrap()
+        .join("tests/files/stitch/blocks.maf");
+    let bgzf = tmp_dir.path().join("blocks.maf.bgz");
+    write_bgzf(&input, &bgzf);
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("index")
+        .arg("-i")
+        .arg(&bgzf)
+        .assert()
+        .success();
+    assert!(tmp_dir.path().join("blocks.maf.bgz.idx").exists());
+
+    let bed = tmp_dir.path().join("regions.bed");
+    std::fs::write(&bed, "chr1\t2\t12\tr1\nchr1\t13\t16\tr2\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("maf")
+        .arg("-i")
+        .arg(&bgzf)
+        .arg("--reference")
+        .arg(bed)
+        .arg("--from-bed")
+        .arg("--species-names")
+        .assert()
+        .success();
+    let output = tmp_dir.path().join("Genomic-Convert");
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &output.join("r1-chr1-2-12.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    assert_eq!("AA??????AC-", matrix["ref"]);
+    assert_eq!("TA??????ACT", matrix["sp1"]);
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &output.join("r2-chr1-13-16.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    assert_eq!("TTG", matrix["ref"]);
+
+    // BED files with the full MAF source name match the index.
+    let bed = tmp_dir.path().join("source.bed");
+    std::fs::write(&bed, "ref.chr1\t13\t16\tr3\n").unwrap();
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("maf")
+        .arg("maf")
+        .arg("-i")
+        .arg(&bgzf)
+        .arg("--reference")
+        .arg(bed)
+        .arg("--from-bed")
+        .arg("-o")
+        .arg("Source-Convert")
+        .arg("--species-names")
+        .assert()
+        .success();
+    let (matrix, _) = segul::helper::sequence::SeqParser::new(
+        &tmp_dir
+            .path()
+            .join("Source-Convert")
+            .join("r3-ref_chr1-13-16.fas"),
+        &segul::helper::types::DataType::Dna,
+    )
+    .parse(&InputFmt::Fasta);
+    assert_eq!("TTG", matrix["ref"]);
+    // Blocks outside the regions are not read with the index.
+    assert!(!output.join("missing-refs").exists());
+}
@@ -0,0 +1,27 @@
+mod utils;
+
+use std::env;
+
+use predicates::Predicate;
+
+use segul::helper::finder::SeqFileFinder;
+use segul::helper::types::InputFmt;
+
+#[test]
+fn test_outlier_remove() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/outlier/");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("sequence")
+        .arg("outlier")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--remove")
+        .assert()
+        .success();
+    let summary = tmp_dir.path().join("Sequence-Outlier/outlier_summary.csv");
+    assert!(predicates::path::is_file().eval(&summary));
+    test_results!(2, tmp_dir, "Sequence-Outlier/filtered_alignments", Nexus);
+}
@@ -0,0 +1,27 @@
+mod utils;
+
+use std::env;
+
+use predicates::Predicate;
+
+use segul::helper::finder::SeqFileFinder;
+use segul::helper::types::InputFmt;
+
+#[test]
+fn test_select_species() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/select/");
+    let map = dir.join("species.csv");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("sequence")
+        .arg("select")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--map")
+        .arg(map)
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Sequence-Select", Nexus);
+}
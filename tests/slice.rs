@@ -0,0 +1,48 @@
+mod utils;
+
+use std::env;
+
+use predicates::Predicate;
+
+use segul::helper::finder::SeqFileFinder;
+use segul::helper::types::InputFmt;
+
+#[test]
+fn test_slice_range() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/slice/");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("align")
+        .arg("slice")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--range")
+        .arg("1-4")
+        .arg("5-12")
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Align-Slice", Nexus);
+}
+
+#[test]
+fn test_slice_bed() {
+    let tmp_dir = utils::create_tmp_dir().unwrap();
+    let dir = env::current_dir().unwrap().join("tests/files/slice/");
+    let bed = dir.join("regions.bed");
+    let mut cmd = utils::segul(tmp_dir.path());
+    cmd.arg("align")
+        .arg("slice")
+        .arg("-d")
+        .arg(dir)
+        .arg("-f")
+        .arg("fasta")
+        .arg("--bed")
+        .arg(bed)
+        .arg("--reference")
+        .arg("ref")
+        .assert()
+        .success();
+    test_results!(2, tmp_dir, "Align-Slice", Nexus);
+}